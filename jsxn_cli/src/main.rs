@@ -0,0 +1,330 @@
+//! Command-line tool to check, format and convert JSXN
+
+use jsxn::{
+    codegen::{self, CodegenOptions, Runtime},
+    format::{self, FormatOptions},
+    html::{self, RenderOptions},
+    jsxn::{self as parser, JsxnValue},
+    options::ParserOptions,
+    profile::{self, Profile},
+    tagged,
+};
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process,
+};
+
+const USAGE: &str = "\
+Usage: jsxn <command> [options] [files...]
+
+Reads each file, or standard input if there are none or a file is `-`.
+
+Commands:
+    check      Validate the input and print every error found
+    fmt        Format files in place, or standard input to standard output
+    to-json    Convert to JSON
+    to-html    Render to HTML
+    to-js      Generate JavaScript that creates the elements
+
+Options:
+    --relaxed              Accept comments, trailing commas, JSON5, template
+                           variables and scalar roots
+    --check                fmt: report unformatted input instead of formatting it
+    --profile <profile>    to-json: current, react, jsxn or tagged
+    --pretty               to-json, to-html: indent the output
+    --runtime <runtime>    to-js: classic or automatic
+    --module               to-js: generate a module exporting the elements
+
+Exits with 1 if any input has errors or is not formatted, and 2 if the
+arguments are invalid.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Check,
+    Fmt,
+    ToJson,
+    ToHtml,
+    ToJs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JsonProfile {
+    Profile(Profile),
+    Tagged,
+}
+
+struct Args {
+    command: Command,
+    files: Vec<String>,
+    options: ParserOptions,
+    check: bool,
+    profile: JsonProfile,
+    pretty: bool,
+    runtime: Runtime,
+    module: bool,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let command = match args.next().as_deref() {
+        Some("check") => Command::Check,
+        Some("fmt") => Command::Fmt,
+        Some("to-json") => Command::ToJson,
+        Some("to-html") => Command::ToHtml,
+        Some("to-js") => Command::ToJs,
+        Some(command) => return Err(format!("unknown command `{}`", command)),
+        None => return Err(String::from("missing command")),
+    };
+    let mut parsed = Args {
+        command,
+        files: vec![],
+        options: ParserOptions::STRICT,
+        check: false,
+        profile: JsonProfile::Profile(Profile::Current),
+        pretty: false,
+        runtime: Runtime::Classic,
+        module: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let allowed = match arg.as_str() {
+            "--relaxed" => {
+                parsed.options = ParserOptions::RELAXED;
+                true
+            }
+            "--check" => {
+                parsed.check = true;
+                command == Command::Fmt
+            }
+            "--profile" => {
+                parsed.profile = match args.next().as_deref() {
+                    Some("current") => JsonProfile::Profile(Profile::Current),
+                    Some("react") => JsonProfile::Profile(Profile::React),
+                    Some("jsxn") => JsonProfile::Profile(Profile::Jsxn),
+                    Some("tagged") => JsonProfile::Tagged,
+                    Some(profile) => return Err(format!("unknown profile `{}`", profile)),
+                    None => return Err(String::from("missing profile")),
+                };
+                command == Command::ToJson
+            }
+            "--pretty" => {
+                parsed.pretty = true;
+                command == Command::ToJson || command == Command::ToHtml
+            }
+            "--runtime" => {
+                parsed.runtime = match args.next().as_deref() {
+                    Some("classic") => Runtime::Classic,
+                    Some("automatic") => Runtime::Automatic,
+                    Some(runtime) => return Err(format!("unknown runtime `{}`", runtime)),
+                    None => return Err(String::from("missing runtime")),
+                };
+                command == Command::ToJs
+            }
+            "--module" => {
+                parsed.module = true;
+                command == Command::ToJs
+            }
+            "-" => {
+                parsed.files.push(arg.clone());
+                true
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ => {
+                parsed.files.push(arg.clone());
+                true
+            }
+        };
+        if !allowed {
+            return Err(format!("`{}` is not an option of this command", arg));
+        }
+    }
+    Ok(parsed)
+}
+
+/// An input, which is a file unless `path` is `None`
+struct Input {
+    name: String,
+    path: Option<String>,
+    bytes: Vec<u8>,
+}
+
+fn read_inputs(files: &[String]) -> Result<Vec<Input>, String> {
+    if files.is_empty() {
+        return read_inputs(&[String::from("-")]);
+    }
+    files
+        .iter()
+        .map(|file| {
+            let mut bytes = vec![];
+            if file == "-" {
+                io::stdin()
+                    .read_to_end(&mut bytes)
+                    .map_err(|err| format!("<stdin>: {}", err))?;
+                Ok(Input {
+                    name: String::from("<stdin>"),
+                    path: None,
+                    bytes,
+                })
+            } else {
+                bytes = fs::read(file).map_err(|err| format!("{}: {}", file, err))?;
+                Ok(Input {
+                    name: file.clone(),
+                    path: Some(file.clone()),
+                    bytes,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Run the command on every input, returning whether they all succeeded
+fn run(args: &Args, inputs: &[Input]) -> bool {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut succeeded = true;
+    for input in inputs {
+        let result = if args.command == Command::Check {
+            check(args, input)
+        } else {
+            convert(args, input, &mut stdout)
+        };
+        if let Err(message) = result {
+            eprintln!("{}: {}", input.name, message);
+            succeeded = false;
+        }
+    }
+    succeeded
+}
+
+/// Print every error of an input, recovering from syntax errors to find
+/// the ones after them
+fn check(args: &Args, input: &Input) -> Result<(), String> {
+    let error = match parser::parse_bytes_with_options(&input.bytes, &args.options) {
+        Ok(_) => return Ok(()),
+        Err(error) => error,
+    };
+    let text = match std::str::from_utf8(&input.bytes) {
+        Ok(text) => text.trim_start_matches('\u{feff}'),
+        Err(_) => return Err(error.to_string()),
+    };
+    let (_, errors) = parser::parse_recovering_with_options(text, &args.options);
+    if errors.is_empty() {
+        return Err(error.to_string());
+    }
+    let (last, others) = errors.split_last().unwrap();
+    for error in others {
+        eprintln!("{}: {}", input.name, error);
+    }
+    Err(last.to_string())
+}
+
+fn convert<W: Write>(args: &Args, input: &Input, out: &mut W) -> Result<(), String> {
+    if args.command == Command::Fmt {
+        return fmt(args, input, out);
+    }
+    let value = parser::parse_bytes_with_options(&input.bytes, &args.options)
+        .map_err(|err| err.to_string())?;
+    let output = match args.command {
+        Command::ToJson => to_json(args, &value).map_err(|err| err.to_string())?,
+        Command::ToHtml => {
+            let options = if args.pretty {
+                RenderOptions::PRETTY
+            } else {
+                RenderOptions::COMPACT
+            };
+            html::render_jsxn_with_options(&value, &options) + "\n"
+        }
+        Command::ToJs => {
+            let options = CodegenOptions {
+                runtime: args.runtime,
+                ..CodegenOptions::CLASSIC
+            };
+            if args.module {
+                codegen::generate_module(&value, &options)
+            } else {
+                codegen::generate(&value, &options) + "\n"
+            }
+        }
+        Command::Check | Command::Fmt => unreachable!(),
+    };
+    out.write_all(output.as_bytes())
+        .map_err(|err| err.to_string())
+}
+
+fn to_json(args: &Args, value: &JsxnValue) -> serde_json::Result<String> {
+    let mut json = match (args.profile, args.pretty) {
+        (JsonProfile::Profile(profile), false) => {
+            serde_json::to_string(&profile::profiled(value, profile))?
+        }
+        (JsonProfile::Profile(profile), true) => {
+            serde_json::to_string_pretty(&profile::profiled(value, profile))?
+        }
+        (JsonProfile::Tagged, false) => serde_json::to_string(&tagged::tagged(value))?,
+        (JsonProfile::Tagged, true) => serde_json::to_string_pretty(&tagged::tagged(value))?,
+    };
+    json.push('\n');
+    Ok(json)
+}
+
+/// Format a file in place, or standard input to standard output. With
+/// `--check`, only report whether the input is already formatted. A byte
+/// order mark is kept, and not counted as a change.
+fn fmt<W: Write>(args: &Args, input: &Input, out: &mut W) -> Result<(), String> {
+    let text = match std::str::from_utf8(&input.bytes) {
+        Ok(text) => text,
+        Err(_) => {
+            return Err(
+                match parser::parse_bytes_with_options(&input.bytes, &args.options) {
+                    Ok(_) => String::from("only UTF-8 input can be formatted"),
+                    Err(err) => err.to_string(),
+                },
+            )
+        }
+    };
+    let (bom, text) = match text.strip_prefix('\u{feff}') {
+        Some(text) => ("\u{feff}", text),
+        None => ("", text),
+    };
+    let formatted = format::format_source(text, &args.options, &FormatOptions::DEFAULT)
+        .map_err(|err| err.to_string())?;
+    let unchanged = text == formatted;
+    if args.check {
+        return if unchanged {
+            Ok(())
+        } else {
+            Err(String::from("not formatted"))
+        };
+    }
+    let formatted = String::from(bom) + &formatted;
+    match &input.path {
+        Some(_) if unchanged => Ok(()),
+        Some(path) => fs::write(path, formatted).map_err(|err| err.to_string()),
+        None => out
+            .write_all(formatted.as_bytes())
+            .map_err(|err| err.to_string()),
+    }
+}
+
+fn main() {
+    if matches!(env::args().nth(1).as_deref(), Some("-h") | Some("--help")) {
+        println!("{}", USAGE);
+        return;
+    }
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    let inputs = match read_inputs(&args.files) {
+        Ok(inputs) => inputs,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    };
+    if !run(&args, &inputs) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn jsxn(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jsxn"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("jsxn-cli-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn check() {
+    let output = jsxn(&["check"], "<p>valid</p>");
+    assert!(output.status.success());
+    assert_eq!(stderr(&output), "");
+
+    let output = jsxn(&["check"], "<div></p>\n<br>");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("<stdin>: "));
+    assert!(stderr(&output).contains("line 1"));
+}
+
+#[test]
+fn fmt() {
+    let output = jsxn(&["fmt"], "<ul>  <li>one</li></ul>");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "<ul><li>one</li></ul>\n");
+
+    let output = jsxn(&["fmt", "--check"], "<ul>  <li>one</li></ul>");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr(&output), "<stdin>: not formatted\n");
+
+    let path = temp_file("fmt.jsxn", "{ \"a\": [1] }");
+    let file = path.to_str().unwrap();
+    assert!(jsxn(&["fmt", file], "").status.success());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\n  \"a\": [\n    1\n  ]\n}\n"
+    );
+    assert!(jsxn(&["fmt", "--check", file], "").status.success());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn fmt_byte_order_mark() {
+    let output = jsxn(&["fmt"], "\u{feff}<ul>  <li>one</li></ul>");
+    assert_eq!(stdout(&output), "\u{feff}<ul><li>one</li></ul>\n");
+
+    let path = temp_file("bom.jsxn", "\u{feff}<br />\n");
+    let file = path.to_str().unwrap();
+    assert!(jsxn(&["fmt", "--check", file], "").status.success());
+    fs::write(&path, "\u{feff}<br/>").unwrap();
+    assert!(jsxn(&["fmt", file], "").status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "\u{feff}<br />\n");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn convert() {
+    let output = jsxn(&["to-json"], "<p key=\"a\">hi</p>");
+    assert_eq!(
+        stdout(&output),
+        "{\"type\":\"p\",\"props\":{\"key\":\"a\"},\"children\":[\"hi\"]}\n"
+    );
+    let output = jsxn(&["to-json", "--profile", "react"], "<p key=\"a\">hi</p>");
+    assert_eq!(
+        stdout(&output),
+        "{\"$$typeof\":\"react.element\",\"type\":\"p\",\"key\":\"a\",\"ref\":null,\"props\":{\"children\":\"hi\"}}\n"
+    );
+    let output = jsxn(&["to-json", "--profile", "tagged"], "[null]");
+    assert_eq!(
+        stdout(&output),
+        "{\"kind\":\"array\",\"items\":[{\"kind\":\"null\"}]}\n"
+    );
+
+    let output = jsxn(&["to-html"], "<p className=\"a\">hi</p>");
+    assert_eq!(stdout(&output), "<p class=\"a\">hi</p>\n");
+
+    let output = jsxn(&["to-js", "--runtime", "automatic"], "<p>hi</p>");
+    assert_eq!(stdout(&output), "_jsx(\"p\", {children: \"hi\"})\n");
+
+    let output = jsxn(&["to-html", "--relaxed"], "<p>{$name}</p>");
+    assert!(output.status.success());
+    let output = jsxn(&["to-html"], "<p>{$name}</p>");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn invalid_arguments() {
+    assert_eq!(jsxn(&[], "").status.code(), Some(2));
+    assert_eq!(jsxn(&["lint"], "").status.code(), Some(2));
+    assert_eq!(jsxn(&["to-html", "--module"], "").status.code(), Some(2));
+    assert_eq!(
+        jsxn(&["to-json", "--profile", "xml"], "").status.code(),
+        Some(2)
+    );
+    assert_eq!(
+        jsxn(&["check", "does-not-exist.jsxn"], "").status.code(),
+        Some(1)
+    );
+    assert!(jsxn(&["--help"], "").status.success());
+}
@@ -0,0 +1,567 @@
+#![deny(missing_docs)]
+
+//! Macros for building JSXN trees in Rust, and parsing JSXN files at compile
+//! time
+
+use jsxn::{
+    json::{escape, JsonValue},
+    jsx::JsxValue,
+    jsxn::{self as parser, JsxnValue},
+    options::ParserOptions,
+};
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::quote;
+use std::{collections::BTreeSet, env, fs, path::PathBuf};
+use syn::{
+    braced, bracketed,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_macro_input, token, Ident, Lit, LitStr, Token,
+};
+
+/// Build a `jsxn::jsxn::JsxnValue` from JSXN written as Rust tokens,
+/// checking its syntax at compile time.
+///
+/// The syntax is that of JSXN, except that text is written as string
+/// literals, such as `<p>"Hello, world!"</p>`, since Rust tokens do not keep
+/// the whitespace of text. Strings that JSX text cannot hold become string
+/// expressions, as if written in braces.
+///
+/// Rust values are interpolated with `#name` or `#(expression)`, and
+/// converted with `jsxn::jsx::ToJsxValue`. Among children and as prop
+/// values, they are expressions, like `{#name}`, and among JSON values they
+/// are JSON values, or JSON values holding JSX values.
+#[proc_macro]
+pub fn jsxn(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as Root).0.into()
+}
+
+/// Parse a JSXN file at compile time, expanding to the construction of its
+/// `jsxn::jsxn::JsxnValue`, so that no parsing happens at runtime.
+///
+/// The path is relative to the directory of the crate's `Cargo.toml`, and
+/// syntax errors in the file are reported as compiler errors with their line
+/// and column in the file. The file is parsed with the strict options, unless
+/// `relaxed` follows the path, such as in
+/// `include_jsxn!("page.jsxn", relaxed)`. The crate is rebuilt when the file
+/// changes.
+#[proc_macro]
+pub fn include_jsxn(input: TokenStream) -> TokenStream {
+    let include = parse_macro_input!(input as Include);
+    match include.expand() {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+struct Include {
+    path: LitStr,
+    options: ParserOptions,
+}
+
+impl Parse for Include {
+    fn parse(input: ParseStream) -> syn::Result<Include> {
+        let path = input.parse()?;
+        let mut options = ParserOptions::STRICT;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            if !input.is_empty() {
+                let option = input.parse::<Ident>()?;
+                if option != "relaxed" {
+                    return Err(syn::Error::new(option.span(), "expected `relaxed`"));
+                }
+                options = ParserOptions::RELAXED;
+                input.parse::<Option<Token![,]>>()?;
+            }
+        }
+        Ok(Include { path, options })
+    }
+}
+
+impl Include {
+    fn expand(&self) -> syn::Result<TokenStream2> {
+        let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        let path = manifest_dir.join(self.path.value());
+        let error = |message| syn::Error::new(self.path.span(), message);
+        let bytes = fs::read(&path)
+            .map_err(|err| error(format!("cannot read {}: {}", path.display(), err)))?;
+        let value = parser::parse_bytes_with_options(&bytes, &self.options)
+            .map_err(|err| error(format!("{}: {}", self.path.value(), err)))?;
+        let tokens = jsxn_tokens(&value);
+        // Including the file makes the crate depend on it, so that it is
+        // rebuilt when the file changes
+        let path = path.to_string_lossy();
+        Ok(quote! {{
+            const _: &[u8] = ::std::include_bytes!(#path);
+            #tokens
+        }})
+    }
+}
+
+/// An expression constructing a parsed JSXN value
+fn jsxn_tokens(value: &JsxnValue) -> TokenStream2 {
+    match value {
+        JsxnValue::JsxValue(jsx) => {
+            let jsx = jsx_tokens(jsx);
+            quote!(::jsxn::jsxn::JsxnValue::JsxValue(#jsx))
+        }
+        JsxnValue::JsonValue(json) => {
+            let json = json_tokens(json);
+            quote!(::jsxn::jsxn::JsxnValue::JsonValue(#json))
+        }
+    }
+}
+
+fn jsx_tokens(value: &JsxValue) -> TokenStream2 {
+    match value {
+        JsxValue::JsxElement(element) => {
+            let name = element.r#type();
+            let props = element.props().iter().map(|(name, value)| {
+                let value = jsx_tokens(value);
+                quote!((::std::string::String::from(#name), #value))
+            });
+            let children = element.children().iter().map(jsx_tokens);
+            quote! {
+                ::jsxn::jsx::JsxValue::JsxElement(::jsxn::jsx::JsxElement::new(
+                    ::std::string::String::from(#name),
+                    ::std::vec![#(#props),*]
+                        .into_iter()
+                        .collect::<::std::collections::BTreeMap<_, _>>(),
+                    ::std::vec![#(#children),*],
+                ))
+            }
+        }
+        JsxValue::JsxFragment(fragment) => {
+            let children = fragment.children().iter().map(jsx_tokens);
+            quote! {
+                ::jsxn::jsx::JsxValue::JsxFragment(::jsxn::jsx::JsxFragment::new(
+                    ::std::vec![#(#children),*],
+                ))
+            }
+        }
+        JsxValue::JsonValue(json) => {
+            let json = json_tokens(json);
+            quote!(::jsxn::jsx::JsxValue::JsonValue(#json))
+        }
+        JsxValue::JsxExpression(jsx) => {
+            let jsx = jsx_tokens(jsx);
+            quote!(::jsxn::jsx::JsxValue::JsxExpression(::std::boxed::Box::new(#jsx)))
+        }
+        JsxValue::Variable(path) => quote! {
+            ::jsxn::jsx::JsxValue::Variable(::std::vec![
+                #(::std::string::String::from(#path)),*
+            ])
+        },
+        // Only the recovering parser inserts errors
+        JsxValue::Error(_) => unreachable!(),
+    }
+}
+
+fn json_tokens(value: &JsonValue) -> TokenStream2 {
+    match value {
+        // Strings are kept escaped, as they are in the parsed tree
+        JsonValue::Str(s) => quote!(::jsxn::json::JsonValue::Str(::std::string::String::from(#s))),
+        JsonValue::Boolean(boolean) => quote!(::jsxn::json::JsonValue::Boolean(#boolean)),
+        JsonValue::Num(num) => {
+            let num = if num.is_nan() {
+                quote!(::std::f64::NAN)
+            } else if num.is_infinite() && *num > 0.0 {
+                quote!(::std::f64::INFINITY)
+            } else if num.is_infinite() {
+                quote!(::std::f64::NEG_INFINITY)
+            } else {
+                let num = Literal::f64_suffixed(*num);
+                quote!(#num)
+            };
+            quote!(::jsxn::json::JsonValue::Num(#num))
+        }
+        JsonValue::Array(array) => {
+            let items = array.iter().map(json_tokens);
+            quote!(::jsxn::json::JsonValue::Array(::std::vec![#(#items),*]))
+        }
+        JsonValue::Object(object) => {
+            let entries = object.iter().map(|(key, value)| {
+                let value = json_tokens(value);
+                quote!((::std::string::String::from(#key), #value))
+            });
+            quote! {
+                ::jsxn::json::JsonValue::Object(
+                    ::std::vec![#(#entries),*]
+                        .into_iter()
+                        .collect::<::std::collections::BTreeMap<_, _>>(),
+                )
+            }
+        }
+        JsonValue::Null => quote!(::jsxn::json::JsonValue::Null),
+        JsonValue::JsxValue(jsx) => {
+            let jsx = jsx_tokens(jsx);
+            quote!(::jsxn::json::JsonValue::JsxValue(::std::boxed::Box::new(#jsx)))
+        }
+    }
+}
+
+struct Root(TokenStream2);
+
+impl Parse for Root {
+    fn parse(input: ParseStream) -> syn::Result<Root> {
+        let value = if input.peek(Token![<]) {
+            let jsx = jsx(input)?;
+            quote!(::jsxn::jsxn::JsxnValue::JsxValue(#jsx))
+        } else if input.peek(Token![#]) {
+            let value = interpolation(input)?;
+            quote! {
+                match ::jsxn::jsx::ToJsxValue::to_jsx_value(&#value) {
+                    ::jsxn::jsx::JsxValue::JsonValue(json) => ::jsxn::jsxn::JsxnValue::JsonValue(json),
+                    jsx => ::jsxn::jsxn::JsxnValue::JsxValue(jsx),
+                }
+            }
+        } else {
+            let json = json(input)?;
+            quote!(::jsxn::jsxn::JsxnValue::JsonValue(#json))
+        };
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after the root value"));
+        }
+        Ok(Root(value))
+    }
+}
+
+/// Parse an element or a fragment into an expression of a `JsxValue`
+fn jsx(input: ParseStream) -> syn::Result<TokenStream2> {
+    let open = input.parse::<Token![<]>()?;
+    if input.peek(Token![>]) {
+        input.parse::<Token![>]>()?;
+        let children = children(input, "fragment", open.span)?;
+        input.parse::<Token![<]>()?;
+        input.parse::<Token![/]>()?;
+        if !input.peek(Token![>]) {
+            return Err(input.error("expected `</>` closing the fragment"));
+        }
+        input.parse::<Token![>]>()?;
+        return Ok(quote! {
+            ::jsxn::jsx::JsxValue::JsxFragment(::jsxn::jsx::JsxFragment::new(
+                ::std::vec![#(#children),*],
+            ))
+        });
+    }
+
+    let name = name(input)?;
+    let mut names = BTreeSet::new();
+    let mut props = vec![];
+    while !input.peek(Token![/]) && !input.peek(Token![>]) {
+        if input.is_empty() {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("unclosed tag `{}`", name),
+            ));
+        }
+        let (prop, span) = prop_name(input)?;
+        if !names.insert(prop.clone()) {
+            return Err(syn::Error::new(span, format!("duplicate prop `{}`", prop)));
+        }
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            prop_value(input)?
+        } else {
+            quote!(::jsxn::jsx::JsxValue::JsonValue(
+                ::jsxn::json::JsonValue::Boolean(true)
+            ))
+        };
+        props.push(quote!((::std::string::String::from(#prop), #value)));
+    }
+
+    let children = if input.peek(Token![/]) {
+        input.parse::<Token![/]>()?;
+        input.parse::<Token![>]>()?;
+        vec![]
+    } else {
+        input.parse::<Token![>]>()?;
+        let children = children(input, &format!("element `{}`", name), name.span())?;
+        input.parse::<Token![<]>()?;
+        input.parse::<Token![/]>()?;
+        let closing = self::name(input)?;
+        if closing != name {
+            return Err(syn::Error::new(
+                closing.span(),
+                format!("expected `</{}>`, found `</{}>`", name, closing),
+            ));
+        }
+        input.parse::<Token![>]>()?;
+        children
+    };
+    let name = name.to_string();
+    Ok(quote! {
+        ::jsxn::jsx::JsxValue::JsxElement(::jsxn::jsx::JsxElement::new(
+            ::std::string::String::from(#name),
+            ::std::vec![#(#props),*]
+                .into_iter()
+                .collect::<::std::collections::BTreeMap<_, _>>(),
+            ::std::vec![#(#children),*],
+        ))
+    })
+}
+
+/// Parse the children of an element or a fragment, up to its closing tag
+fn children(
+    input: ParseStream,
+    parent: &str,
+    span: proc_macro2::Span,
+) -> syn::Result<Vec<TokenStream2>> {
+    let mut children = vec![];
+    while !(input.peek(Token![<]) && input.peek2(Token![/])) {
+        if input.is_empty() {
+            return Err(syn::Error::new(span, format!("unclosed {}", parent)));
+        }
+        children.push(if input.peek(Token![<]) {
+            jsx(input)?
+        } else if input.peek(LitStr) {
+            text(&input.parse()?)
+        } else if input.peek(token::Brace) || input.peek(Token![#]) {
+            expression(input)?
+        } else if input.peek(Ident::peek_any) {
+            return Err(input.error("text must be written as a string literal"));
+        } else {
+            return Err(input.error("expected an element, a string, `{` or `#`"));
+        });
+    }
+    Ok(children)
+}
+
+/// A text child, written as JSX text if it can be, and as a string
+/// expression otherwise
+fn text(text: &LitStr) -> TokenStream2 {
+    let value = text.value();
+    let plain = !value.is_empty()
+        && !value.starts_with(char::is_whitespace)
+        && !value.ends_with(char::is_whitespace)
+        && !value.contains(['{', '}', '<', '>', '\n']);
+    if plain {
+        quote!(::jsxn::jsx::JsxValue::JsonValue(::jsxn::json::JsonValue::Str(
+            ::std::string::String::from(#value)
+        )))
+    } else {
+        let json = string(&value);
+        quote!(::jsxn::jsx::JsxValue::JsxExpression(::std::boxed::Box::new(
+            ::jsxn::jsx::JsxValue::JsonValue(#json)
+        )))
+    }
+}
+
+fn prop_value(input: ParseStream) -> syn::Result<TokenStream2> {
+    if input.peek(LitStr) {
+        let json = string(&input.parse::<LitStr>()?.value());
+        Ok(quote!(::jsxn::jsx::JsxValue::JsonValue(#json)))
+    } else if input.peek(token::Brace) || input.peek(Token![#]) {
+        expression(input)
+    } else {
+        Err(input.error("expected a string, `{` or `#` as the prop value"))
+    }
+}
+
+/// Parse a JSX expression, either in braces or interpolated, into an
+/// expression of a `JsxValue`
+fn expression(input: ParseStream) -> syn::Result<TokenStream2> {
+    let value = if input.peek(Token![#]) {
+        let value = interpolation(input)?;
+        quote!(::jsxn::jsx::ToJsxValue::to_jsx_value(&#value))
+    } else {
+        let content;
+        let braces = braced!(content in input);
+        let value = if content.is_empty() {
+            return Err(syn::Error::new(braces.span.join(), "empty expression"));
+        } else if content.peek(Token![#]) {
+            let value = interpolation(&content)?;
+            quote!(::jsxn::jsx::ToJsxValue::to_jsx_value(&#value))
+        } else if content.peek(Token![<]) {
+            jsx(&content)?
+        } else {
+            let json = json(&content)?;
+            quote!(::jsxn::jsx::JsxValue::JsonValue(#json))
+        };
+        if !content.is_empty() {
+            return Err(content.error("unexpected tokens in the expression"));
+        }
+        value
+    };
+    Ok(quote!(::jsxn::jsx::JsxValue::JsxExpression(::std::boxed::Box::new(#value))))
+}
+
+/// Parse a JSON value into an expression of a `JsonValue`
+fn json(input: ParseStream) -> syn::Result<TokenStream2> {
+    if input.peek(Token![<]) {
+        let jsx = jsx(input)?;
+        return Ok(quote!(::jsxn::json::JsonValue::JsxValue(::std::boxed::Box::new(#jsx))));
+    }
+    if input.peek(Token![#]) {
+        let value = interpolation(input)?;
+        return Ok(quote! {
+            match ::jsxn::jsx::ToJsxValue::to_jsx_value(&#value) {
+                ::jsxn::jsx::JsxValue::JsonValue(json) => json,
+                jsx => ::jsxn::json::JsonValue::JsxValue(::std::boxed::Box::new(jsx)),
+            }
+        });
+    }
+    if input.peek(token::Bracket) {
+        let content;
+        bracketed!(content in input);
+        let mut items = vec![];
+        while !content.is_empty() {
+            items.push(json(&content)?);
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
+        }
+        return Ok(quote!(::jsxn::json::JsonValue::Array(
+            ::std::vec![#(#items),*]
+        )));
+    }
+    if input.peek(token::Brace) {
+        let content;
+        braced!(content in input);
+        let mut keys = BTreeSet::new();
+        let mut entries = vec![];
+        while !content.is_empty() {
+            let key = content.parse::<LitStr>()?;
+            if !keys.insert(key.value()) {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("duplicate key {:?}", key.value()),
+                ));
+            }
+            content.parse::<Token![:]>()?;
+            let value = json(&content)?;
+            let key = escape(&key.value());
+            entries.push(quote!((::std::string::String::from(#key), #value)));
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
+        }
+        return Ok(quote! {
+            ::jsxn::json::JsonValue::Object(
+                ::std::vec![#(#entries),*]
+                    .into_iter()
+                    .collect::<::std::collections::BTreeMap<_, _>>(),
+            )
+        });
+    }
+    if input.peek(Ident::peek_any) {
+        let ident = input.call(Ident::parse_any)?;
+        return match ident.to_string().as_str() {
+            "null" => Ok(quote!(::jsxn::json::JsonValue::Null)),
+            "true" => Ok(quote!(::jsxn::json::JsonValue::Boolean(true))),
+            "false" => Ok(quote!(::jsxn::json::JsonValue::Boolean(false))),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                format!("expected a JSON value, found `{}`", ident),
+            )),
+        };
+    }
+
+    let negative = input.peek(Token![-]);
+    if negative {
+        input.parse::<Token![-]>()?;
+    }
+    let lit = input.parse::<Lit>()?;
+    let (written, digits, suffix) = match &lit {
+        Lit::Int(int) => (int.to_string(), int.base10_digits(), int.suffix()),
+        Lit::Float(float) => (float.to_string(), float.base10_digits(), float.suffix()),
+        Lit::Str(s) if !negative => return Ok(string(&s.value())),
+        _ => return Err(syn::Error::new(lit.span(), "expected a JSON value")),
+    };
+    if !suffix.is_empty() {
+        return Err(syn::Error::new(
+            lit.span(),
+            "numbers cannot have a type suffix",
+        ));
+    }
+    if !json_number(&written) {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!("`{}` is not a JSON number", written),
+        ));
+    }
+    let number = match digits.parse::<f64>() {
+        Ok(number) if number.is_finite() => number,
+        _ => return Err(syn::Error::new(lit.span(), "number out of range")),
+    };
+    let number = Literal::f64_suffixed(if negative { -number } else { number });
+    Ok(quote!(::jsxn::json::JsonValue::Num(#number)))
+}
+
+/// Whether a number literal is written the way JSON writes numbers, in
+/// decimal, without underscores, leading zeros or a trailing point
+// `Option::is_none_or` is newer than the Rust versions the crate builds with
+#[allow(clippy::unnecessary_map_or)]
+fn json_number(written: &str) -> bool {
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let (mantissa, exponent) = match written.find(['e', 'E']) {
+        Some(index) => (&written[..index], Some(&written[index + 1..])),
+        None => (written, None),
+    };
+    let (integer, fraction) = match mantissa.find('.') {
+        Some(index) => (&mantissa[..index], Some(&mantissa[index + 1..])),
+        None => (mantissa, None),
+    };
+    digits(integer)
+        && (integer == "0" || !integer.starts_with('0'))
+        && fraction.map_or(true, digits)
+        && exponent.map_or(true, |exponent| {
+            digits(exponent.trim_start_matches(['+', '-']))
+        })
+}
+
+/// A JSON string holding `value`
+fn string(value: &str) -> TokenStream2 {
+    let escaped = escape(value);
+    quote!(::jsxn::json::JsonValue::Str(::std::string::String::from(#escaped)))
+}
+
+/// Parse `#name` or `#(expression)` into the interpolated expression
+fn interpolation(input: ParseStream) -> syn::Result<TokenStream2> {
+    input.parse::<Token![#]>()?;
+    if input.peek(token::Paren) {
+        let content;
+        parenthesized!(content in input);
+        let expression = content.parse::<TokenStream2>()?;
+        Ok(quote!((#expression)))
+    } else {
+        let ident = input.call(Ident::parse_any)?;
+        Ok(quote!(#ident))
+    }
+}
+
+/// Parse the name of a prop, which is names joined by `-`, as in
+/// `aria-label`, returning it with the span of its first name
+fn prop_name(input: ParseStream) -> syn::Result<(String, Span)> {
+    let first = name(input)?;
+    let mut prop = first.to_string();
+    while input.peek(Token![-]) && input.peek2(Ident::peek_any) {
+        input.parse::<Token![-]>()?;
+        prop.push('-');
+        prop.push_str(&name(input)?.to_string());
+    }
+    Ok((prop, first.span()))
+}
+
+/// Parse the name of an element or a prop, which JSXN limits to ASCII
+/// letters and digits
+fn name(input: ParseStream) -> syn::Result<Ident> {
+    let name = input.call(Ident::parse_any)?;
+    if name.to_string().chars().all(|c| c.is_ascii_alphanumeric()) {
+        Ok(name)
+    } else {
+        Err(syn::Error::new(
+            name.span(),
+            format!(
+                "`{}` is not a JSXN name, which has only letters and digits",
+                name
+            ),
+        ))
+    }
+}
@@ -0,0 +1,23 @@
+use ::jsxn::{jsxn, options::ParserOptions};
+use jsxn_macros::include_jsxn;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn include_file() {
+    assert_eq!(
+        include_jsxn!("tests/fixtures/page.jsxn"),
+        jsxn::parse(include_str!("fixtures/page.jsxn")).unwrap()
+    );
+}
+
+#[test]
+fn include_relaxed_file() {
+    assert_eq!(
+        include_jsxn!("tests/fixtures/relaxed.jsxn", relaxed),
+        jsxn::parse_with_options(
+            include_str!("fixtures/relaxed.jsxn"),
+            &ParserOptions::RELAXED
+        )
+        .unwrap()
+    );
+}
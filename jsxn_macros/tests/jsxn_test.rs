@@ -0,0 +1,125 @@
+use ::jsxn::{
+    json::JsonValue,
+    jsx::{JsxElement, JsxValue},
+    jsxn::{self, JsxnValue},
+};
+use jsxn_macros::jsxn;
+use pretty_assertions::assert_eq;
+use std::collections::BTreeMap;
+
+fn parse(input: &str) -> JsxnValue {
+    jsxn::parse(input).unwrap()
+}
+
+#[test]
+fn build_elements() {
+    assert_eq!(
+        jsxn!(
+            <div className="card" id={"main"} tabIndex={0} hidden>
+                <label htmlFor="name">"Name"</label>
+                <input id="name" value="a \"b\"" />
+                <br></br>
+                <Hello signOff data-id={1} aria-label-text="hi" />
+            </div>
+        ),
+        parse(
+            r#"
+            <div className="card" id={"main"} tabIndex={0} hidden>
+                <label htmlFor="name">Name</label>
+                <input id="name" value="a \"b\"" />
+                <br></br>
+                <Hello signOff data-id={1} aria-label-text="hi" />
+            </div>
+            "#
+        )
+    );
+}
+
+#[test]
+fn build_fragments_and_expressions() {
+    assert_eq!(
+        jsxn!(
+            <>
+                "Hello, world!"
+                " padded "
+                {"in braces"}
+                {[1, -2.5, true, null, <b />]}
+                {<i>"italic"</i>}
+                {{"nested": {"empty": {}}}}
+            </>
+        ),
+        parse(
+            r#"
+            <>
+                Hello, world!
+                {" padded "}
+                {"in braces"}
+                {[1, -2.5, true, null, <b />]}
+                {<i>italic</i>}
+                {{"nested": {"empty": {}}}}
+            </>
+            "#
+        )
+    );
+}
+
+#[test]
+fn build_json() {
+    assert_eq!(
+        jsxn!({
+            "text": "line\n\"quoted\"",
+            "numbers": [0, -1, 1.5e3, 16, 2.5E-1],
+            "page": <main>"Home"</main>,
+        }),
+        parse(
+            r#"{
+                "text": "line\n\"quoted\"",
+                "numbers": [0, -1, 1500, 16, 0.25],
+                "page": <main>Home</main>
+            }"#
+        )
+    );
+}
+
+#[test]
+fn interpolate_values() {
+    let name = "Ada \"the\" first";
+    let count = 3;
+    let tags = vec!["math", "poetry"];
+    let nickname: Option<String> = None;
+    let heading = JsxElement::new(String::from("h1"), BTreeMap::new(), vec![]);
+    assert_eq!(
+        jsxn!(
+            <section title=#name count=#(count + 1)>
+                #heading
+                <p>#name</p>
+                {#tags}
+                #nickname
+            </section>
+        ),
+        parse(
+            r#"
+            <section title={"Ada \"the\" first"} count={4}>
+                {<h1></h1>}
+                <p>{"Ada \"the\" first"}</p>
+                {["math", "poetry"]}
+                {null}
+            </section>
+            "#
+        )
+    );
+    assert_eq!(
+        jsxn!({"user": {"name": #name, "tags": #tags, "heading": #heading}}),
+        parse(
+            r#"{"user": {"name": "Ada \"the\" first", "tags": ["math", "poetry"], "heading": <h1></h1>}}"#
+        )
+    );
+    assert_eq!(
+        jsxn!(#heading),
+        JsxnValue::JsxValue(JsxValue::JsxElement(heading.clone()))
+    );
+    assert_eq!(
+        jsxn!(#(count * 2)),
+        JsxnValue::JsonValue(JsonValue::Num(6.0))
+    );
+}
@@ -0,0 +1,295 @@
+use crate::{
+    json::JsonValue,
+    jsx::{JsxElement, JsxValue},
+    jsxn::JsxnValue,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
+
+/// The JSX runtime that generated code calls to create elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runtime {
+    /// `createElement(type, props, ...children)` calls, like
+    /// `React.createElement`
+    Classic,
+
+    /// `_jsx(type, props, key)` calls imported from `jsx-runtime`, with the
+    /// children in `props.children`, and `_jsxs` when there are several
+    Automatic,
+}
+
+/// Options for the JavaScript generated from JSXN
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodegenOptions<'a> {
+    /// The runtime to generate calls for
+    pub runtime: Runtime,
+
+    /// The function called to create elements with the classic runtime
+    pub pragma: &'a str,
+
+    /// The component used for fragments with the classic runtime
+    pub pragma_frag: &'a str,
+
+    /// The module whose `jsx-runtime` is imported with the automatic runtime
+    pub import_source: &'a str,
+}
+
+impl CodegenOptions<'static> {
+    /// `React.createElement` calls, which is also the default
+    pub const CLASSIC: CodegenOptions<'static> = CodegenOptions {
+        runtime: Runtime::Classic,
+        pragma: "React.createElement",
+        pragma_frag: "React.Fragment",
+        import_source: "react",
+    };
+
+    /// Calls to the automatic runtime of React
+    pub const AUTOMATIC: CodegenOptions<'static> = CodegenOptions {
+        runtime: Runtime::Automatic,
+        ..CodegenOptions::CLASSIC
+    };
+}
+
+impl Default for CodegenOptions<'static> {
+    fn default() -> CodegenOptions<'static> {
+        CodegenOptions::CLASSIC
+    }
+}
+
+/// Generate a JavaScript expression that creates the elements of a JSXN
+/// value. Element types starting with a lowercase letter are passed as
+/// strings, and others as references to components in scope. Template
+/// variables become references to the variables in scope without the `$`,
+/// unless their name does not start like an identifier, as in `$0`.
+pub fn generate(jsxn: &JsxnValue, options: &CodegenOptions) -> String {
+    let mut js = String::new();
+    // Writing to a `String` cannot fail
+    let _ = Generator {
+        w: &mut js,
+        options,
+    }
+    .jsxn(jsxn);
+    js
+}
+
+/// Generate a JavaScript module whose default export is the elements of a
+/// JSXN value, importing the automatic runtime if it is used
+pub fn generate_module(jsxn: &JsxnValue, options: &CodegenOptions) -> String {
+    let mut js = String::new();
+    if options.runtime == Runtime::Automatic {
+        js.push_str("import { jsx as _jsx, jsxs as _jsxs, Fragment as _Fragment } from ");
+        let _ = write_string(&mut js, &format!("{}/jsx-runtime", options.import_source));
+        js.push_str(";\n");
+    }
+    js.push_str("export default ");
+    js.push_str(&generate(jsxn, options));
+    js.push_str(";\n");
+    js
+}
+
+struct Generator<'o, 'a, W> {
+    w: W,
+    options: &'o CodegenOptions<'a>,
+}
+
+impl<'o, 'a, W: Write> Generator<'o, 'a, W> {
+    fn jsxn(&mut self, jsxn: &JsxnValue) -> fmt::Result {
+        match jsxn {
+            JsxnValue::JsxValue(jsx) => self.jsx(jsx),
+            JsxnValue::JsonValue(json) => self.json(json),
+        }
+    }
+
+    fn jsx(&mut self, jsx: &JsxValue) -> fmt::Result {
+        match jsx {
+            JsxValue::JsxElement(element) => self.element(element),
+            JsxValue::JsxFragment(fragment) => {
+                self.create(None, &BTreeMap::new(), fragment.children())
+            }
+            JsxValue::JsonValue(json) => self.json(json),
+            JsxValue::JsxExpression(jsx) => self.jsx(jsx),
+            JsxValue::Variable(path) => {
+                for (index, segment) in path.iter().enumerate() {
+                    if index == 0 {
+                        if !identifier_start(segment) {
+                            self.w.write_char('$')?;
+                        }
+                        self.w.write_str(segment)?;
+                    } else if identifier_start(segment) {
+                        write!(self.w, ".{}", segment)?;
+                    } else if segment.parse::<usize>().is_ok() {
+                        write!(self.w, "[{}]", segment)?;
+                    } else {
+                        write!(self.w, "[\"{}\"]", segment)?;
+                    }
+                }
+                Ok(())
+            }
+            JsxValue::Error(_) => self.w.write_str("null"),
+        }
+    }
+
+    fn json(&mut self, json: &JsonValue) -> fmt::Result {
+        match json {
+            // JSON string escapes are also valid in JavaScript
+            JsonValue::Str(s) => write!(self.w, "\"{}\"", s),
+            JsonValue::Boolean(boolean) => write!(self.w, "{}", boolean),
+            JsonValue::Num(num) if num.is_nan() => self.w.write_str("NaN"),
+            JsonValue::Num(num) if num.is_infinite() => {
+                self.w
+                    .write_str(if *num > 0.0 { "Infinity" } else { "-Infinity" })
+            }
+            JsonValue::Num(num) => write!(self.w, "{}", num),
+            JsonValue::Null => self.w.write_str("null"),
+            JsonValue::Array(array) => {
+                self.w.write_char('[')?;
+                for (index, item) in array.iter().enumerate() {
+                    if index > 0 {
+                        self.w.write_str(", ")?;
+                    }
+                    self.json(item)?;
+                }
+                self.w.write_char(']')
+            }
+            JsonValue::Object(object) => {
+                self.w.write_char('{')?;
+                for (index, (key, value)) in object.iter().enumerate() {
+                    if index > 0 {
+                        self.w.write_str(", ")?;
+                    }
+                    self.key(key)?;
+                    self.json(value)?;
+                }
+                self.w.write_char('}')
+            }
+            JsonValue::JsxValue(jsx) => self.jsx(jsx),
+        }
+    }
+
+    fn element(&mut self, element: &JsxElement) -> fmt::Result {
+        self.create(Some(element.r#type()), element.props(), element.children())
+    }
+
+    /// Generate the call creating an element, or a fragment if `r#type` is
+    /// `None`
+    fn create(
+        &mut self,
+        r#type: Option<&str>,
+        props: &BTreeMap<String, JsxValue>,
+        children: &[JsxValue],
+    ) -> fmt::Result {
+        let automatic = self.options.runtime == Runtime::Automatic;
+        let function = match self.options.runtime {
+            Runtime::Classic => self.options.pragma,
+            Runtime::Automatic if children.len() > 1 => "_jsxs",
+            Runtime::Automatic => "_jsx",
+        };
+        write!(self.w, "{}(", function)?;
+        match r#type {
+            Some(r#type) if r#type.starts_with(|c: char| c.is_ascii_lowercase()) => {
+                write_string(&mut self.w, r#type)?
+            }
+            Some(r#type) => self.w.write_str(r#type)?,
+            None if automatic => self.w.write_str("_Fragment")?,
+            None => self.w.write_str(self.options.pragma_frag)?,
+        }
+        self.w.write_str(", ")?;
+
+        let key = if automatic { props.get("key") } else { None };
+        let props = props
+            .iter()
+            .filter(|(name, _)| !(automatic && *name == "key"))
+            .collect::<Vec<_>>();
+        if props.is_empty() && (!automatic || children.is_empty()) {
+            self.w.write_str(if automatic { "{}" } else { "null" })?;
+        } else {
+            self.w.write_char('{')?;
+            for (index, (name, value)) in props.iter().enumerate() {
+                if index > 0 {
+                    self.w.write_str(", ")?;
+                }
+                self.key(name)?;
+                self.jsx(value)?;
+            }
+            if automatic && !children.is_empty() {
+                if !props.is_empty() {
+                    self.w.write_str(", ")?;
+                }
+                self.w.write_str("children: ")?;
+                if children.len() > 1 {
+                    self.w.write_char('[')?;
+                    self.children(children)?;
+                    self.w.write_char(']')?;
+                } else {
+                    self.children(children)?;
+                }
+            }
+            self.w.write_char('}')?;
+        }
+
+        if let Some(key) = key {
+            self.w.write_str(", ")?;
+            self.jsx(key)?;
+        }
+        if !automatic && !children.is_empty() {
+            self.w.write_str(", ")?;
+            self.children(children)?;
+        }
+        self.w.write_char(')')
+    }
+
+    fn children(&mut self, children: &[JsxValue]) -> fmt::Result {
+        for (index, child) in children.iter().enumerate() {
+            if index > 0 {
+                self.w.write_str(", ")?;
+            }
+            match child {
+                // Text is kept as written, so it is escaped as a whole
+                JsxValue::JsonValue(JsonValue::Str(text)) => write_string(&mut self.w, text)?,
+                child => self.jsx(child)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write an object key, quoting it unless it is an identifier. Keys are
+    /// kept escaped, like strings.
+    fn key(&mut self, key: &str) -> fmt::Result {
+        let identifier = identifier_start(key)
+            && key
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+        if identifier {
+            self.w.write_str(key)?;
+        } else {
+            write!(self.w, "\"{}\"", key)?;
+        }
+        self.w.write_str(": ")
+    }
+}
+
+/// Whether a name starts with a character that can start an identifier
+fn identifier_start(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+}
+
+/// Write text as a JavaScript string literal
+fn write_string<W: Write>(w: &mut W, text: &str) -> fmt::Result {
+    w.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\u{2028}' => w.write_str("\\u2028")?,
+            '\u{2029}' => w.write_str("\\u2029")?,
+            c if c.is_control() => write!(w, "\\u{:04x}", c as u32)?,
+            c => w.write_char(c)?,
+        }
+    }
+    w.write_char('"')
+}
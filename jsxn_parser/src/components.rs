@@ -0,0 +1,436 @@
+use crate::{
+    error::Error,
+    json::JsonValue,
+    jsx::{JsxElement, JsxFragment, JsxValue},
+    jsxn::{self, JsxnValue},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error, fmt,
+};
+
+/// The default for `Registry::max_depth`
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// The default for `Registry::max_nodes`
+pub const DEFAULT_MAX_NODES: usize = 100_000;
+
+/// A function rendering the props and children of an element to a tree
+pub type RenderFn = dyn Fn(&BTreeMap<String, JsxValue>, &[JsxValue]) -> JsxValue;
+
+/// A component, which renders its props and children to a tree
+pub enum Component {
+    /// A function of the props and children
+    Closure(Box<RenderFn>),
+
+    /// A tree in which `<Children />` is replaced by the children, and
+    /// `<Prop name="..." />` by the value of a prop, or `null` if it is not
+    /// set
+    Template(JsxValue),
+}
+
+/// An error returned when expanding components
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpandError {
+    /// A component renders itself, directly or through other components
+    Cycle {
+        /// The components being expanded, ending with the one that was
+        /// already being expanded
+        path: Vec<String>,
+    },
+
+    /// Components are nested deeper than `Registry::max_depth`
+    TooDeep {
+        /// The components being expanded when the limit was reached
+        path: Vec<String>,
+
+        /// The configured limit
+        limit: usize,
+    },
+
+    /// Components render more nodes in total than `Registry::max_nodes`
+    TooManyNodes {
+        /// The components being expanded when the limit was reached
+        path: Vec<String>,
+
+        /// The configured limit
+        limit: usize,
+    },
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::Cycle { path } => {
+                write!(f, "component cycle: {}", path.join(" -> "))
+            }
+            ExpandError::TooDeep { path, limit } => write!(
+                f,
+                "components nested deeper than {}: {}",
+                limit,
+                path.join(" -> ")
+            ),
+            ExpandError::TooManyNodes { path, limit } => write!(
+                f,
+                "components render more than {} nodes: {}",
+                limit,
+                path.join(" -> ")
+            ),
+        }
+    }
+}
+
+impl error::Error for ExpandError {}
+
+/// Components registered by element type, which replace the elements of
+/// that type when a tree is expanded
+pub struct Registry {
+    components: BTreeMap<String, Component>,
+
+    /// The most components that may be expanded inside one another
+    pub max_depth: usize,
+
+    /// The most nodes that the components may render in one expansion, which
+    /// bounds trees whose components repeat their children
+    pub max_nodes: usize,
+}
+
+impl Registry {
+    /// Create an empty registry
+    pub fn new() -> Registry {
+        Registry {
+            components: BTreeMap::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_nodes: DEFAULT_MAX_NODES,
+        }
+    }
+
+    /// Register a component for elements of type `name`, replacing any
+    /// component already registered for it
+    pub fn register(&mut self, name: &str, component: Component) {
+        self.components.insert(String::from(name), component);
+    }
+
+    /// Register a function of the props and children as a component
+    pub fn register_fn<F>(&mut self, name: &str, f: F)
+    where
+        F: Fn(&BTreeMap<String, JsxValue>, &[JsxValue]) -> JsxValue + 'static,
+    {
+        self.register(name, Component::Closure(Box::new(f)));
+    }
+
+    /// Parse a JSXN template and register it as a component
+    pub fn register_template(&mut self, name: &str, template: &str) -> Result<(), Error> {
+        let template = match jsxn::parse(template)? {
+            JsxnValue::JsxValue(jsx) => jsx,
+            JsxnValue::JsonValue(json) => JsxValue::JsonValue(json),
+        };
+        self.register(name, Component::Template(template));
+        Ok(())
+    }
+
+    /// Replace every element of a registered type with the tree its
+    /// component renders, expanding the props and children of an element
+    /// before they are passed to its component
+    pub fn expand(&self, jsx: &JsxValue) -> Result<JsxValue, ExpandError> {
+        self.expand_jsx(jsx, &mut vec![], &mut 0)
+    }
+
+    /// Expand the components of a JSXN value
+    pub fn expand_jsxn(&self, jsxn: &JsxnValue) -> Result<JsxnValue, ExpandError> {
+        Ok(match jsxn {
+            JsxnValue::JsxValue(jsx) => JsxnValue::JsxValue(self.expand(jsx)?),
+            JsxnValue::JsonValue(json) => {
+                JsxnValue::JsonValue(self.expand_json(json, &mut vec![], &mut 0)?)
+            }
+        })
+    }
+
+    /// Expand a tree, counting the nodes rendered by components in `nodes`
+    fn expand_jsx(
+        &self,
+        jsx: &JsxValue,
+        path: &mut Vec<String>,
+        nodes: &mut usize,
+    ) -> Result<JsxValue, ExpandError> {
+        Ok(match jsx {
+            JsxValue::JsxElement(element) => {
+                let mut props = BTreeMap::new();
+                for (name, value) in element.props() {
+                    props.insert(name.clone(), self.expand_jsx(value, path, nodes)?);
+                }
+                let children = self.expand_children(element.children(), path, nodes)?;
+                self.render(element.r#type(), props, children, path, nodes)?
+            }
+            JsxValue::JsxFragment(fragment) => JsxValue::JsxFragment(JsxFragment::new(
+                self.expand_children(fragment.children(), path, nodes)?,
+            )),
+            JsxValue::JsonValue(json) => JsxValue::JsonValue(self.expand_json(json, path, nodes)?),
+            JsxValue::JsxExpression(jsx) => {
+                JsxValue::JsxExpression(Box::new(self.expand_jsx(jsx, path, nodes)?))
+            }
+            JsxValue::Error(_) | JsxValue::Variable(_) => jsx.clone(),
+        })
+    }
+
+    /// Render an element whose props and children are already expanded with
+    /// its component, if it has one
+    fn render(
+        &self,
+        name: &str,
+        props: BTreeMap<String, JsxValue>,
+        children: Vec<JsxValue>,
+        path: &mut Vec<String>,
+        nodes: &mut usize,
+    ) -> Result<JsxValue, ExpandError> {
+        let component = match self.components.get(name) {
+            Some(component) => component,
+            None => {
+                return Ok(JsxValue::JsxElement(JsxElement::new(
+                    String::from(name),
+                    props,
+                    children,
+                )))
+            }
+        };
+        if path.iter().any(|expanding| expanding == name) {
+            path.push(String::from(name));
+            return Err(ExpandError::Cycle { path: path.clone() });
+        }
+        if path.len() >= self.max_depth {
+            path.push(String::from(name));
+            return Err(ExpandError::TooDeep {
+                path: path.clone(),
+                limit: self.max_depth,
+            });
+        }
+
+        path.push(String::from(name));
+        let expanded = match component {
+            // The output of a closure is opaque, so all of it is expanded,
+            // but only the nodes it adds to its props and children count
+            Component::Closure(f) => {
+                let output = f(&props, &children);
+                let passed = props.values().chain(&children).map(count_nodes).sum();
+                self.count(count_nodes(&output).saturating_sub(passed), path, nodes)?;
+                self.expand_jsx(&output, path, nodes)?
+            }
+            Component::Template(template) => {
+                let mut arguments = Arguments {
+                    props: &props,
+                    children: &children,
+                    inserted: BTreeSet::new(),
+                };
+                self.instantiate(template, &mut arguments, path, nodes)?
+            }
+        };
+        path.pop();
+        Ok(expanded)
+    }
+
+    /// Add `count` nodes rendered by the component at the end of `path`
+    fn count(&self, count: usize, path: &[String], nodes: &mut usize) -> Result<(), ExpandError> {
+        *nodes += count;
+        if *nodes > self.max_nodes {
+            return Err(ExpandError::TooManyNodes {
+                path: path.to_vec(),
+                limit: self.max_nodes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Expand a template, replacing its `<Children />` and `<Prop />`
+    /// elements with the arguments, which are already expanded and are not
+    /// walked again. Each node of the template counts as rendered, and so
+    /// does each copy of an argument after the first.
+    fn instantiate(
+        &self,
+        template: &JsxValue,
+        arguments: &mut Arguments,
+        path: &mut Vec<String>,
+        nodes: &mut usize,
+    ) -> Result<JsxValue, ExpandError> {
+        match template {
+            JsxValue::JsxElement(element) if element.r#type() == "Children" => {
+                let children = JsxValue::JsxFragment(JsxFragment::new(arguments.children.to_vec()));
+                return self.insert(None, children, arguments, path, nodes);
+            }
+            JsxValue::JsxElement(element) if element.r#type() == "Prop" => {
+                return match element.props().get("name") {
+                    Some(JsxValue::JsonValue(JsonValue::Str(name))) => {
+                        let value = arguments
+                            .props
+                            .get(name)
+                            .cloned()
+                            .unwrap_or(JsxValue::JsonValue(JsonValue::Null));
+                        self.insert(Some(name), value, arguments, path, nodes)
+                    }
+                    _ => Ok(JsxValue::JsonValue(JsonValue::Null)),
+                };
+            }
+            _ => {}
+        }
+
+        self.count(1, path, nodes)?;
+        Ok(match template {
+            JsxValue::JsxElement(element) => {
+                let mut props = BTreeMap::new();
+                for (name, value) in element.props() {
+                    props.insert(
+                        name.clone(),
+                        self.instantiate(value, arguments, path, nodes)?,
+                    );
+                }
+                let children = element
+                    .children()
+                    .iter()
+                    .map(|child| self.instantiate(child, arguments, path, nodes))
+                    .collect::<Result<_, _>>()?;
+                self.render(element.r#type(), props, children, path, nodes)?
+            }
+            JsxValue::JsxFragment(fragment) => JsxValue::JsxFragment(JsxFragment::new(
+                fragment
+                    .children()
+                    .iter()
+                    .map(|child| self.instantiate(child, arguments, path, nodes))
+                    .collect::<Result<_, _>>()?,
+            )),
+            JsxValue::JsonValue(json) => {
+                JsxValue::JsonValue(self.instantiate_json(json, arguments, path, nodes)?)
+            }
+            JsxValue::JsxExpression(jsx) => match self.instantiate(jsx, arguments, path, nodes)? {
+                // A prop that is already an expression is not wrapped again
+                expression @ JsxValue::JsxExpression(_) => expression,
+                jsx => JsxValue::JsxExpression(Box::new(jsx)),
+            },
+            JsxValue::Error(_) | JsxValue::Variable(_) => template.clone(),
+        })
+    }
+
+    /// Insert the value of a prop, or the children if `argument` is `None`,
+    /// into the output of a template
+    fn insert(
+        &self,
+        argument: Option<&str>,
+        value: JsxValue,
+        arguments: &mut Arguments,
+        path: &[String],
+        nodes: &mut usize,
+    ) -> Result<JsxValue, ExpandError> {
+        if !arguments.inserted.insert(argument.map(String::from)) {
+            self.count(count_nodes(&value), path, nodes)?;
+        }
+        Ok(value)
+    }
+
+    fn instantiate_json(
+        &self,
+        json: &JsonValue,
+        arguments: &mut Arguments,
+        path: &mut Vec<String>,
+        nodes: &mut usize,
+    ) -> Result<JsonValue, ExpandError> {
+        Ok(match json {
+            JsonValue::Array(array) => JsonValue::Array(
+                array
+                    .iter()
+                    .map(|item| self.instantiate_json(item, arguments, path, nodes))
+                    .collect::<Result<_, _>>()?,
+            ),
+            JsonValue::Object(object) => {
+                let mut instantiated = BTreeMap::new();
+                for (key, value) in object {
+                    instantiated.insert(
+                        key.clone(),
+                        self.instantiate_json(value, arguments, path, nodes)?,
+                    );
+                }
+                JsonValue::Object(instantiated)
+            }
+            JsonValue::JsxValue(jsx) => match self.instantiate(jsx, arguments, path, nodes)? {
+                JsxValue::JsonValue(json) => json,
+                jsx => JsonValue::JsxValue(Box::new(jsx)),
+            },
+            json => json.clone(),
+        })
+    }
+
+    fn expand_children(
+        &self,
+        children: &[JsxValue],
+        path: &mut Vec<String>,
+        nodes: &mut usize,
+    ) -> Result<Vec<JsxValue>, ExpandError> {
+        children
+            .iter()
+            .map(|child| self.expand_jsx(child, path, nodes))
+            .collect()
+    }
+
+    fn expand_json(
+        &self,
+        json: &JsonValue,
+        path: &mut Vec<String>,
+        nodes: &mut usize,
+    ) -> Result<JsonValue, ExpandError> {
+        Ok(match json {
+            JsonValue::Array(array) => JsonValue::Array(
+                array
+                    .iter()
+                    .map(|item| self.expand_json(item, path, nodes))
+                    .collect::<Result<_, _>>()?,
+            ),
+            JsonValue::Object(object) => {
+                let mut expanded = BTreeMap::new();
+                for (key, value) in object {
+                    expanded.insert(key.clone(), self.expand_json(value, path, nodes)?);
+                }
+                JsonValue::Object(expanded)
+            }
+            JsonValue::JsxValue(jsx) => {
+                JsonValue::JsxValue(Box::new(self.expand_jsx(jsx, path, nodes)?))
+            }
+            json => json.clone(),
+        })
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::new()
+    }
+}
+
+/// The number of nodes in a tree
+fn count_nodes(jsx: &JsxValue) -> usize {
+    match jsx {
+        JsxValue::JsxElement(element) => {
+            1 + element.props().values().map(count_nodes).sum::<usize>()
+                + element.children().iter().map(count_nodes).sum::<usize>()
+        }
+        JsxValue::JsxFragment(fragment) => {
+            1 + fragment.children().iter().map(count_nodes).sum::<usize>()
+        }
+        JsxValue::JsonValue(json) => count_json_nodes(json),
+        JsxValue::JsxExpression(jsx) => 1 + count_nodes(jsx),
+        JsxValue::Error(_) | JsxValue::Variable(_) => 1,
+    }
+}
+
+fn count_json_nodes(json: &JsonValue) -> usize {
+    match json {
+        JsonValue::Array(array) => 1 + array.iter().map(count_json_nodes).sum::<usize>(),
+        JsonValue::Object(object) => 1 + object.values().map(count_json_nodes).sum::<usize>(),
+        JsonValue::JsxValue(jsx) => count_nodes(jsx),
+        _ => 1,
+    }
+}
+
+/// The props and children passed to a template, and the ones it has
+/// inserted so far, by prop name or `None` for the children
+struct Arguments<'a> {
+    props: &'a BTreeMap<String, JsxValue>,
+    children: &'a [JsxValue],
+    inserted: BTreeSet<Option<String>>,
+}
@@ -0,0 +1,97 @@
+use crate::error::{Error, Location};
+use std::{borrow::Cow, char, str};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16_LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16_BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// Text decoded from byte input
+pub struct Decoded<'a> {
+    pub text: Cow<'a, str>,
+
+    /// The location in the input of the start of `text`, after any UTF-8
+    /// byte order mark, or `None` if `text` was transcoded from UTF-16
+    pub base: Option<Location>,
+}
+
+impl<'a> Decoded<'a> {
+    /// Translate a location within `text` to a location within the input
+    pub fn locate(&self, error: Error) -> Error {
+        match self.base {
+            Some(base) => error.relative_to(base),
+            None => error.map_location(|location| utf16_location(&self.text, location.offset)),
+        }
+    }
+}
+
+/// The location in UTF-16 input with a byte order mark of a byte offset
+/// within the text transcoded from it, which has the same lines and columns
+/// but takes two bytes for each UTF-16 code unit
+fn utf16_location(text: &str, offset: usize) -> Location {
+    let units = text.get(..offset).unwrap_or(text).encode_utf16().count();
+    Location {
+        offset: UTF16_LE_BOM.len() + 2 * units,
+        ..Location::new(text, offset)
+    }
+}
+
+/// Decode UTF-8 input, with or without a byte order mark, or UTF-16 input
+/// with a byte order mark
+pub fn decode(input: &[u8]) -> Result<Decoded<'_>, Error> {
+    if input.starts_with(UTF16_LE_BOM) {
+        return decode_utf16(&input[UTF16_LE_BOM.len()..], u16::from_le_bytes);
+    }
+    if input.starts_with(UTF16_BE_BOM) {
+        return decode_utf16(&input[UTF16_BE_BOM.len()..], u16::from_be_bytes);
+    }
+
+    let bom = if input.starts_with(UTF8_BOM) {
+        UTF8_BOM.len()
+    } else {
+        0
+    };
+    let base = Location {
+        offset: bom,
+        line: 1,
+        column: 1,
+    };
+    let bytes = &input[bom..];
+    match str::from_utf8(bytes) {
+        Ok(text) => Ok(Decoded {
+            text: Cow::Borrowed(text),
+            base: Some(base),
+        }),
+        Err(e) => {
+            let valid = str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default();
+            Err(Error::Syntax {
+                location: Location::new(valid, valid.len()).relative_to(base),
+                message: String::from("invalid UTF-8"),
+            })
+        }
+    }
+}
+
+fn decode_utf16(input: &[u8], unit: fn([u8; 2]) -> u16) -> Result<Decoded<'_>, Error> {
+    let invalid = |text: &str| Error::Syntax {
+        location: utf16_location(text, text.len()),
+        message: String::from("invalid UTF-16"),
+    };
+
+    let mut text = String::with_capacity(input.len() / 2);
+    let units = input
+        .chunks_exact(2)
+        .map(|bytes| unit([bytes[0], bytes[1]]));
+    for c in char::decode_utf16(units) {
+        match c {
+            Ok(c) => text.push(c),
+            Err(_) => return Err(invalid(&text)),
+        }
+    }
+    if !input.chunks_exact(2).remainder().is_empty() {
+        return Err(invalid(&text));
+    }
+    Ok(Decoded {
+        text: Cow::Owned(text),
+        base: None,
+    })
+}
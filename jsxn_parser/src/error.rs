@@ -0,0 +1,335 @@
+use crate::shared::{State, Violation};
+use nom::{
+    error::{VerboseError, VerboseErrorKind},
+    Err,
+};
+use serde::{Deserialize, Serialize};
+use std::{error, fmt, io};
+
+/// A position in the parsed input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    /// Byte offset from the start of the input
+    pub offset: usize,
+
+    /// One-based line number
+    pub line: usize,
+
+    /// One-based column number, counted in characters
+    pub column: usize,
+}
+
+impl Location {
+    /// Compute the location of a byte offset within `input`
+    pub fn new(input: &str, offset: usize) -> Location {
+        let mut line = 1;
+        let mut column = 1;
+        for (index, c) in input.char_indices() {
+            if index >= offset {
+                break;
+            }
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        Location {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    /// Translate a location relative to the text starting at `base`
+    pub(crate) fn relative_to(self, base: Location) -> Location {
+        Location {
+            offset: base.offset + self.offset,
+            line: base.line + self.line - 1,
+            column: if self.line == 1 {
+                base.column + self.column - 1
+            } else {
+                self.column
+            },
+        }
+    }
+
+    /// Compute the location of `remainder`, a suffix of `input`
+    pub(crate) fn of_remainder(input: &str, remainder: &str) -> Location {
+        Location::new(input, input.len() - remainder.len())
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// A range of the parsed input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    /// The location of the first byte of the range
+    pub start: Location,
+
+    /// The location of the first byte after the range
+    pub end: Location,
+}
+
+/// An error returned by the JSXN entry points
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The input is not valid JSXN
+    Syntax {
+        /// Where the error was detected
+        location: Location,
+
+        /// A description of what was expected
+        message: String,
+    },
+
+    /// The input is larger than `ParseLimits::max_input_bytes`
+    InputTooLarge {
+        /// The first byte past the limit
+        location: Location,
+
+        /// The configured limit
+        limit: usize,
+    },
+
+    /// The input could not be read
+    Io {
+        /// How much of the input had been read
+        location: Location,
+
+        /// The kind of I/O error
+        kind: io::ErrorKind,
+
+        /// A description of the I/O error
+        message: String,
+    },
+
+    /// The tree has more nodes than `ParseLimits::max_nodes`
+    TooManyNodes {
+        /// The node that exceeded the limit
+        location: Location,
+
+        /// The configured limit
+        limit: usize,
+    },
+
+    /// A string or text is longer than `ParseLimits::max_string_length`
+    StringTooLong {
+        /// The string that exceeded the limit
+        location: Location,
+
+        /// The configured limit
+        limit: usize,
+    },
+
+    /// An element has more props than `ParseLimits::max_props`
+    TooManyProps {
+        /// The element that exceeded the limit
+        location: Location,
+
+        /// The configured limit
+        limit: usize,
+    },
+
+    /// An element or fragment has more children than
+    /// `ParseLimits::max_children`
+    TooManyChildren {
+        /// The element or fragment that exceeded the limit
+        location: Location,
+
+        /// The configured limit
+        limit: usize,
+    },
+
+    /// A value is nested deeper than `ParseLimits::max_depth`
+    TooDeep {
+        /// The value that exceeded the limit
+        location: Location,
+
+        /// The configured limit
+        limit: usize,
+    },
+}
+
+impl Error {
+    /// The location in the input where the error was detected
+    pub fn location(&self) -> Location {
+        match self {
+            Error::Syntax { location, .. }
+            | Error::InputTooLarge { location, .. }
+            | Error::Io { location, .. }
+            | Error::TooManyNodes { location, .. }
+            | Error::StringTooLong { location, .. }
+            | Error::TooManyProps { location, .. }
+            | Error::TooManyChildren { location, .. }
+            | Error::TooDeep { location, .. } => *location,
+        }
+    }
+
+    /// Convert the error of a parse of `input` into the limit that stopped it,
+    /// or otherwise a syntax error
+    pub(crate) fn from_parse(input: &str, state: &State, error: Err<VerboseError<&str>>) -> Error {
+        let limits = &state.options.limits;
+        if let Some((violation, remainder)) = state.violation() {
+            let location = Location::of_remainder(input, remainder);
+            let limit = |limit: Option<usize>| limit.unwrap_or_default();
+            return match violation {
+                Violation::TooManyNodes => Error::TooManyNodes {
+                    location,
+                    limit: limit(limits.max_nodes),
+                },
+                Violation::StringTooLong => Error::StringTooLong {
+                    location,
+                    limit: limit(limits.max_string_length),
+                },
+                Violation::TooManyProps => Error::TooManyProps {
+                    location,
+                    limit: limit(limits.max_props),
+                },
+                Violation::TooManyChildren => Error::TooManyChildren {
+                    location,
+                    limit: limit(limits.max_children),
+                },
+                Violation::TooDeep => Error::TooDeep {
+                    location,
+                    limit: limit(limits.max_depth),
+                },
+            };
+        }
+        match error {
+            Err::Error(e) | Err::Failure(e) => Error::from_verbose(input, e),
+            Err::Incomplete(_) => Error::Syntax {
+                location: Location::new(input, input.len()),
+                message: String::from("unexpected end of input"),
+            },
+        }
+    }
+
+    /// Convert a nom error into a syntax error positioned within `input`
+    pub(crate) fn from_verbose(input: &str, error: VerboseError<&str>) -> Error {
+        let (remainder, kind) = match error.errors.first() {
+            Some((remainder, kind)) => (*remainder, kind),
+            None => {
+                return Error::Syntax {
+                    location: Location::new(input, 0),
+                    message: String::from("invalid input"),
+                }
+            }
+        };
+        let context = error.errors.iter().find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(context) => Some(context),
+            _ => None,
+        });
+        let message = match (kind, context) {
+            (VerboseErrorKind::Char(c), Some(context)) => {
+                format!("expected '{}' in {}", c, context)
+            }
+            (VerboseErrorKind::Char(c), None) => format!("expected '{}'", c),
+            (_, Some(context)) => format!("invalid {}", context),
+            (VerboseErrorKind::Nom(kind), None) => String::from(kind.description()),
+            (VerboseErrorKind::Context(_), None) => unreachable!(),
+        };
+        Error::Syntax {
+            location: Location::of_remainder(input, remainder),
+            message,
+        }
+    }
+
+    /// Translate the location of the error to be relative to the text
+    /// starting at `base`
+    pub(crate) fn relative_to(self, base: Location) -> Error {
+        self.map_location(|location| location.relative_to(base))
+    }
+
+    /// Replace the location of the error with `f` of it
+    pub(crate) fn map_location(self, f: impl FnOnce(Location) -> Location) -> Error {
+        match self {
+            Error::Syntax { location, message } => Error::Syntax {
+                location: f(location),
+                message,
+            },
+            Error::InputTooLarge { location, limit } => Error::InputTooLarge {
+                location: f(location),
+                limit,
+            },
+            Error::Io {
+                location,
+                kind,
+                message,
+            } => Error::Io {
+                location: f(location),
+                kind,
+                message,
+            },
+            Error::TooManyNodes { location, limit } => Error::TooManyNodes {
+                location: f(location),
+                limit,
+            },
+            Error::StringTooLong { location, limit } => Error::StringTooLong {
+                location: f(location),
+                limit,
+            },
+            Error::TooManyProps { location, limit } => Error::TooManyProps {
+                location: f(location),
+                limit,
+            },
+            Error::TooManyChildren { location, limit } => Error::TooManyChildren {
+                location: f(location),
+                limit,
+            },
+            Error::TooDeep { location, limit } => Error::TooDeep {
+                location: f(location),
+                limit,
+            },
+        }
+    }
+
+    /// A syntax error for input left over after the root value
+    pub(crate) fn trailing_input(input: &str, remainder: &str) -> Error {
+        Error::Syntax {
+            location: Location::of_remainder(input, remainder),
+            message: String::from("unexpected trailing input"),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Syntax { location, message } => write!(f, "{} at {}", message, location),
+            Error::InputTooLarge { location, limit } => write!(
+                f,
+                "input exceeds the limit of {} bytes at {}",
+                limit, location
+            ),
+            Error::Io {
+                location, message, ..
+            } => write!(f, "{} at {}", message, location),
+            Error::TooManyNodes { location, limit } => {
+                write!(f, "more than {} nodes at {}", limit, location)
+            }
+            Error::StringTooLong { location, limit } => write!(
+                f,
+                "string exceeds the limit of {} bytes at {}",
+                limit, location
+            ),
+            Error::TooManyProps { location, limit } => {
+                write!(f, "more than {} props at {}", limit, location)
+            }
+            Error::TooManyChildren { location, limit } => {
+                write!(f, "more than {} children at {}", limit, location)
+            }
+            Error::TooDeep { location, limit } => {
+                write!(f, "nested deeper than {} at {}", limit, location)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
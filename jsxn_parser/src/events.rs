@@ -0,0 +1,1308 @@
+use crate::{
+    error::{Error, Location, Span},
+    json::{json5_identifier, json_scalar, json_string, JsonValue},
+    jsx::{
+        jsx_element_closing_tag, jsx_fragement_closing_tag, jsx_fragment_opening_tag,
+        jsx_prop_name, jsx_variable, JsxElement, JsxFragment, JsxValue,
+    },
+    jsxn::JsxnValue,
+    options::ParserOptions,
+    shared::{enabled, sp, State},
+    stream::StreamRoot,
+};
+use nom::{
+    branch::alt,
+    character::complete::{alphanumeric1 as alphanumeric, char},
+    combinator::{map, verify},
+    error::{context, VerboseError},
+    sequence::preceded,
+    Err, IResult,
+};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// An event emitted by the pull parser. Values are emitted depth first, with
+/// every start event matched by an end event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The start of a JSON object, followed by a `Key` and a value for each
+    /// entry
+    StartObject,
+
+    /// The key of the next JSON object entry
+    Key(String),
+
+    /// The end of a JSON object
+    EndObject,
+
+    /// The start of a JSON array, followed by its values
+    StartArray,
+
+    /// The end of a JSON array
+    EndArray,
+
+    /// A JSON string, or a JSX prop string
+    Str(String),
+
+    /// A JSON number
+    Num(f64),
+
+    /// A JSON boolean, or a JSX prop without a value
+    Boolean(bool),
+
+    /// A JSON null value
+    Null,
+
+    /// The start of a JSX Element, followed by a `Prop` and a value for each
+    /// prop, then its children
+    StartElement {
+        /// The element type
+        name: String,
+    },
+
+    /// The name of the next JSX prop
+    Prop {
+        /// The prop name
+        name: String,
+    },
+
+    /// The end of a JSX Element
+    EndElement,
+
+    /// The start of a JSX Fragment, followed by its children
+    StartFragment,
+
+    /// The end of a JSX Fragment
+    EndFragment,
+
+    /// JSX text
+    Text(String),
+
+    /// The start of a JSX Expression, followed by its value
+    StartExpression,
+
+    /// The end of a JSX Expression
+    EndExpression,
+
+    /// Input that could not be parsed, emitted in place of a value when
+    /// recovering from errors
+    Error(Span),
+
+    /// A reference to a template variable, as its path
+    Variable(Vec<String>),
+}
+
+/// The whitespace around JSX children
+const SPACE: &[char] = &[' ', '\t', '\r', '\n'];
+
+/// Whether the input starts with the closing tag of an element or fragment
+fn closing_tag(i: &str) -> bool {
+    jsx_element_closing_tag::<VerboseError<&str>>(i).is_ok()
+        || jsx_fragement_closing_tag::<VerboseError<&str>>(i).is_ok()
+}
+
+/// Where a value appears, which decides the kinds of values permitted
+#[derive(Clone, Copy)]
+enum Context {
+    Root,
+    Array,
+    Object,
+    Expression,
+}
+
+#[derive(Clone)]
+enum Frame {
+    Document {
+        started: bool,
+    },
+    Array {
+        items: usize,
+    },
+    Object {
+        entries: usize,
+        value_next: bool,
+    },
+    /// The props of an element starting at offset `start`
+    Props {
+        name: String,
+        start: usize,
+        props: usize,
+    },
+    PropValue,
+
+    /// The children of an element or fragment starting at offset `start`
+    Children {
+        name: Option<String>,
+        start: usize,
+        children: usize,
+    },
+    Expression {
+        done: bool,
+    },
+}
+
+/// A value being built from the events between its start and end
+enum Partial {
+    /// The entries so far, and the key of the next entry
+    Object(BTreeMap<String, JsonValue>, Option<String>),
+    Array(Vec<JsonValue>),
+    Element {
+        name: String,
+        props: BTreeMap<String, JsxValue>,
+
+        /// The name of the prop whose value comes next
+        prop: Option<String>,
+        children: Vec<JsxValue>,
+    },
+    Fragment(Vec<JsxValue>),
+    Expression(Option<JsxValue>),
+}
+
+/// A child of an element or fragment that starts with `<` and is being
+/// parsed as an element or fragment. If that fails, the parser goes back to
+/// the start of the child and reads the `<` as text instead, like the text
+/// of the tree parser, which ends only at a child that parses.
+struct Checkpoint<'a> {
+    /// The offset of the `<`
+    at: usize,
+
+    /// Where the child starts, which is before the `<` if text precedes it
+    start: &'a str,
+
+    /// The children frame, and where it is in the stack
+    frame: Frame,
+    depth: usize,
+
+    /// The number of events held back and of nodes before the child
+    held: usize,
+    nodes: usize,
+}
+
+/// A complete value, before it is placed in the value containing it
+enum Built {
+    Json(JsonValue),
+    Jsx(JsxValue),
+}
+
+impl Built {
+    fn into_json(self) -> JsonValue {
+        match self {
+            Built::Json(json) => json,
+            Built::Jsx(jsx) => JsonValue::JsxValue(Box::new(jsx)),
+        }
+    }
+
+    fn into_jsx(self) -> JsxValue {
+        match self {
+            Built::Json(json) => JsxValue::JsonValue(json),
+            Built::Jsx(jsx) => jsx,
+        }
+    }
+
+    fn into_jsxn(self) -> JsxnValue {
+        match self {
+            Built::Json(json) => JsxnValue::JsonValue(json),
+            Built::Jsx(jsx) => JsxnValue::JsxValue(jsx),
+        }
+    }
+}
+
+/// A pull parser that emits the events of a JSXN document one at a time,
+/// without building the tree
+pub struct Parser<'a> {
+    input: &'a str,
+    rest: &'a str,
+    options: ParserOptions,
+    stack: Vec<Frame>,
+    queue: VecDeque<Event>,
+    nodes: usize,
+
+    /// Children being parsed as elements or fragments, whose events are held
+    /// back until the outermost one ends, along with the input remaining at
+    /// the start and end of each event
+    checkpoints: Vec<Checkpoint<'a>>,
+    held: VecDeque<(Event, &'a str, &'a str)>,
+
+    /// The input remaining at the start and end of the last event, if it was
+    /// held back and the parser has moved on since
+    emitted: Option<(&'a str, &'a str)>,
+
+    /// The offsets of the `<` that were found not to start a child, and are
+    /// read as text
+    text: BTreeSet<usize>,
+
+    /// Errors recovered from, or `None` if the first error ends the events
+    diagnostics: Option<Vec<Error>>,
+
+    /// The input remaining at the start of the last event
+    start: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    /// Create a new pull parser over `input`
+    pub fn new(input: &'a str) -> Parser<'a> {
+        Parser::with_options(input, ParserOptions::STRICT)
+    }
+
+    /// Create a new pull parser over `input` with the given options
+    pub fn with_options(input: &'a str, options: ParserOptions) -> Parser<'a> {
+        Parser {
+            input,
+            rest: input,
+            options,
+            stack: vec![Frame::Document { started: false }],
+            queue: VecDeque::new(),
+            nodes: 0,
+            checkpoints: vec![],
+            held: VecDeque::new(),
+            emitted: None,
+            text: BTreeSet::new(),
+            diagnostics: None,
+            start: input,
+        }
+    }
+
+    /// The location of the last event emitted
+    pub fn location(&self) -> Location {
+        let start = self.emitted.map_or(self.start, |(start, _)| start);
+        Location::of_remainder(self.input, start)
+    }
+
+    /// The byte offset of the end of the input consumed so far
+    pub(crate) fn consumed(&self) -> usize {
+        self.input.len() - self.emitted.map_or(self.rest, |(_, rest)| rest).len()
+    }
+
+    /// Keep going after syntax errors, skipping or closing the input around
+    /// each error and emitting `Event::Error` in place of any value that
+    /// could not be parsed. Errors are collected instead of returned, and
+    /// exceeding a limit ends the events after closing every open value.
+    pub fn recover(mut self) -> Parser<'a> {
+        self.diagnostics = Some(vec![]);
+        self
+    }
+
+    /// The errors recovered from so far
+    pub fn diagnostics(&self) -> &[Error] {
+        self.diagnostics.as_deref().unwrap_or_default()
+    }
+
+    /// Consume the remaining events and build them into a tree
+    pub fn build(mut self) -> Result<JsxnValue, Error> {
+        self.tree()
+    }
+
+    /// Recover from errors while building the events into a tree, returning
+    /// the best effort tree along with every error found
+    pub fn build_recovering(self) -> (JsxnValue, Vec<Error>) {
+        let mut parser = self.recover();
+        let value = match parser.tree() {
+            Ok(value) => value,
+            Err(e) => {
+                let span = parser.span(parser.input);
+                parser.diagnostics.get_or_insert_with(Vec::new).push(e);
+                JsxnValue::JsxValue(JsxValue::Error(span))
+            }
+        };
+        (value, parser.diagnostics.unwrap_or_default())
+    }
+
+    /// A parser continuing inside the root array, element or fragment of a
+    /// stream, after `items` of its items
+    pub(crate) fn inside(
+        input: &'a str,
+        options: ParserOptions,
+        root: &StreamRoot,
+        items: usize,
+    ) -> Parser<'a> {
+        let mut parser = Parser::with_options(input, options);
+        parser.stack = vec![match root {
+            StreamRoot::Array => Frame::Array { items },
+            StreamRoot::Element { r#type, .. } => Frame::Children {
+                name: Some(r#type.clone()),
+                start: 0,
+                children: items,
+            },
+            StreamRoot::Fragment | StreamRoot::Document => Frame::Children {
+                name: None,
+                start: 0,
+                children: items,
+            },
+        }];
+        parser
+    }
+
+    /// Parse the next item of the array, element or fragment the parser is
+    /// inside, or return `None` if it ends instead
+    pub(crate) fn item(&mut self) -> Result<Option<JsxnValue>, Error> {
+        let array = matches!(self.stack.first(), Some(Frame::Array { .. }));
+        Ok(self.value_tree()?.map(|item| match item {
+            item if array => JsxnValue::JsonValue(item.into_json()),
+            item => JsxnValue::JsxValue(item.into_jsx()),
+        }))
+    }
+
+    /// Parse the opening of a root array, element or fragment, stopping
+    /// before its first item. Returns the root and whether any items follow,
+    /// or `None` if the document has another root.
+    pub(crate) fn root_opening(&mut self) -> Result<Option<(StreamRoot, bool)>, Error> {
+        let r#type = match self.expect()? {
+            Event::StartArray => return Ok(Some((StreamRoot::Array, true))),
+            Event::StartFragment => return Ok(Some((StreamRoot::Fragment, true))),
+            Event::StartElement { name } => name,
+            _ => return Ok(None),
+        };
+        let mut props = BTreeMap::new();
+        loop {
+            // Stepping directly stops at the children, which `next` would
+            // start parsing
+            let event = match self.queue.pop_front() {
+                Some(event) => event,
+                None if matches!(self.stack.last(), Some(Frame::Children { .. })) => {
+                    return Ok(Some((StreamRoot::Element { r#type, props }, true)));
+                }
+                None => match self.step()? {
+                    Some(event) => event,
+                    None => continue,
+                },
+            };
+            match event {
+                Event::EndElement => {
+                    return Ok(Some((StreamRoot::Element { r#type, props }, false)))
+                }
+                Event::Prop { name } => match self.value_tree()? {
+                    Some(value) => {
+                        props.insert(name, value.into_jsx());
+                    }
+                    None => return Err(self.error("unexpected event")),
+                },
+                _ => return Err(self.error("unexpected event")),
+            }
+        }
+    }
+
+    fn tree(&mut self) -> Result<JsxnValue, Error> {
+        let value = match self.value_tree()? {
+            Some(value) => value.into_jsxn(),
+            None => return Err(self.error("unexpected event")),
+        };
+        match self.next() {
+            None => Ok(value),
+            Some(Err(e)) => Err(e),
+            Some(Ok(_)) => Err(self.error("unexpected event")),
+        }
+    }
+
+    /// Build the next value from the events, or return `None` if the array,
+    /// element or fragment enclosing it ends instead
+    fn value_tree(&mut self) -> Result<Option<Built>, Error> {
+        let mut stack = vec![];
+        loop {
+            let event = self.expect()?;
+            if stack.is_empty()
+                && matches!(
+                    event,
+                    Event::EndArray | Event::EndElement | Event::EndFragment
+                )
+            {
+                return Ok(None);
+            }
+            let built = match event {
+                Event::StartObject => {
+                    stack.push(Partial::Object(BTreeMap::new(), None));
+                    continue;
+                }
+                Event::StartArray => {
+                    stack.push(Partial::Array(vec![]));
+                    continue;
+                }
+                Event::StartElement { name } => {
+                    stack.push(Partial::Element {
+                        name,
+                        props: BTreeMap::new(),
+                        prop: None,
+                        children: vec![],
+                    });
+                    continue;
+                }
+                Event::StartFragment => {
+                    stack.push(Partial::Fragment(vec![]));
+                    continue;
+                }
+                Event::StartExpression => {
+                    stack.push(Partial::Expression(None));
+                    continue;
+                }
+                Event::Key(key) => match stack.last_mut() {
+                    Some(Partial::Object(_, next @ None)) => {
+                        *next = Some(key);
+                        continue;
+                    }
+                    _ => return Err(self.error("unexpected event")),
+                },
+                Event::Prop { name } => match stack.last_mut() {
+                    Some(Partial::Element {
+                        prop: next @ None, ..
+                    }) => {
+                        *next = Some(name);
+                        continue;
+                    }
+                    _ => return Err(self.error("unexpected event")),
+                },
+                Event::EndObject => match stack.pop() {
+                    Some(Partial::Object(object, None)) => Built::Json(JsonValue::Object(object)),
+                    _ => return Err(self.error("unexpected event")),
+                },
+                Event::EndArray => match stack.pop() {
+                    Some(Partial::Array(array)) => Built::Json(JsonValue::Array(array)),
+                    _ => return Err(self.error("unexpected event")),
+                },
+                Event::EndElement => match stack.pop() {
+                    Some(Partial::Element {
+                        name,
+                        props,
+                        prop: None,
+                        children,
+                    }) => Built::Jsx(JsxValue::JsxElement(JsxElement::new(name, props, children))),
+                    _ => return Err(self.error("unexpected event")),
+                },
+                Event::EndFragment => match stack.pop() {
+                    Some(Partial::Fragment(children)) => {
+                        Built::Jsx(JsxValue::JsxFragment(JsxFragment::new(children)))
+                    }
+                    _ => return Err(self.error("unexpected event")),
+                },
+                Event::EndExpression => match stack.pop() {
+                    Some(Partial::Expression(Some(jsx))) => {
+                        Built::Jsx(JsxValue::JsxExpression(Box::new(jsx)))
+                    }
+                    _ => return Err(self.error("unexpected event")),
+                },
+                Event::Str(s) => Built::Json(JsonValue::Str(s)),
+                Event::Num(num) => Built::Json(JsonValue::Num(num)),
+                Event::Boolean(boolean) => Built::Json(JsonValue::Boolean(boolean)),
+                Event::Null => Built::Json(JsonValue::Null),
+                Event::Text(text) => Built::Jsx(JsxValue::JsonValue(JsonValue::Str(text))),
+                Event::Error(span) => Built::Jsx(JsxValue::Error(span)),
+                Event::Variable(path) => Built::Jsx(JsxValue::Variable(path)),
+            };
+            match stack.last_mut() {
+                None => return Ok(Some(built)),
+                Some(Partial::Object(object, key)) => match key.take() {
+                    Some(key) => {
+                        object.insert(key, built.into_json());
+                    }
+                    None => return Err(self.error("unexpected event")),
+                },
+                Some(Partial::Array(array)) => array.push(built.into_json()),
+                Some(Partial::Element {
+                    props,
+                    prop,
+                    children,
+                    ..
+                }) => match prop.take() {
+                    Some(prop) => {
+                        props.insert(prop, built.into_jsx());
+                    }
+                    None => children.push(built.into_jsx()),
+                },
+                Some(Partial::Fragment(children)) => children.push(built.into_jsx()),
+                Some(Partial::Expression(value @ None)) => *value = Some(built.into_jsx()),
+                Some(Partial::Expression(Some(_))) => return Err(self.error("unexpected event")),
+            }
+        }
+    }
+
+    fn expect(&mut self) -> Result<Event, Error> {
+        match self.next() {
+            Some(result) => result,
+            None => Err(self.error("unexpected end of events")),
+        }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::Syntax {
+            location: Location::of_remainder(self.input, self.rest),
+            message: String::from(message),
+        }
+    }
+
+    /// Run `f` over the remaining input
+    fn run<O, F>(&mut self, f: F) -> Result<O, Error>
+    where
+        F: for<'s> Fn(&'s str, &State<'s>) -> IResult<&'s str, O, VerboseError<&'s str>>,
+    {
+        match self.attempt(&f)? {
+            Some(output) => Ok(output),
+            None => {
+                let state = State::new(&self.options);
+                match f(self.rest, &state) {
+                    Err(e) => Err(Error::from_parse(self.input, &state, e)),
+                    Ok(_) => Err(self.error("invalid input")),
+                }
+            }
+        }
+    }
+
+    /// Run `f` over the remaining input, returning `None` if it does not
+    /// match
+    fn attempt<O, F>(&mut self, f: F) -> Result<Option<O>, Error>
+    where
+        F: for<'s> Fn(&'s str, &State<'s>) -> IResult<&'s str, O, VerboseError<&'s str>>,
+    {
+        let rest = self.rest;
+        let (consumed, output) = {
+            let state = State::new(&self.options);
+            match f(rest, &state) {
+                Ok((input_remainder, output)) => (rest.len() - input_remainder.len(), output),
+                Err(Err::Error(_)) if state.violation().is_none() => return Ok(None),
+                Err(e) => return Err(Error::from_parse(self.input, &state, e)),
+            }
+        };
+        self.rest = &rest[consumed..];
+        Ok(Some(output))
+    }
+
+    fn ws(&mut self) -> Result<(), Error> {
+        self.run(|i, state| map(|i| state.ws(i), |_| ())(i))?;
+        self.start = self.rest;
+        Ok(())
+    }
+
+    fn node(&mut self) -> Result<(), Error> {
+        self.nodes += 1;
+        match self.options.limits.max_nodes {
+            Some(limit) if self.nodes > limit => Err(Error::TooManyNodes {
+                location: self.location(),
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Open a value nested inside the values already open, checking the
+    /// depth limit
+    fn open(&mut self, frame: Frame) -> Result<(), Error> {
+        if let Some(limit) = self.options.limits.max_depth {
+            let depth = self
+                .stack
+                .iter()
+                .filter(|frame| !matches!(frame, Frame::Document { .. } | Frame::PropValue))
+                .count();
+            if depth + 1 > limit {
+                return Err(Error::TooDeep {
+                    location: self.location(),
+                    limit,
+                });
+            }
+        }
+        self.stack.push(frame);
+        Ok(())
+    }
+
+    fn value(&mut self, context: Context) -> Result<Event, Error> {
+        self.ws()?;
+        let roots = self.options.roots;
+        let (object, array, scalar, jsx) = match context {
+            Context::Root => (
+                roots.object,
+                roots.array,
+                roots.scalar,
+                roots.element || roots.fragment,
+            ),
+            Context::Array | Context::Object => (true, true, true, self.options.jsx_in_json),
+            Context::Expression => {
+                let json = self.options.json_in_jsx;
+                (json, json, json, true)
+            }
+        };
+
+        if object && self.attempt(|i, _| char('{')(i))?.is_some() {
+            self.node()?;
+            self.open(Frame::Object {
+                entries: 0,
+                value_next: false,
+            })?;
+            return Ok(Event::StartObject);
+        }
+        if array && self.attempt(|i, _| char('[')(i))?.is_some() {
+            self.node()?;
+            self.open(Frame::Array { items: 0 })?;
+            return Ok(Event::StartArray);
+        }
+        if jsx && self.rest.starts_with('<') {
+            let event = self.jsx_start()?;
+            let allowed = match (context, &event) {
+                (Context::Root, Event::StartFragment) => roots.fragment,
+                (Context::Root, _) => roots.element,
+                _ => true,
+            };
+            if !allowed {
+                return Err(self.error("invalid root"));
+            }
+            return Ok(event);
+        }
+        if let Context::Expression = context {
+            if self.options.variables && self.rest.starts_with('$') {
+                if let Some(JsxValue::Variable(path)) = self.attempt(|i, _| jsx_variable(i))? {
+                    self.node()?;
+                    return Ok(Event::Variable(path));
+                }
+            }
+        }
+        if scalar {
+            if let Some(json) = self.attempt(|i, state| json_scalar(i, state))? {
+                self.node()?;
+                return Ok(match json {
+                    JsonValue::Str(s) => Event::Str(s),
+                    JsonValue::Num(num) => Event::Num(num),
+                    JsonValue::Boolean(boolean) => Event::Boolean(boolean),
+                    _ => Event::Null,
+                });
+            }
+        }
+        Err(self.error(match context {
+            Context::Root => "expected '<' in jsx fragment",
+            Context::Array => "expected ']' in json array",
+            Context::Object => "invalid json key value",
+            Context::Expression => "invalid jsx expression",
+        }))
+    }
+
+    fn jsx_start(&mut self) -> Result<Event, Error> {
+        self.node()?;
+        let start = self.consumed();
+        if self.attempt(|i, _| jsx_fragment_opening_tag(i))?.is_some() {
+            self.open(Frame::Children {
+                name: None,
+                start,
+                children: 0,
+            })?;
+            return Ok(Event::StartFragment);
+        }
+        let name = self
+            .attempt(|i, state| {
+                map(
+                    preceded(char('<'), preceded(|i| state.ws(i), alphanumeric)),
+                    String::from,
+                )(i)
+            })?
+            .ok_or_else(|| self.error("invalid jsx element opening tag"))?;
+        self.open(Frame::Props {
+            name: name.clone(),
+            start,
+            props: 0,
+        })?;
+        Ok(Event::StartElement { name })
+    }
+
+    fn expression_start(&mut self) -> Result<Event, Error> {
+        self.run(|i, _| char('{')(i))?;
+        self.node()?;
+        self.open(Frame::Expression { done: false })?;
+        Ok(Event::StartExpression)
+    }
+
+    /// Consume the separator before the next item of an array or object,
+    /// returning whether the array or object was closed instead
+    fn separator(&mut self, count: usize, close: char, context: &str) -> Result<bool, Error> {
+        self.ws()?;
+        if self.attempt(|i, _| char(close)(i))?.is_some() {
+            return Ok(true);
+        }
+        if count == 0 {
+            return Ok(false);
+        }
+        // Like a missing separator, a stray comma is reported where it is
+        let error = self.error(&format!("expected '{}' in {}", close, context));
+        if self.attempt(|i, _| char(',')(i))?.is_none() {
+            return Err(error);
+        }
+        self.ws()?;
+        if !self.options.trailing_commas && self.rest.starts_with(&[',', close][..]) {
+            return Err(error);
+        }
+        Ok(self.options.trailing_commas && self.attempt(|i, _| char(close)(i))?.is_some())
+    }
+
+    /// The span from `start`, a suffix of the input, to the remaining input
+    fn span(&self, start: &str) -> Span {
+        Span {
+            start: Location::of_remainder(self.input, start),
+            end: Location::of_remainder(self.input, self.rest),
+        }
+    }
+
+    /// Skip at least one character, and then up to the next of `stops`
+    /// outside of a string
+    fn skip(&mut self, stops: &str) -> Span {
+        let start = self.rest;
+        let mut end = start.len();
+        let mut quote = None;
+        let mut escaped = false;
+        for (index, c) in start.char_indices() {
+            match quote {
+                Some(_) if escaped => escaped = false,
+                Some(_) if c == '\\' => escaped = true,
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if index > 0 && stops.contains(c) => {
+                    end = index;
+                    break;
+                }
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None => {}
+            }
+        }
+        self.rest = &start[end..];
+        self.span(start)
+    }
+
+    /// Whether the remaining input closes a value enclosing the frame on top
+    /// of the stack
+    fn closes_ancestor(&self) -> bool {
+        let rest = self.rest;
+        let ancestors = &self.stack[..self.stack.len().saturating_sub(1)];
+        ancestors.iter().any(|frame| match frame {
+            Frame::Array { .. } => rest.starts_with(']'),
+            Frame::Object { .. } | Frame::Expression { .. } => rest.starts_with('}'),
+            Frame::Children {
+                name: Some(name), ..
+            } => match jsx_element_closing_tag::<VerboseError<&str>>(rest) {
+                Ok((_, closing_tag)) => closing_tag == name,
+                Err(_) => false,
+            },
+            Frame::Children { name: None, .. } => {
+                jsx_fragement_closing_tag::<VerboseError<&str>>(rest).is_ok()
+            }
+            _ => false,
+        })
+    }
+
+    /// Start parsing the child at `self.start`, whose `<` is at offset `at`,
+    /// as an element or fragment, unless that is already under way after
+    /// the text before it
+    fn checkpoint(&mut self, at: usize, frame: Frame) {
+        if self.checkpoints.last().map(|checkpoint| checkpoint.at) == Some(at) {
+            return;
+        }
+        self.checkpoints.push(Checkpoint {
+            at,
+            start: self.start,
+            frame,
+            depth: self.stack.len() - 1,
+            held: self.held.len(),
+            nodes: self.nodes,
+        });
+    }
+
+    /// Stop holding back the events of the children that have ended
+    fn resolve(&mut self) {
+        while let Some(checkpoint) = self.checkpoints.last() {
+            let consumed = self.input.len() - self.rest.len();
+            if consumed <= checkpoint.at || self.stack.len() > checkpoint.depth + 1 {
+                return;
+            }
+            self.checkpoints.pop();
+        }
+    }
+
+    /// Go back to the start of the innermost child being parsed as an
+    /// element or fragment after a syntax error inside it, to read it as
+    /// text instead. Errors inside expressions are not undone, like the
+    /// tree parser does not backtrack out of them.
+    fn backtrack(&mut self, error: &Error) -> bool {
+        let checkpoint = match self.checkpoints.last() {
+            Some(checkpoint) if matches!(error, Error::Syntax { .. }) => checkpoint,
+            _ => return false,
+        };
+        if self.stack[checkpoint.depth + 1..]
+            .iter()
+            .any(|frame| matches!(frame, Frame::Expression { .. }))
+        {
+            return false;
+        }
+        let checkpoint = self.checkpoints.pop().unwrap();
+        self.text.insert(checkpoint.at);
+        self.rest = checkpoint.start;
+        self.start = checkpoint.start;
+        self.stack.truncate(checkpoint.depth);
+        self.stack.push(checkpoint.frame);
+        self.held.truncate(checkpoint.held);
+        self.queue.clear();
+        self.nodes = checkpoint.nodes;
+        true
+    }
+
+    /// Close every open value, ending the events
+    fn close_all(&mut self) {
+        let end = &self.input[self.input.len()..];
+        while let Some(frame) = self.stack.pop() {
+            let events = match frame {
+                Frame::Document { started: false } => {
+                    vec![Event::Error(self.span(self.rest))]
+                }
+                Frame::Document { started: true } => vec![],
+                Frame::Array { .. } => vec![Event::EndArray],
+                Frame::Object {
+                    value_next: true, ..
+                } => vec![Event::Error(self.span(self.rest)), Event::EndObject],
+                Frame::Object { .. } => vec![Event::EndObject],
+                Frame::Props { .. } | Frame::Children { name: Some(_), .. } => {
+                    vec![Event::EndElement]
+                }
+                Frame::Children { name: None, .. } => vec![Event::EndFragment],
+                Frame::PropValue => vec![Event::Error(self.span(self.rest))],
+                Frame::Expression { done: false } => {
+                    vec![Event::Error(self.span(self.rest)), Event::EndExpression]
+                }
+                Frame::Expression { done: true } => vec![Event::EndExpression],
+            };
+            self.queue.extend(events);
+            self.rest = end;
+        }
+    }
+
+    /// Continue after a syntax error in the frame on top of the stack, by
+    /// skipping input, inserting an error value, or closing the frame
+    fn recover_frame(&mut self) {
+        self.rest = self.rest.trim_start();
+        let frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        if self.rest.is_empty() {
+            self.stack.push(frame);
+            return self.close_all();
+        }
+        let closes_ancestor = {
+            self.stack.push(frame.clone());
+            let closes_ancestor = self.closes_ancestor();
+            self.stack.pop();
+            closes_ancestor
+        };
+
+        match frame {
+            Frame::Document { started: false } => {
+                let start = self.rest;
+                self.rest = &self.input[self.input.len()..];
+                self.queue.push_back(Event::Error(self.span(start)));
+            }
+            Frame::Document { started: true } => self.rest = &self.input[self.input.len()..],
+            Frame::Array { .. } | Frame::Object { .. } if closes_ancestor => {
+                self.queue.push_back(match frame {
+                    Frame::Array { .. } => Event::EndArray,
+                    _ => Event::EndObject,
+                });
+            }
+            Frame::Array { items } => {
+                if self.rest.starts_with(']') {
+                    self.rest = &self.rest[1..];
+                    self.queue.push_back(Event::EndArray);
+                    return;
+                }
+                let span = if self.rest.starts_with(',') {
+                    self.span(self.rest)
+                } else {
+                    self.skip(",]}")
+                };
+                self.queue.push_back(Event::Error(span));
+                self.stack.push(Frame::Array { items: items + 1 });
+            }
+            Frame::Object {
+                entries,
+                value_next: true,
+            } => {
+                let span = if self.rest.starts_with(&[',', '}'][..]) {
+                    self.span(self.rest)
+                } else {
+                    self.skip(",}")
+                };
+                self.queue.push_back(Event::Error(span));
+                self.stack.push(Frame::Object {
+                    entries,
+                    value_next: false,
+                });
+            }
+            Frame::Object { entries, .. } => {
+                if self.rest.starts_with('}') {
+                    self.rest = &self.rest[1..];
+                    self.queue.push_back(Event::EndObject);
+                    return;
+                }
+                let missing_entry = self.rest.starts_with(',');
+                self.skip(",}");
+                self.stack.push(Frame::Object {
+                    entries: if missing_entry { entries } else { entries + 1 },
+                    value_next: false,
+                });
+            }
+            Frame::Props { .. } if closes_ancestor || self.rest.starts_with('<') => {
+                self.queue.push_back(Event::EndElement);
+            }
+            Frame::Props { .. } => {
+                self.skip(" \t\r\n/><");
+                self.stack.push(frame);
+            }
+            Frame::PropValue => {
+                let span = if self.rest.starts_with(&['/', '>'][..]) {
+                    self.span(self.rest)
+                } else {
+                    self.skip(" \t\r\n/><")
+                };
+                self.queue.push_back(Event::Error(span));
+            }
+            Frame::Expression { done: false } => {
+                let span = if closes_ancestor || self.rest.starts_with('}') {
+                    self.span(self.rest)
+                } else {
+                    self.skip("}<")
+                };
+                self.queue.push_back(Event::Error(span));
+                self.stack.push(Frame::Expression { done: true });
+            }
+            Frame::Expression { done: true } => {
+                // The expression already has its value, so input before its
+                // `}` becomes a child after it, or is skipped in a prop
+                let span = if closes_ancestor {
+                    None
+                } else {
+                    let span = self.skip("}<");
+                    if self.rest.starts_with('}') {
+                        self.rest = &self.rest[1..];
+                    }
+                    Some(span)
+                };
+                self.queue.push_back(Event::EndExpression);
+                if let (Some(span), Some(Frame::Children { children, .. })) =
+                    (span, self.stack.last_mut())
+                {
+                    *children += 1;
+                    self.queue.push_back(Event::Error(span));
+                }
+            }
+            Frame::Children { name, .. } if closes_ancestor => {
+                self.queue.push_back(match name {
+                    Some(_) => Event::EndElement,
+                    None => Event::EndFragment,
+                });
+            }
+            Frame::Children {
+                name,
+                start: element,
+                children,
+            } => {
+                let start = self.rest;
+                if start.starts_with("</") {
+                    self.rest = match start.find('>') {
+                        Some(index) => &start[index + 1..],
+                        None => &start[start.len()..],
+                    };
+                    self.queue.push_back(Event::Error(self.span(start)));
+                    self.stack.push(Frame::Children {
+                        name,
+                        start: element,
+                        children: children + 1,
+                    });
+                } else {
+                    let span = self.skip("<{");
+                    self.queue.push_back(Event::Error(span));
+                    self.stack.push(Frame::Children {
+                        name,
+                        start: element,
+                        children: children + 1,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Parse the next event of the frame on top of the stack, if it has one
+    fn step(&mut self) -> Result<Option<Event>, Error> {
+        let frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+        match frame {
+            Frame::Document { started: false } => {
+                if let Some(limit) = self.options.limits.max_input_bytes {
+                    if self.input.len() > limit {
+                        return Err(Error::InputTooLarge {
+                            location: Location::new(self.input, limit),
+                            limit,
+                        });
+                    }
+                }
+                self.stack.push(Frame::Document { started: true });
+                self.value(Context::Root).map(Some)
+            }
+            Frame::Document { started: true } => {
+                self.ws()?;
+                if self.rest.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(Error::trailing_input(self.input, self.rest))
+                }
+            }
+            Frame::Array { items } => {
+                if self.separator(items, ']', "json array")? {
+                    return Ok(Some(Event::EndArray));
+                }
+                self.stack.push(Frame::Array { items: items + 1 });
+                self.value(Context::Array).map(Some)
+            }
+            Frame::Object {
+                entries,
+                value_next: true,
+            } => {
+                self.stack.push(Frame::Object {
+                    entries,
+                    value_next: false,
+                });
+                self.value(Context::Object).map(Some)
+            }
+            Frame::Object {
+                entries,
+                value_next: false,
+            } => {
+                if self.separator(entries, '}', "json object")? {
+                    return Ok(Some(Event::EndObject));
+                }
+                let json5 = self.options.json5;
+                let key = self
+                    .attempt(|i, state| {
+                        alt((
+                            |i| json_string(i, state),
+                            enabled(json5, |i| json5_identifier(i, state)),
+                        ))(i)
+                    })?
+                    .ok_or_else(|| self.error("expected '}' in json object"))?;
+                self.run(|i, state| {
+                    context("json key value", preceded(|i| state.ws(i), char(':')))(i)
+                })?;
+                self.stack.push(Frame::Object {
+                    entries: entries + 1,
+                    value_next: true,
+                });
+                Ok(Some(Event::Key(key)))
+            }
+            Frame::Props { name, start, props } => {
+                self.ws()?;
+                if self
+                    .attempt(|i, state| {
+                        preceded(char('/'), preceded(|i| state.ws(i), char('>')))(i)
+                    })?
+                    .is_some()
+                {
+                    return Ok(Some(Event::EndElement));
+                }
+                if self.attempt(|i, _| char('>')(i))?.is_some() {
+                    self.stack.push(Frame::Children {
+                        name: Some(name),
+                        start,
+                        children: 0,
+                    });
+                    return Ok(None);
+                }
+                let prop = self
+                    .attempt(|i, _| map(jsx_prop_name, String::from)(i))?
+                    .ok_or_else(|| self.error("invalid jsx element opening tag"))?;
+                if let Some(limit) = self.options.limits.max_props {
+                    if props + 1 > limit {
+                        return Err(Error::TooManyProps {
+                            location: Location::new(self.input, start),
+                            limit,
+                        });
+                    }
+                }
+                self.stack.push(Frame::Props {
+                    name,
+                    start,
+                    props: props + 1,
+                });
+                if self
+                    .attempt(|i, state| preceded(|i| state.ws(i), char('='))(i))?
+                    .is_some()
+                {
+                    self.stack.push(Frame::PropValue);
+                } else {
+                    self.node()?;
+                    self.queue.push_back(Event::Boolean(true));
+                }
+                Ok(Some(Event::Prop { name: prop }))
+            }
+            Frame::PropValue => {
+                self.ws()?;
+                if self.rest.starts_with('{') {
+                    return self.expression_start().map(Some);
+                }
+                let value = self
+                    .attempt(|i, state| json_string(i, state))?
+                    .ok_or_else(|| self.error("invalid jsx element opening tag"))?;
+                self.node()?;
+                Ok(Some(Event::Str(value)))
+            }
+            Frame::Expression { done: false } => {
+                self.stack.push(Frame::Expression { done: true });
+                self.value(Context::Expression).map(Some)
+            }
+            Frame::Expression { done: true } => {
+                self.run(|i, state| {
+                    context("jsx expression", preceded(|i| state.ws(i), char('}')))(i)
+                })?;
+                Ok(Some(Event::EndExpression))
+            }
+            Frame::Children {
+                name,
+                start,
+                children,
+            } => {
+                let closed = match &name {
+                    Some(name) => self
+                        .attempt(|i, _| {
+                            map(
+                                verify(jsx_element_closing_tag, |closing_tag: &str| {
+                                    closing_tag == name
+                                }),
+                                |_| (),
+                            )(i)
+                        })?
+                        .map(|_| Event::EndElement),
+                    None => self
+                        .attempt(|i, _| jsx_fragement_closing_tag(i))?
+                        .map(|_| Event::EndFragment),
+                };
+                if closed.is_some() {
+                    return Ok(closed);
+                }
+
+                self.run(|i, _| sp(i).map(|(i, _)| (i, ())))?;
+                self.start = self.rest;
+                let closing = closing_tag(self.rest);
+                if self.rest.is_empty() || closing {
+                    return Err(self.error(match (&name, closing) {
+                        (Some(_), false) => "expected '<' in jsx element closing tag",
+                        (Some(_), true) => "invalid jsx element closing tag",
+                        (None, false) => "expected '<' in jsx fragment closing tag",
+                        (None, true) => "invalid jsx fragment closing tag",
+                    }));
+                }
+                if let Some(limit) = self.options.limits.max_children {
+                    if children + 1 > limit {
+                        return Err(Error::TooManyChildren {
+                            location: Location::new(self.input, start),
+                            limit,
+                        });
+                    }
+                }
+                let frame = Frame::Children {
+                    name: name.clone(),
+                    start,
+                    children,
+                };
+                self.stack.push(Frame::Children {
+                    name,
+                    start,
+                    children: children + 1,
+                });
+                if self.rest.starts_with('{') {
+                    return self.expression_start().map(Some);
+                }
+                let at = self.consumed();
+                if self.rest.starts_with('<') && !self.text.contains(&at) {
+                    self.checkpoint(at, frame);
+                    return self.jsx_start().map(Some);
+                }
+
+                // Text runs up to the next child, without the whitespace
+                // before it. A `<` that does not start a child is part of
+                // the text.
+                let mut end = self.rest.len();
+                let mut from = 0;
+                while let Some(index) = self.rest[from..].find(&['<', '{'][..]) {
+                    let index = from + index;
+                    let rest = &self.rest[index..];
+                    if rest.starts_with('{') || closing_tag(rest) {
+                        end = index;
+                        break;
+                    }
+                    if !self.text.contains(&(at + index)) {
+                        self.checkpoint(at + index, frame);
+                        end = index;
+                        break;
+                    }
+                    from = index + 1;
+                }
+                let text = self.rest[..end].trim_end_matches(SPACE);
+                if let Some(limit) = self.options.limits.max_string_length {
+                    if text.len() > limit {
+                        return Err(Error::StringTooLong {
+                            location: self.location(),
+                            limit,
+                        });
+                    }
+                }
+                self.node()?;
+                self.rest = &self.rest[text.len()..];
+                Ok(Some(Event::Text(String::from(text))))
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Parser<'a> {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.resolve();
+            if self.checkpoints.is_empty() {
+                if let Some((event, start, rest)) = self.held.pop_front() {
+                    self.emitted = Some((start, rest));
+                    return Some(Ok(event));
+                }
+                self.emitted = None;
+                if let Some(event) = self.queue.pop_front() {
+                    return Some(Ok(event));
+                }
+            } else if let Some(event) = self.queue.pop_front() {
+                self.held.push_back((event, self.start, self.rest));
+                continue;
+            }
+            let frame = match self.stack.last() {
+                Some(frame) => frame.clone(),
+                None => return None,
+            };
+            let depth = self.stack.len();
+            match self.step() {
+                Ok(Some(event)) if self.checkpoints.is_empty() => return Some(Ok(event)),
+                Ok(Some(event)) => self.held.push_back((event, self.start, self.rest)),
+                Ok(None) => {}
+                Err(e) => {
+                    self.stack.truncate(depth - 1);
+                    self.stack.push(frame);
+                    if self.backtrack(&e) {
+                        continue;
+                    }
+                    self.checkpoints.clear();
+                    match &mut self.diagnostics {
+                        Some(diagnostics) => {
+                            let stop = !matches!(e, Error::Syntax { .. });
+                            diagnostics.push(e);
+                            if stop {
+                                self.close_all();
+                            } else {
+                                self.recover_frame();
+                            }
+                        }
+                        None => {
+                            self.stack.clear();
+                            self.queue.clear();
+                            self.held.clear();
+                            return Some(Err(e));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
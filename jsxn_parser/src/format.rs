@@ -0,0 +1,820 @@
+use crate::{
+    error::Error,
+    events::{Event, Parser},
+    json::JsonValue,
+    jsx::JsxValue,
+    jsxn::JsxnValue,
+    options::ParserOptions,
+};
+use std::slice;
+
+/// Options for the layout of formatted JSXN
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// The number of spaces to indent each level by
+    pub indent: usize,
+
+    /// The column that elements and tags are kept within on a single line
+    /// where possible
+    pub line_width: usize,
+}
+
+impl FormatOptions {
+    /// Two spaces of indentation and lines of up to 80 columns, which is
+    /// also the default
+    pub const DEFAULT: FormatOptions = FormatOptions {
+        indent: 2,
+        line_width: 80,
+    };
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions::DEFAULT
+    }
+}
+
+/// Write a JSXN value back as source, in the same layout as `format_source`
+pub fn format(jsxn: &JsxnValue) -> String {
+    format_with_options(jsxn, &FormatOptions::DEFAULT)
+}
+
+/// Write a JSXN value back as source with the given options
+pub fn format_with_options(jsxn: &JsxnValue, options: &FormatOptions) -> String {
+    let node = match jsxn {
+        JsxnValue::JsxValue(jsx) => Node::from_jsx(jsx),
+        JsxnValue::JsonValue(json) => Node::from_json(json),
+    };
+    print(&Item::new(node), &[], options)
+}
+
+/// Parse JSXN source and write it back in its canonical layout, keeping its
+/// comments and blank lines, and the order of its props and object keys.
+/// The layout only depends on these and the values of the source, so
+/// formatting the output again does not change it.
+///
+/// - Non-empty JSON arrays and objects have one item per line, like
+///   `serde_json::to_string_pretty`.
+/// - Elements are kept on one line if they fit within the line width, and
+///   otherwise have one child per line.
+/// - A tag that does not fit has one prop per line, with the `>` or `/>`
+///   on a line of its own.
+/// - Each comment is put on a line of its own before the item, prop or
+///   closing bracket that follows it, and single blank lines are kept
+///   between items, props and children.
+/// - Strings and text are kept as written, and numbers are written in
+///   their shortest form.
+pub fn format_source(
+    input: &str,
+    parser_options: &ParserOptions,
+    options: &FormatOptions,
+) -> Result<String, Error> {
+    let mut builder = Builder {
+        input,
+        parser: Parser::with_options(input, *parser_options),
+        cursor: 0,
+    };
+    let (event, slice) = builder.next()?;
+    let root = builder.value(event, slice)?;
+    if let Some(result) = builder.parser.next() {
+        result?;
+        return Err(builder.unexpected());
+    }
+    let (trailing, _) = leading_trivia(&input[builder.cursor..]);
+    Ok(print(&root, &trailing, options))
+}
+
+/// A comment or blank line between the tokens of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Trivia {
+    BlankLine,
+    Comment(String),
+}
+
+/// A node and the trivia before it
+struct Item {
+    leading: Vec<Trivia>,
+    node: Node,
+}
+
+impl Item {
+    fn new(node: Node) -> Item {
+        Item {
+            leading: vec![],
+            node,
+        }
+    }
+}
+
+struct Prop {
+    leading: Vec<Trivia>,
+    name: String,
+
+    /// The value, or `None` for a prop without one
+    value: Option<Node>,
+}
+
+/// The layout of a JSXN tree. The `dangling` trivia of a node comes before
+/// its closing bracket.
+enum Node {
+    /// An element, or a fragment if `r#type` is `None`
+    Element {
+        r#type: Option<String>,
+        props: Vec<Prop>,
+        dangling: Vec<Trivia>,
+        children: Vec<Item>,
+    },
+    Expression {
+        value: Box<Item>,
+        dangling: Vec<Trivia>,
+    },
+    Text(String),
+
+    /// A scalar or variable, written as it is printed
+    Atom(String),
+    Array {
+        items: Vec<Item>,
+        dangling: Vec<Trivia>,
+    },
+    Object {
+        entries: Vec<(String, Item)>,
+        dangling: Vec<Trivia>,
+    },
+}
+
+impl Node {
+    fn from_jsx(jsx: &JsxValue) -> Node {
+        match jsx {
+            JsxValue::JsxElement(element) => Node::Element {
+                r#type: Some(String::from(element.r#type())),
+                props: element
+                    .props()
+                    .iter()
+                    .map(|(name, value)| Prop {
+                        leading: vec![],
+                        name: name.clone(),
+                        value: match value {
+                            JsxValue::JsonValue(JsonValue::Boolean(true)) => None,
+                            JsxValue::JsonValue(JsonValue::Str(s)) => Some(Node::Atom(quote(s))),
+                            JsxValue::JsxExpression(jsx) => Some(Node::expression(jsx)),
+                            value => Some(Node::expression(value)),
+                        },
+                    })
+                    .collect(),
+                dangling: vec![],
+                children: Node::children(element.children()),
+            },
+            JsxValue::JsxFragment(fragment) => Node::Element {
+                r#type: None,
+                props: vec![],
+                dangling: vec![],
+                children: Node::children(fragment.children()),
+            },
+            JsxValue::JsonValue(json) => Node::from_json(json),
+            JsxValue::JsxExpression(jsx) => Node::expression(jsx),
+            JsxValue::Variable(path) => Node::Atom(format!("${}", path.join("."))),
+            JsxValue::Error(_) => Node::Atom(String::from("null")),
+        }
+    }
+
+    fn from_json(json: &JsonValue) -> Node {
+        match json {
+            JsonValue::Str(s) => Node::Atom(quote(s)),
+            JsonValue::Boolean(boolean) => Node::Atom(boolean.to_string()),
+            JsonValue::Num(num) => Node::Atom(number(*num)),
+            JsonValue::Null => Node::Atom(String::from("null")),
+            JsonValue::Array(array) => Node::Array {
+                items: array
+                    .iter()
+                    .map(|item| Item::new(Node::from_json(item)))
+                    .collect(),
+                dangling: vec![],
+            },
+            JsonValue::Object(object) => Node::Object {
+                entries: object
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Item::new(Node::from_json(value))))
+                    .collect(),
+                dangling: vec![],
+            },
+            JsonValue::JsxValue(jsx) => Node::from_jsx(jsx),
+        }
+    }
+
+    fn expression(jsx: &JsxValue) -> Node {
+        Node::Expression {
+            value: Box::new(Item::new(Node::from_jsx(jsx))),
+            dangling: vec![],
+        }
+    }
+
+    /// Children are text, elements and expressions, so any other value is
+    /// wrapped in an expression
+    fn children(children: &[JsxValue]) -> Vec<Item> {
+        children
+            .iter()
+            .filter_map(|child| match child {
+                JsxValue::Error(_) => None,
+                JsxValue::JsonValue(JsonValue::Str(text)) => Some(Node::Text(text.clone())),
+                JsxValue::JsxElement(_) | JsxValue::JsxFragment(_) | JsxValue::JsxExpression(_) => {
+                    Some(Node::from_jsx(child))
+                }
+                child => Some(Node::expression(child)),
+            })
+            .map(Item::new)
+            .collect()
+    }
+}
+
+/// Strings are stored with the escapes of a JSON string
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s)
+}
+
+fn number(num: f64) -> String {
+    if num.is_nan() {
+        String::from("NaN")
+    } else if num.is_infinite() {
+        String::from(if num > 0.0 { "Infinity" } else { "-Infinity" })
+    } else if num != 0.0 && (num.abs() >= 1e21 || num.abs() < 1e-6) {
+        format!("{:e}", num)
+    } else {
+        num.to_string()
+    }
+}
+
+/// Builds the layout of source from the events of a pull parser, taking
+/// the trivia of each event from the source consumed for it
+struct Builder<'a> {
+    input: &'a str,
+    parser: Parser<'a>,
+    cursor: usize,
+}
+
+impl<'a> Builder<'a> {
+    /// The next event and the source consumed for it. Whitespace after the
+    /// event is left to the next one, which it separates from the event.
+    fn next(&mut self) -> Result<(Event, &'a str), Error> {
+        let event = match self.parser.next() {
+            Some(event) => event?,
+            None => return Err(self.unexpected()),
+        };
+        let start = self.cursor;
+        self.cursor += self.input[start..self.parser.consumed()].trim_end().len();
+        Ok((event, &self.input[start..self.cursor]))
+    }
+
+    fn unexpected(&self) -> Error {
+        Error::Syntax {
+            location: self.parser.location(),
+            message: String::from("unexpected event"),
+        }
+    }
+
+    fn value(&mut self, event: Event, slice: &str) -> Result<Item, Error> {
+        let node = match event {
+            Event::StartObject => self.object()?,
+            Event::StartArray => self.array()?,
+            Event::StartElement { name } => self.element(name)?,
+            Event::StartFragment => self.fragment()?,
+            Event::StartExpression => self.expression()?,
+            Event::Str(s) => Node::Atom(quote(&s)),
+            Event::Num(num) => Node::Atom(number(num)),
+            Event::Boolean(boolean) => Node::Atom(boolean.to_string()),
+            Event::Null => Node::Atom(String::from("null")),
+            Event::Variable(path) => Node::Atom(format!("${}", path.join("."))),
+            _ => return Err(self.unexpected()),
+        };
+        Ok(Item {
+            leading: trivia(slice),
+            node,
+        })
+    }
+
+    fn object(&mut self) -> Result<Node, Error> {
+        let mut entries = vec![];
+        loop {
+            match self.next()? {
+                (Event::Key(key), slice) => {
+                    let mut leading = trivia(slice);
+                    let (event, slice) = self.next()?;
+                    let mut value = self.value(event, slice)?;
+                    leading.append(&mut value.leading);
+                    value.leading = leading;
+                    entries.push((key, value));
+                }
+                (Event::EndObject, slice) => {
+                    return Ok(Node::Object {
+                        entries,
+                        dangling: trivia(slice),
+                    })
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Node, Error> {
+        let mut items = vec![];
+        loop {
+            match self.next()? {
+                (Event::EndArray, slice) => {
+                    return Ok(Node::Array {
+                        items,
+                        dangling: trivia(slice),
+                    })
+                }
+                (event, slice) => items.push(self.value(event, slice)?),
+            }
+        }
+    }
+
+    fn element(&mut self, name: String) -> Result<Node, Error> {
+        let mut props = vec![];
+        loop {
+            match self.next()? {
+                (Event::Prop { name: prop }, slice) => {
+                    let mut leading = trivia(slice);
+                    let (event, slice) = self.next()?;
+                    leading.extend(trivia(slice));
+                    let value = match event {
+                        Event::Boolean(true) => None,
+                        Event::Str(s) => Some(Node::Atom(quote(&s))),
+                        Event::StartExpression => Some(self.expression()?),
+                        _ => return Err(self.unexpected()),
+                    };
+                    props.push(Prop {
+                        leading,
+                        name: prop,
+                        value,
+                    });
+                }
+                (Event::EndElement, slice) => {
+                    return Ok(Node::Element {
+                        r#type: Some(name),
+                        props,
+                        dangling: trivia(slice),
+                        children: vec![],
+                    })
+                }
+                (event, slice) => {
+                    // The first child comes after the `>` of the opening tag,
+                    // and the trivia before the `>` is part of the tag
+                    let (dangling, token) = leading_trivia(slice);
+                    let rest = &slice[token..];
+                    let rest = rest.strip_prefix('>').unwrap_or(rest);
+                    return Ok(Node::Element {
+                        r#type: Some(name),
+                        props,
+                        dangling,
+                        children: self.children(Some((event, rest)))?,
+                    });
+                }
+            }
+        }
+    }
+
+    fn fragment(&mut self) -> Result<Node, Error> {
+        Ok(Node::Element {
+            r#type: None,
+            props: vec![],
+            dangling: vec![],
+            children: self.children(None)?,
+        })
+    }
+
+    /// Children cannot have comments between them, only blank lines
+    fn children(&mut self, mut first: Option<(Event, &str)>) -> Result<Vec<Item>, Error> {
+        let mut children = vec![];
+        loop {
+            let (event, slice) = match first.take() {
+                Some((event, slice)) => (event, blank_lines(slice)),
+                None => {
+                    let (event, slice) = self.next()?;
+                    (event, blank_lines(slice))
+                }
+            };
+            let node = match event {
+                Event::EndElement | Event::EndFragment => return Ok(children),
+                Event::Text(text) => Node::Text(text),
+                Event::StartElement { name } => self.element(name)?,
+                Event::StartFragment => self.fragment()?,
+                Event::StartExpression => self.expression()?,
+                _ => return Err(self.unexpected()),
+            };
+            children.push(Item {
+                leading: slice,
+                node,
+            });
+        }
+    }
+
+    fn expression(&mut self) -> Result<Node, Error> {
+        let (event, slice) = self.next()?;
+        let value = self.value(event, slice)?;
+        match self.next()? {
+            (Event::EndExpression, slice) => Ok(Node::Expression {
+                value: Box::new(value),
+                dangling: trivia(slice),
+            }),
+            _ => Err(self.unexpected()),
+        }
+    }
+}
+
+/// The trivia of the source consumed for an event. Comments after the
+/// start of its token, such as between a key and its colon, are moved
+/// before it.
+fn trivia(slice: &str) -> Vec<Trivia> {
+    let (mut trivia, token) = leading_trivia(slice);
+    trivia.extend(comments(&slice[token..]));
+    trivia
+}
+
+/// The comments and blank lines before the first token of `slice`, skipping
+/// separating commas, and the offset of the token
+fn leading_trivia(slice: &str) -> (Vec<Trivia>, usize) {
+    let mut trivia = vec![];
+    let mut newlines = 0;
+    let mut rest = slice;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("//") || rest.starts_with("/*") {
+            if newlines >= 2 {
+                trivia.push(Trivia::BlankLine);
+            }
+            newlines = 0;
+            let comment = &rest[..comment_len(rest)];
+            trivia.push(Trivia::Comment(String::from(comment.trim_end())));
+            rest = &rest[comment.len()..];
+        } else if c.is_whitespace() || c == ',' {
+            if c == '\n' {
+                newlines += 1;
+            }
+            rest = &rest[c.len_utf8()..];
+        } else {
+            break;
+        }
+    }
+    if newlines >= 2 {
+        trivia.push(Trivia::BlankLine);
+    }
+    (trivia, slice.len() - rest.len())
+}
+
+/// The comments in a token, outside of its strings
+fn comments(token: &str) -> Vec<Trivia> {
+    let mut comments = vec![];
+    let mut quote = None;
+    let mut escaped = false;
+    let mut index = 0;
+    while let Some(c) = token[index..].chars().next() {
+        let rest = &token[index..];
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if rest.starts_with("//") || rest.starts_with("/*") => {
+                let comment = &rest[..comment_len(rest)];
+                comments.push(Trivia::Comment(String::from(comment.trim_end())));
+                index += comment.len();
+                continue;
+            }
+            None => {}
+        }
+        index += c.len_utf8();
+    }
+    comments
+}
+
+/// The length of the comment at the start of `rest`
+fn comment_len(rest: &str) -> usize {
+    if rest.starts_with("//") {
+        rest.find('\n').unwrap_or(rest.len())
+    } else {
+        rest[2..].find("*/").map_or(rest.len(), |end| end + 4)
+    }
+}
+
+/// A blank line if there is one before the first token of `slice`
+fn blank_lines(slice: &str) -> Vec<Trivia> {
+    let whitespace = &slice[..slice.len() - slice.trim_start().len()];
+    if whitespace.matches('\n').count() >= 2 {
+        vec![Trivia::BlankLine]
+    } else {
+        vec![]
+    }
+}
+
+fn print(root: &Item, trailing: &[Trivia], options: &FormatOptions) -> String {
+    let mut printer = Printer {
+        out: String::new(),
+        options,
+    };
+    let mut started = false;
+    let blank = printer.comments(&root.leading, 0, &mut started);
+    if started {
+        printer.line(0, blank);
+    }
+    printer.node(&root.node, 0);
+    printer.comments(trailing, 0, &mut true);
+    printer.out.push('\n');
+    printer.out
+}
+
+struct Printer<'o> {
+    out: String,
+    options: &'o FormatOptions,
+}
+
+impl<'o> Printer<'o> {
+    /// Print on a single line, or return `None` if the layout requires a
+    /// line break
+    fn flat<F>(&self, f: F) -> Option<String>
+    where
+        F: FnOnce(&mut Printer),
+    {
+        let options = FormatOptions {
+            line_width: usize::MAX,
+            ..*self.options
+        };
+        let mut printer = Printer {
+            out: String::new(),
+            options: &options,
+        };
+        f(&mut printer);
+        if printer.out.contains('\n') {
+            None
+        } else {
+            Some(printer.out)
+        }
+    }
+
+    fn column(&self) -> usize {
+        let line = match self.out.rfind('\n') {
+            Some(index) => &self.out[index + 1..],
+            None => &self.out,
+        };
+        line.chars().count()
+    }
+
+    fn fits(&self, flat: &str) -> bool {
+        self.column().saturating_add(flat.chars().count()) <= self.options.line_width
+    }
+
+    fn newline(&mut self, depth: usize) {
+        self.out.push('\n');
+        for _ in 0..depth * self.options.indent {
+            self.out.push(' ');
+        }
+    }
+
+    /// Start a line, after a blank line if `blank`, unless nothing has been
+    /// printed yet
+    fn line(&mut self, depth: usize, blank: bool) {
+        if self.out.is_empty() {
+            return;
+        }
+        if blank {
+            self.out.push('\n');
+        }
+        self.newline(depth);
+    }
+
+    /// Print each comment on a line of its own, returning whether a blank
+    /// line follows the last of them. Blank lines are only kept once
+    /// something has `started` the block.
+    fn comments(&mut self, trivia: &[Trivia], depth: usize, started: &mut bool) -> bool {
+        let mut blank = false;
+        for trivia in trivia {
+            match trivia {
+                Trivia::BlankLine => blank = true,
+                Trivia::Comment(comment) => {
+                    self.line(depth, blank && *started);
+                    self.out.push_str(comment);
+                    *started = true;
+                    blank = false;
+                }
+            }
+        }
+        blank
+    }
+
+    /// Print each item on a line of its own, followed by the dangling
+    /// trivia of the block
+    fn block<T, L, F>(
+        &mut self,
+        items: &[T],
+        dangling: &[Trivia],
+        depth: usize,
+        leading: L,
+        mut f: F,
+    ) where
+        L: Fn(&T) -> &[Trivia],
+        F: FnMut(&mut Self, usize, &T),
+    {
+        let mut started = false;
+        for (index, item) in items.iter().enumerate() {
+            let blank = self.comments(leading(item), depth, &mut started);
+            self.line(depth, blank && started);
+            started = true;
+            f(self, index, item);
+        }
+        self.comments(dangling, depth, &mut started);
+    }
+
+    fn node(&mut self, node: &Node, depth: usize) {
+        match node {
+            Node::Element {
+                r#type,
+                props,
+                dangling,
+                children,
+            } => self.element(r#type.as_deref(), props, dangling, children, depth),
+            Node::Expression { value, dangling } => {
+                self.out.push('{');
+                if value.leading.is_empty() && dangling.is_empty() {
+                    self.node(&value.node, depth);
+                } else {
+                    self.block(
+                        slice::from_ref(&**value),
+                        dangling,
+                        depth + 1,
+                        |item| &item.leading,
+                        |printer, _, item| printer.node(&item.node, depth + 1),
+                    );
+                    self.newline(depth);
+                }
+                self.out.push('}');
+            }
+            // Text is kept as written
+            Node::Text(text) | Node::Atom(text) => self.out.push_str(text),
+            Node::Array { items, dangling } if items.is_empty() && dangling.is_empty() => {
+                self.out.push_str("[]")
+            }
+            Node::Array { items, dangling } => {
+                self.out.push('[');
+                self.block(
+                    items,
+                    dangling,
+                    depth + 1,
+                    |item| &item.leading,
+                    |printer, index, item| {
+                        printer.node(&item.node, depth + 1);
+                        if index + 1 < items.len() {
+                            printer.out.push(',');
+                        }
+                    },
+                );
+                self.newline(depth);
+                self.out.push(']');
+            }
+            Node::Object { entries, dangling } if entries.is_empty() && dangling.is_empty() => {
+                self.out.push_str("{}")
+            }
+            Node::Object { entries, dangling } => {
+                self.out.push('{');
+                self.block(
+                    entries,
+                    dangling,
+                    depth + 1,
+                    |(_, item)| &item.leading,
+                    |printer, index, (key, item)| {
+                        printer.out.push_str(&quote(key));
+                        printer.out.push_str(": ");
+                        printer.node(&item.node, depth + 1);
+                        if index + 1 < entries.len() {
+                            printer.out.push(',');
+                        }
+                    },
+                );
+                self.newline(depth);
+                self.out.push('}');
+            }
+        }
+    }
+
+    fn element(
+        &mut self,
+        r#type: Option<&str>,
+        props: &[Prop],
+        dangling: &[Trivia],
+        children: &[Item],
+        depth: usize,
+    ) {
+        if let Some(flat) = self.flat(|p| p.element_flat(r#type, props, dangling, children)) {
+            if self.fits(&flat) {
+                self.out.push_str(&flat);
+                return;
+            }
+        }
+
+        let end = match (r#type, children.is_empty()) {
+            (Some(_), true) => "/>",
+            (None, true) => "></>",
+            (_, false) => ">",
+        };
+        let tag = self.flat(|p| p.opening_tag_flat(r#type, props, dangling));
+        match tag {
+            Some(tag) if children.is_empty() && (props.is_empty() || r#type.is_none()) => {
+                self.out.push_str(&tag);
+                self.out
+                    .push_str(if r#type.is_some() { " />" } else { end });
+                return;
+            }
+            Some(tag)
+                if !children.is_empty()
+                    && (props.is_empty() || self.fits(&(tag.clone() + end))) =>
+            {
+                self.out.push_str(&tag);
+                self.out.push_str(end);
+            }
+            _ => {
+                self.out.push('<');
+                if let Some(r#type) = r#type {
+                    self.out.push_str(r#type);
+                }
+                self.block(
+                    props,
+                    dangling,
+                    depth + 1,
+                    |prop| &prop.leading,
+                    |printer, _, prop| printer.prop(prop, depth + 1),
+                );
+                self.newline(depth);
+                self.out.push_str(end);
+                if children.is_empty() {
+                    return;
+                }
+            }
+        }
+
+        self.block(
+            children,
+            &[],
+            depth + 1,
+            |child| &child.leading,
+            |printer, _, child| printer.node(&child.node, depth + 1),
+        );
+        self.newline(depth);
+        self.closing_tag(r#type);
+    }
+
+    fn element_flat(
+        &mut self,
+        r#type: Option<&str>,
+        props: &[Prop],
+        dangling: &[Trivia],
+        children: &[Item],
+    ) {
+        if children.iter().any(|child| !child.leading.is_empty()) {
+            self.out.push('\n');
+            return;
+        }
+        self.opening_tag_flat(r#type, props, dangling);
+        if children.is_empty() {
+            self.out
+                .push_str(if r#type.is_some() { " />" } else { "></>" });
+            return;
+        }
+        self.out.push('>');
+        for child in children {
+            self.node(&child.node, 0);
+        }
+        self.closing_tag(r#type);
+    }
+
+    /// Print an opening tag without its `>`, which has a line break if the
+    /// tag has trivia
+    fn opening_tag_flat(&mut self, r#type: Option<&str>, props: &[Prop], dangling: &[Trivia]) {
+        if !dangling.is_empty() || props.iter().any(|prop| !prop.leading.is_empty()) {
+            self.out.push('\n');
+            return;
+        }
+        self.out.push('<');
+        if let Some(r#type) = r#type {
+            self.out.push_str(r#type);
+        }
+        for prop in props {
+            self.out.push(' ');
+            self.prop(prop, 0);
+        }
+    }
+
+    fn prop(&mut self, prop: &Prop, depth: usize) {
+        self.out.push_str(&prop.name);
+        if let Some(value) = &prop.value {
+            self.out.push('=');
+            self.node(value, depth);
+        }
+    }
+
+    fn closing_tag(&mut self, r#type: Option<&str>) {
+        self.out.push_str("</");
+        if let Some(r#type) = r#type {
+            self.out.push_str(r#type);
+        }
+        self.out.push('>');
+    }
+}
@@ -0,0 +1,966 @@
+use crate::{
+    format,
+    html::VOID_ELEMENTS,
+    json::{escape, JsonValue},
+    jsx::{JsxElement, JsxFragment, JsxValue},
+    jsxn::JsxnValue,
+};
+use std::collections::BTreeMap;
+
+/// Elements whose content is text that is not decoded
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Elements whose content is text, without elements
+const TEXT_ELEMENTS: &[&str] = &["textarea", "title"];
+
+/// Elements whose whitespace is kept as written
+const PREFORMATTED_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
+/// Attributes that are true when present, whatever their value
+const BOOLEAN_ATTRIBUTES: &[&str] = &[
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
+
+/// HTML and SVG attributes that React names differently, beyond which
+/// attributes keep their names
+const PROP_NAMES: &[(&str, &str)] = &[
+    ("accent-height", "accentHeight"),
+    ("accept-charset", "acceptCharset"),
+    ("accesskey", "accessKey"),
+    ("alignment-baseline", "alignmentBaseline"),
+    ("allowfullscreen", "allowFullScreen"),
+    ("arabic-form", "arabicForm"),
+    ("autocomplete", "autoComplete"),
+    ("autofocus", "autoFocus"),
+    ("autoplay", "autoPlay"),
+    ("baseline-shift", "baselineShift"),
+    ("cap-height", "capHeight"),
+    ("cellpadding", "cellPadding"),
+    ("cellspacing", "cellSpacing"),
+    ("charset", "charSet"),
+    ("class", "className"),
+    ("clip-path", "clipPath"),
+    ("clip-rule", "clipRule"),
+    ("color-interpolation", "colorInterpolation"),
+    ("color-interpolation-filters", "colorInterpolationFilters"),
+    ("color-profile", "colorProfile"),
+    ("color-rendering", "colorRendering"),
+    ("colspan", "colSpan"),
+    ("contenteditable", "contentEditable"),
+    ("crossorigin", "crossOrigin"),
+    ("datetime", "dateTime"),
+    ("dominant-baseline", "dominantBaseline"),
+    ("enable-background", "enableBackground"),
+    ("enctype", "encType"),
+    ("fill-opacity", "fillOpacity"),
+    ("fill-rule", "fillRule"),
+    ("flood-color", "floodColor"),
+    ("flood-opacity", "floodOpacity"),
+    ("font-family", "fontFamily"),
+    ("font-size", "fontSize"),
+    ("font-size-adjust", "fontSizeAdjust"),
+    ("font-stretch", "fontStretch"),
+    ("font-style", "fontStyle"),
+    ("font-variant", "fontVariant"),
+    ("font-weight", "fontWeight"),
+    ("for", "htmlFor"),
+    ("formaction", "formAction"),
+    ("formnovalidate", "formNoValidate"),
+    ("frameborder", "frameBorder"),
+    ("glyph-name", "glyphName"),
+    ("glyph-orientation-horizontal", "glyphOrientationHorizontal"),
+    ("glyph-orientation-vertical", "glyphOrientationVertical"),
+    ("horiz-adv-x", "horizAdvX"),
+    ("horiz-origin-x", "horizOriginX"),
+    ("hreflang", "hrefLang"),
+    ("http-equiv", "httpEquiv"),
+    ("image-rendering", "imageRendering"),
+    ("inputmode", "inputMode"),
+    ("itemprop", "itemProp"),
+    ("itemscope", "itemScope"),
+    ("itemtype", "itemType"),
+    ("letter-spacing", "letterSpacing"),
+    ("lighting-color", "lightingColor"),
+    ("marker-end", "markerEnd"),
+    ("marker-mid", "markerMid"),
+    ("marker-start", "markerStart"),
+    ("maxlength", "maxLength"),
+    ("minlength", "minLength"),
+    ("nomodule", "noModule"),
+    ("novalidate", "noValidate"),
+    ("overline-position", "overlinePosition"),
+    ("overline-thickness", "overlineThickness"),
+    ("paint-order", "paintOrder"),
+    ("panose-1", "panose1"),
+    ("playsinline", "playsInline"),
+    ("pointer-events", "pointerEvents"),
+    ("readonly", "readOnly"),
+    ("referrerpolicy", "referrerPolicy"),
+    ("rendering-intent", "renderingIntent"),
+    ("rowspan", "rowSpan"),
+    ("shape-rendering", "shapeRendering"),
+    ("spellcheck", "spellCheck"),
+    ("srcdoc", "srcDoc"),
+    ("srclang", "srcLang"),
+    ("srcset", "srcSet"),
+    ("stop-color", "stopColor"),
+    ("stop-opacity", "stopOpacity"),
+    ("strikethrough-position", "strikethroughPosition"),
+    ("strikethrough-thickness", "strikethroughThickness"),
+    ("stroke-dasharray", "strokeDasharray"),
+    ("stroke-dashoffset", "strokeDashoffset"),
+    ("stroke-linecap", "strokeLinecap"),
+    ("stroke-linejoin", "strokeLinejoin"),
+    ("stroke-miterlimit", "strokeMiterlimit"),
+    ("stroke-opacity", "strokeOpacity"),
+    ("stroke-width", "strokeWidth"),
+    ("tabindex", "tabIndex"),
+    ("text-anchor", "textAnchor"),
+    ("text-decoration", "textDecoration"),
+    ("text-rendering", "textRendering"),
+    ("underline-position", "underlinePosition"),
+    ("underline-thickness", "underlineThickness"),
+    ("unicode-bidi", "unicodeBidi"),
+    ("unicode-range", "unicodeRange"),
+    ("units-per-em", "unitsPerEm"),
+    ("usemap", "useMap"),
+    ("v-alphabetic", "vAlphabetic"),
+    ("v-hanging", "vHanging"),
+    ("v-ideographic", "vIdeographic"),
+    ("v-mathematical", "vMathematical"),
+    ("vector-effect", "vectorEffect"),
+    ("vert-adv-y", "vertAdvY"),
+    ("vert-origin-x", "vertOriginX"),
+    ("vert-origin-y", "vertOriginY"),
+    ("word-spacing", "wordSpacing"),
+    ("writing-mode", "writingMode"),
+    ("x-height", "xHeight"),
+    ("xlink:actuate", "xlinkActuate"),
+    ("xlink:arcrole", "xlinkArcrole"),
+    ("xlink:href", "xlinkHref"),
+    ("xlink:role", "xlinkRole"),
+    ("xlink:show", "xlinkShow"),
+    ("xlink:title", "xlinkTitle"),
+    ("xlink:type", "xlinkType"),
+    ("xml:base", "xmlBase"),
+    ("xml:lang", "xmlLang"),
+    ("xml:space", "xmlSpace"),
+    ("xmlns:xlink", "xmlnsXlink"),
+];
+
+/// Elements closed by the start tag of another element of the same group
+const IMPLIED_END_TAGS: &[&[&str]] = &[
+    &["p"],
+    &["li"],
+    &["option"],
+    &["tr"],
+    &["td", "th"],
+    &["dt", "dd"],
+];
+
+/// The named character references of HTML 4, which include all of Latin-1,
+/// and `&apos;`. Other named references are kept as written.
+const ENTITIES: &[(&str, char)] = &[
+    ("AElig", '\u{c6}'),
+    ("Aacute", '\u{c1}'),
+    ("Acirc", '\u{c2}'),
+    ("Agrave", '\u{c0}'),
+    ("Alpha", '\u{391}'),
+    ("Aring", '\u{c5}'),
+    ("Atilde", '\u{c3}'),
+    ("Auml", '\u{c4}'),
+    ("Beta", '\u{392}'),
+    ("Ccedil", '\u{c7}'),
+    ("Chi", '\u{3a7}'),
+    ("Dagger", '\u{2021}'),
+    ("Delta", '\u{394}'),
+    ("ETH", '\u{d0}'),
+    ("Eacute", '\u{c9}'),
+    ("Ecirc", '\u{ca}'),
+    ("Egrave", '\u{c8}'),
+    ("Epsilon", '\u{395}'),
+    ("Eta", '\u{397}'),
+    ("Euml", '\u{cb}'),
+    ("Gamma", '\u{393}'),
+    ("Iacute", '\u{cd}'),
+    ("Icirc", '\u{ce}'),
+    ("Igrave", '\u{cc}'),
+    ("Iota", '\u{399}'),
+    ("Iuml", '\u{cf}'),
+    ("Kappa", '\u{39a}'),
+    ("Lambda", '\u{39b}'),
+    ("Mu", '\u{39c}'),
+    ("Ntilde", '\u{d1}'),
+    ("Nu", '\u{39d}'),
+    ("OElig", '\u{152}'),
+    ("Oacute", '\u{d3}'),
+    ("Ocirc", '\u{d4}'),
+    ("Ograve", '\u{d2}'),
+    ("Omega", '\u{3a9}'),
+    ("Omicron", '\u{39f}'),
+    ("Oslash", '\u{d8}'),
+    ("Otilde", '\u{d5}'),
+    ("Ouml", '\u{d6}'),
+    ("Phi", '\u{3a6}'),
+    ("Pi", '\u{3a0}'),
+    ("Prime", '\u{2033}'),
+    ("Psi", '\u{3a8}'),
+    ("Rho", '\u{3a1}'),
+    ("Scaron", '\u{160}'),
+    ("Sigma", '\u{3a3}'),
+    ("THORN", '\u{de}'),
+    ("Tau", '\u{3a4}'),
+    ("Theta", '\u{398}'),
+    ("Uacute", '\u{da}'),
+    ("Ucirc", '\u{db}'),
+    ("Ugrave", '\u{d9}'),
+    ("Upsilon", '\u{3a5}'),
+    ("Uuml", '\u{dc}'),
+    ("Xi", '\u{39e}'),
+    ("Yacute", '\u{dd}'),
+    ("Yuml", '\u{178}'),
+    ("Zeta", '\u{396}'),
+    ("aacute", '\u{e1}'),
+    ("acirc", '\u{e2}'),
+    ("acute", '\u{b4}'),
+    ("aelig", '\u{e6}'),
+    ("agrave", '\u{e0}'),
+    ("alefsym", '\u{2135}'),
+    ("alpha", '\u{3b1}'),
+    ("amp", '&'),
+    ("and", '\u{2227}'),
+    ("ang", '\u{2220}'),
+    ("apos", '\''),
+    ("aring", '\u{e5}'),
+    ("asymp", '\u{2248}'),
+    ("atilde", '\u{e3}'),
+    ("auml", '\u{e4}'),
+    ("bdquo", '\u{201e}'),
+    ("beta", '\u{3b2}'),
+    ("brvbar", '\u{a6}'),
+    ("bull", '\u{2022}'),
+    ("cap", '\u{2229}'),
+    ("ccedil", '\u{e7}'),
+    ("cedil", '\u{b8}'),
+    ("cent", '\u{a2}'),
+    ("chi", '\u{3c7}'),
+    ("circ", '\u{2c6}'),
+    ("clubs", '\u{2663}'),
+    ("cong", '\u{2245}'),
+    ("copy", '\u{a9}'),
+    ("crarr", '\u{21b5}'),
+    ("cup", '\u{222a}'),
+    ("curren", '\u{a4}'),
+    ("dArr", '\u{21d3}'),
+    ("dagger", '\u{2020}'),
+    ("darr", '\u{2193}'),
+    ("deg", '\u{b0}'),
+    ("delta", '\u{3b4}'),
+    ("diams", '\u{2666}'),
+    ("divide", '\u{f7}'),
+    ("eacute", '\u{e9}'),
+    ("ecirc", '\u{ea}'),
+    ("egrave", '\u{e8}'),
+    ("empty", '\u{2205}'),
+    ("emsp", '\u{2003}'),
+    ("ensp", '\u{2002}'),
+    ("epsilon", '\u{3b5}'),
+    ("equiv", '\u{2261}'),
+    ("eta", '\u{3b7}'),
+    ("eth", '\u{f0}'),
+    ("euml", '\u{eb}'),
+    ("euro", '\u{20ac}'),
+    ("exist", '\u{2203}'),
+    ("fnof", '\u{192}'),
+    ("forall", '\u{2200}'),
+    ("frac12", '\u{bd}'),
+    ("frac14", '\u{bc}'),
+    ("frac34", '\u{be}'),
+    ("frasl", '\u{2044}'),
+    ("gamma", '\u{3b3}'),
+    ("ge", '\u{2265}'),
+    ("gt", '>'),
+    ("hArr", '\u{21d4}'),
+    ("harr", '\u{2194}'),
+    ("hearts", '\u{2665}'),
+    ("hellip", '\u{2026}'),
+    ("iacute", '\u{ed}'),
+    ("icirc", '\u{ee}'),
+    ("iexcl", '\u{a1}'),
+    ("igrave", '\u{ec}'),
+    ("image", '\u{2111}'),
+    ("infin", '\u{221e}'),
+    ("int", '\u{222b}'),
+    ("iota", '\u{3b9}'),
+    ("iquest", '\u{bf}'),
+    ("isin", '\u{2208}'),
+    ("iuml", '\u{ef}'),
+    ("kappa", '\u{3ba}'),
+    ("lArr", '\u{21d0}'),
+    ("lambda", '\u{3bb}'),
+    ("lang", '\u{2329}'),
+    ("laquo", '\u{ab}'),
+    ("larr", '\u{2190}'),
+    ("lceil", '\u{2308}'),
+    ("ldquo", '\u{201c}'),
+    ("le", '\u{2264}'),
+    ("lfloor", '\u{230a}'),
+    ("lowast", '\u{2217}'),
+    ("loz", '\u{25ca}'),
+    ("lrm", '\u{200e}'),
+    ("lsaquo", '\u{2039}'),
+    ("lsquo", '\u{2018}'),
+    ("lt", '<'),
+    ("macr", '\u{af}'),
+    ("mdash", '\u{2014}'),
+    ("micro", '\u{b5}'),
+    ("middot", '\u{b7}'),
+    ("minus", '\u{2212}'),
+    ("mu", '\u{3bc}'),
+    ("nabla", '\u{2207}'),
+    ("nbsp", '\u{a0}'),
+    ("ndash", '\u{2013}'),
+    ("ne", '\u{2260}'),
+    ("ni", '\u{220b}'),
+    ("not", '\u{ac}'),
+    ("notin", '\u{2209}'),
+    ("nsub", '\u{2284}'),
+    ("ntilde", '\u{f1}'),
+    ("nu", '\u{3bd}'),
+    ("oacute", '\u{f3}'),
+    ("ocirc", '\u{f4}'),
+    ("oelig", '\u{153}'),
+    ("ograve", '\u{f2}'),
+    ("oline", '\u{203e}'),
+    ("omega", '\u{3c9}'),
+    ("omicron", '\u{3bf}'),
+    ("oplus", '\u{2295}'),
+    ("or", '\u{2228}'),
+    ("ordf", '\u{aa}'),
+    ("ordm", '\u{ba}'),
+    ("oslash", '\u{f8}'),
+    ("otilde", '\u{f5}'),
+    ("otimes", '\u{2297}'),
+    ("ouml", '\u{f6}'),
+    ("para", '\u{b6}'),
+    ("part", '\u{2202}'),
+    ("permil", '\u{2030}'),
+    ("perp", '\u{22a5}'),
+    ("phi", '\u{3c6}'),
+    ("pi", '\u{3c0}'),
+    ("piv", '\u{3d6}'),
+    ("plusmn", '\u{b1}'),
+    ("pound", '\u{a3}'),
+    ("prime", '\u{2032}'),
+    ("prod", '\u{220f}'),
+    ("prop", '\u{221d}'),
+    ("psi", '\u{3c8}'),
+    ("quot", '"'),
+    ("rArr", '\u{21d2}'),
+    ("radic", '\u{221a}'),
+    ("rang", '\u{232a}'),
+    ("raquo", '\u{bb}'),
+    ("rarr", '\u{2192}'),
+    ("rceil", '\u{2309}'),
+    ("rdquo", '\u{201d}'),
+    ("real", '\u{211c}'),
+    ("reg", '\u{ae}'),
+    ("rfloor", '\u{230b}'),
+    ("rho", '\u{3c1}'),
+    ("rlm", '\u{200f}'),
+    ("rsaquo", '\u{203a}'),
+    ("rsquo", '\u{2019}'),
+    ("sbquo", '\u{201a}'),
+    ("scaron", '\u{161}'),
+    ("sdot", '\u{22c5}'),
+    ("sect", '\u{a7}'),
+    ("shy", '\u{ad}'),
+    ("sigma", '\u{3c3}'),
+    ("sigmaf", '\u{3c2}'),
+    ("sim", '\u{223c}'),
+    ("spades", '\u{2660}'),
+    ("sub", '\u{2282}'),
+    ("sube", '\u{2286}'),
+    ("sum", '\u{2211}'),
+    ("sup", '\u{2283}'),
+    ("sup1", '\u{b9}'),
+    ("sup2", '\u{b2}'),
+    ("sup3", '\u{b3}'),
+    ("supe", '\u{2287}'),
+    ("szlig", '\u{df}'),
+    ("tau", '\u{3c4}'),
+    ("there4", '\u{2234}'),
+    ("theta", '\u{3b8}'),
+    ("thetasym", '\u{3d1}'),
+    ("thinsp", '\u{2009}'),
+    ("thorn", '\u{fe}'),
+    ("tilde", '\u{2dc}'),
+    ("times", '\u{d7}'),
+    ("trade", '\u{2122}'),
+    ("uArr", '\u{21d1}'),
+    ("uacute", '\u{fa}'),
+    ("uarr", '\u{2191}'),
+    ("ucirc", '\u{fb}'),
+    ("ugrave", '\u{f9}'),
+    ("uml", '\u{a8}'),
+    ("upsih", '\u{3d2}'),
+    ("upsilon", '\u{3c5}'),
+    ("uuml", '\u{fc}'),
+    ("weierp", '\u{2118}'),
+    ("xi", '\u{3be}'),
+    ("yacute", '\u{fd}'),
+    ("yen", '\u{a5}'),
+    ("yuml", '\u{ff}'),
+    ("zeta", '\u{3b6}'),
+    ("zwj", '\u{200d}'),
+    ("zwnj", '\u{200c}'),
+];
+
+/// Convert HTML into a JSX value: the element if the HTML is a single
+/// element, and otherwise a fragment of its nodes.
+///
+/// Attributes become props named the way React names them: `class` as
+/// `className`, `for` as `htmlFor`, and other renamed attributes such as
+/// `tabindex` as `tabIndex` and `stroke-width` as `strokeWidth`, while
+/// `data-*`, `aria-*` and other attributes keep their names. Inline styles
+/// become objects of camel cased properties, and boolean attributes and
+/// attributes without a value become bare props. Event handler attributes, whose
+/// values are scripts, are left out, and so are comments and doctypes.
+///
+/// Character references are decoded, and text that JSX text cannot hold
+/// becomes a string expression. Whitespace is collapsed like a browser
+/// renders it, except in `<pre>` and `<textarea>`, and as in JSX, whitespace
+/// spanning lines is dropped while spaces between text and elements on the
+/// same line are kept as `{" "}`.
+///
+/// Like browsers, the conversion accepts any input: elements that are not
+/// closed are closed at the end of their parent, and unmatched end tags are
+/// ignored.
+pub fn convert(html: &str) -> JsxValue {
+    let mut children = Converter {
+        input: html,
+        pos: 0,
+    }
+    .nodes(
+        &[],
+        Context {
+            svg: false,
+            preformatted: false,
+        },
+    );
+    children.retain(|node| !matches!(node, Node::Text(text) if text.chars().all(is_space)));
+    match children.as_slice() {
+        [Node::Element(_)] => match children.pop() {
+            Some(Node::Element(element)) => element.into_jsx(),
+            _ => unreachable!(),
+        },
+        _ => JsxValue::JsxFragment(JsxFragment::new(jsx_children(children, false))),
+    }
+}
+
+/// Convert HTML into JSXN source text, as formatted by `format::format`
+pub fn convert_to_source(html: &str) -> String {
+    format::format(&JsxnValue::JsxValue(convert(html)))
+}
+
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+struct Element {
+    name: String,
+    props: BTreeMap<String, JsxValue>,
+    children: Vec<Node>,
+    preformatted: bool,
+}
+
+impl Element {
+    fn into_jsx(self) -> JsxValue {
+        JsxValue::JsxElement(JsxElement::new(
+            self.name,
+            self.props,
+            jsx_children(self.children, self.preformatted),
+        ))
+    }
+}
+
+/// Where the converted element is: in an `<svg>` element, where names are
+/// case sensitive, and in a preformatted element
+#[derive(Clone, Copy)]
+struct Context {
+    svg: bool,
+    preformatted: bool,
+}
+
+struct Converter<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Converter<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    /// Read nodes until the end tag of one of the open elements, innermost
+    /// last, or a start tag that closes the innermost one
+    fn nodes(&mut self, open: &[String], context: Context) -> Vec<Node> {
+        let mut nodes = vec![];
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return nodes;
+            }
+            if rest.starts_with("<!--") {
+                self.pos += rest.find("-->").map_or(rest.len(), |end| end + 3);
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                self.pos += rest.find('>').map_or(rest.len(), |end| end + 1);
+            } else if let Some(name) = end_tag_name(rest) {
+                match open.iter().rposition(|tag| tag.eq_ignore_ascii_case(name)) {
+                    // The end tag of an ancestor closes this element too
+                    Some(index) if index + 1 < open.len() => return nodes,
+                    Some(_) => {
+                        self.pos += rest.find('>').map_or(rest.len(), |end| end + 1);
+                        return nodes;
+                    }
+                    // An end tag of no open element is ignored
+                    None => self.pos += rest.find('>').map_or(rest.len(), |end| end + 1),
+                }
+            } else if rest.starts_with('<')
+                && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+            {
+                let start = self.pos;
+                let (name, attributes, self_closing) = self.start_tag();
+                let tag = name.to_ascii_lowercase();
+                let closes_parent = open.last().is_some_and(|parent| {
+                    IMPLIED_END_TAGS.iter().any(|group| {
+                        group.contains(&tag.as_str()) && group.contains(&parent.as_str())
+                    })
+                });
+                if closes_parent {
+                    // Read again by the parent of the closed element
+                    self.pos = start;
+                    return nodes;
+                }
+                nodes.push(Node::Element(self.element(
+                    name,
+                    attributes,
+                    self_closing,
+                    open,
+                    context,
+                )));
+            } else {
+                let end = rest[1..].find('<').map_or(rest.len(), |end| end + 1);
+                let text = decode(&rest[..end]);
+                self.pos += end;
+                match nodes.last_mut() {
+                    Some(Node::Text(last)) => last.push_str(&text),
+                    _ => nodes.push(Node::Text(text)),
+                }
+            }
+        }
+    }
+
+    fn element(
+        &mut self,
+        name: String,
+        attributes: Vec<(String, Option<String>)>,
+        self_closing: bool,
+        open: &[String],
+        context: Context,
+    ) -> Element {
+        let tag = name.to_ascii_lowercase();
+        let context = Context {
+            svg: context.svg || tag == "svg",
+            preformatted: context.preformatted || PREFORMATTED_ELEMENTS.contains(&tag.as_str()),
+        };
+        let name = jsx_name(if context.svg { &name } else { &tag });
+        let props = attributes
+            .into_iter()
+            .filter_map(|(name, value)| prop(&name, value))
+            .collect();
+        let mut children = if VOID_ELEMENTS.contains(&tag.as_str()) || self_closing {
+            vec![]
+        } else if RAW_TEXT_ELEMENTS.contains(&tag.as_str()) {
+            vec![Node::Text(String::from(self.text_until_end_tag(&tag)))]
+        } else if TEXT_ELEMENTS.contains(&tag.as_str()) {
+            vec![Node::Text(decode(self.text_until_end_tag(&tag)))]
+        } else {
+            let mut open = open.to_vec();
+            open.push(tag.clone());
+            self.nodes(&open, context)
+        };
+        // A newline right after the start tag of these elements is ignored
+        if tag == "pre" || tag == "textarea" {
+            if let Some(Node::Text(text)) = children.first_mut() {
+                if text.starts_with('\n') {
+                    text.remove(0);
+                }
+            }
+        }
+        Element {
+            name,
+            props,
+            children,
+            preformatted: context.preformatted,
+        }
+    }
+
+    /// Read a start tag, returning its name, its attributes and whether it
+    /// is self-closing
+    fn start_tag(&mut self) -> (String, Vec<(String, Option<String>)>, bool) {
+        self.pos += 1;
+        let name = self.take_while(|c| !is_space(c) && c != '>' && c != '/');
+        let mut attributes = vec![];
+        loop {
+            self.take_while(|c| is_space(c) || c == '/');
+            let rest = self.rest();
+            if rest.is_empty() {
+                return (name, attributes, false);
+            }
+            if rest.starts_with('>') {
+                self.pos += 1;
+                let self_closing = self.input[..self.pos - 1].ends_with('/');
+                return (name, attributes, self_closing);
+            }
+            let attribute = self.take_while(|c| !is_space(c) && c != '>' && c != '/' && c != '=');
+            let attribute = if attribute.is_empty() {
+                // A stray `=`
+                self.pos += 1;
+                continue;
+            } else {
+                attribute
+            };
+            self.take_while(is_space);
+            if !self.rest().starts_with('=') {
+                attributes.push((attribute, None));
+                continue;
+            }
+            self.pos += 1;
+            self.take_while(is_space);
+            let value = match self.rest().chars().next() {
+                Some(quote) if quote == '"' || quote == '\'' => {
+                    self.pos += 1;
+                    let value = self.take_while(|c| c != quote);
+                    self.pos = (self.pos + 1).min(self.input.len());
+                    value
+                }
+                _ => self.take_while(|c| !is_space(c) && c != '>'),
+            };
+            attributes.push((attribute, Some(decode(&value))));
+        }
+    }
+
+    /// Read the text of an element that cannot hold elements, and its end
+    /// tag
+    fn text_until_end_tag(&mut self, tag: &str) -> &'a str {
+        let rest = self.rest();
+        let end = rest
+            .match_indices("</")
+            .map(|(index, _)| index)
+            .find(|index| {
+                end_tag_name(&rest[*index..]).is_some_and(|name| name.eq_ignore_ascii_case(tag))
+            })
+            .unwrap_or(rest.len());
+        self.pos += end;
+        self.pos += self
+            .rest()
+            .find('>')
+            .map_or(self.rest().len(), |end| end + 1);
+        &rest[..end]
+    }
+
+    fn take_while<P: Fn(char) -> bool>(&mut self, predicate: P) -> String {
+        let rest = self.rest();
+        let end = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.pos += end;
+        String::from(&rest[..end])
+    }
+}
+
+/// The name of the end tag at the start of `input`, if there is one
+fn end_tag_name(input: &str) -> Option<&str> {
+    if !input.starts_with("</") {
+        return None;
+    }
+    let name = &input[2..];
+    let end = name
+        .find(|c: char| is_space(c) || c == '>' || c == '/')
+        .unwrap_or(name.len());
+    if end == 0 {
+        None
+    } else {
+        Some(&name[..end])
+    }
+}
+
+/// Whitespace as HTML defines it, which unlike `char::is_whitespace` does
+/// not include non-breaking spaces
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\u{c}')
+}
+
+/// The prop for an attribute, or `None` if it is left out
+fn prop(name: &str, value: Option<String>) -> Option<(String, JsxValue)> {
+    let lowercase = name.to_ascii_lowercase();
+    if lowercase.starts_with("on") {
+        return None;
+    }
+    let prop_name = match PROP_NAMES.iter().find(|(html, _)| *html == lowercase) {
+        Some((_, prop)) => String::from(*prop),
+        None => prop_name(name),
+    };
+    if prop_name.is_empty() {
+        return None;
+    }
+    let value = match value {
+        Some(_) if BOOLEAN_ATTRIBUTES.contains(&lowercase.as_str()) => None,
+        value => value,
+    };
+    let value = match value {
+        None => JsxValue::JsonValue(JsonValue::Boolean(true)),
+        Some(style) if lowercase == "style" => {
+            JsxValue::JsxExpression(Box::new(JsxValue::JsonValue(parse_style(&style))))
+        }
+        Some(value) => JsxValue::JsonValue(JsonValue::Str(escape(&value))),
+    };
+    Some((prop_name, value))
+}
+
+/// Camel case a name at its `-` and `:` separators, leaving out the
+/// characters that JSXN names cannot hold
+fn jsx_name(name: &str) -> String {
+    let mut jsx = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '-' || c == ':' {
+            upper = !jsx.is_empty();
+        } else if c.is_ascii_alphanumeric() {
+            if upper {
+                jsx.push(c.to_ascii_uppercase());
+                upper = false;
+            } else {
+                jsx.push(c);
+            }
+        }
+    }
+    jsx
+}
+
+/// Keep an attribute name as written, like React does for `data-*` and
+/// `aria-*` attributes, leaving out the characters that JSXN prop names
+/// cannot hold
+fn prop_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>()
+        .trim_start_matches('-')
+        .to_string()
+}
+
+/// Parse an inline style into an object of camel cased properties, such as
+/// `background-color` as `backgroundColor` and `-webkit-transition` as
+/// `WebkitTransition`. Values that are plain numbers become numbers.
+fn parse_style(style: &str) -> JsonValue {
+    let mut object = BTreeMap::new();
+    for declaration in split_declarations(style) {
+        let colon = match declaration.find(':') {
+            Some(colon) => colon,
+            None => continue,
+        };
+        let property = declaration[..colon].trim();
+        let value = declaration[colon + 1..].trim();
+        if property.is_empty() || value.is_empty() {
+            continue;
+        }
+        let property = if property.starts_with("--") {
+            String::from(property)
+        } else {
+            let property = property.to_ascii_lowercase();
+            match property.strip_prefix("-ms-") {
+                Some(rest) => format!("ms-{}", rest),
+                None => property,
+            }
+            .split('-')
+            .enumerate()
+            .map(|(index, word)| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) if index > 0 => {
+                        first.to_ascii_uppercase().to_string() + chars.as_str()
+                    }
+                    _ => String::from(word),
+                }
+            })
+            .collect()
+        };
+        let numeric = value
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-');
+        let value = match value.parse() {
+            Ok(number) if numeric => JsonValue::Num(number),
+            _ => JsonValue::Str(escape(value)),
+        };
+        object.insert(property, value);
+    }
+    JsonValue::Object(object)
+}
+
+/// Split an inline style at the semicolons that are not in strings or
+/// parentheses
+fn split_declarations(style: &str) -> Vec<&str> {
+    let mut declarations = vec![];
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (index, c) in style.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ';') if depth == 0 => {
+                declarations.push(&style[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    declarations.push(&style[start..]);
+    declarations
+}
+
+/// Decode the character references of HTML text, leaving unknown ones as
+/// written
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let end = rest[1..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+            .map_or(rest.len(), |end| end + 1);
+        let reference = &rest[1..end];
+        let c = if let Some(number) = reference.strip_prefix('#') {
+            match number
+                .strip_prefix('x')
+                .or_else(|| number.strip_prefix('X'))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => number.parse().ok(),
+            }
+            .map(|code| std::char::from_u32(code).unwrap_or(std::char::REPLACEMENT_CHARACTER))
+        } else {
+            ENTITIES
+                .iter()
+                .find(|(name, _)| *name == reference)
+                .map(|(_, c)| *c)
+        };
+        match c {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[end..];
+                rest = rest.strip_prefix(';').unwrap_or(rest);
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// A text child: JSX text if it can be written as such, and a string
+/// expression otherwise
+fn text(text: &str) -> JsxValue {
+    if text.contains(['{', '}', '<', '>']) {
+        string(text)
+    } else {
+        JsxValue::JsonValue(JsonValue::Str(String::from(text)))
+    }
+}
+
+fn string(text: &str) -> JsxValue {
+    JsxValue::JsxExpression(Box::new(JsxValue::JsonValue(JsonValue::Str(escape(text)))))
+}
+
+fn jsx_children(nodes: Vec<Node>, preformatted: bool) -> Vec<JsxValue> {
+    let last = nodes.len().saturating_sub(1);
+    let mut children = vec![];
+    for (index, node) in nodes.into_iter().enumerate() {
+        let content = match node {
+            Node::Element(element) => {
+                children.push(element.into_jsx());
+                continue;
+            }
+            Node::Text(content) => content,
+        };
+        if preformatted {
+            // JSX text would lose its whitespace at lines and edges
+            let plain = !content.starts_with(is_space)
+                && !content.ends_with(is_space)
+                && !content.contains('\n');
+            if plain {
+                children.push(text(&content));
+            } else if !content.is_empty() {
+                children.push(string(&content));
+            }
+            continue;
+        }
+        let trimmed = content.trim_matches(is_space);
+        let leading = &content[..content.len() - content.trim_start_matches(is_space).len()];
+        let trailing = &content[content.trim_end_matches(is_space).len()..];
+        // Whitespace on a single line between two nodes is significant
+        let space = |whitespace: &str, inner: bool| {
+            inner && !whitespace.is_empty() && !whitespace.contains('\n')
+        };
+        if trimmed.is_empty() {
+            if space(&content, index > 0 && index < last) {
+                children.push(string(" "));
+            }
+            continue;
+        }
+        if space(leading, index > 0) {
+            children.push(string(" "));
+        }
+        let collapsed = trimmed
+            .split(is_space)
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        children.push(text(&collapsed));
+        if space(trailing, index < last) {
+            children.push(string(" "));
+        }
+    }
+    children
+}
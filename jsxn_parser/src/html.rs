@@ -0,0 +1,469 @@
+use crate::{
+    json::{unescape, JsonValue},
+    jsx::{JsxElement, JsxValue},
+    jsxn::JsxnValue,
+};
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+    io,
+};
+
+/// Elements that cannot have children, and are rendered without a closing
+/// tag
+pub(crate) const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Style properties whose numeric values are not lengths, and so are
+/// rendered without a `px` unit
+const UNITLESS_PROPERTIES: &[&str] = &[
+    "animationIterationCount",
+    "aspectRatio",
+    "borderImageOutset",
+    "borderImageSlice",
+    "borderImageWidth",
+    "columnCount",
+    "columns",
+    "flex",
+    "flexGrow",
+    "flexNegative",
+    "flexOrder",
+    "flexPositive",
+    "flexShrink",
+    "fontWeight",
+    "gridArea",
+    "gridColumn",
+    "gridColumnEnd",
+    "gridColumnSpan",
+    "gridColumnStart",
+    "gridRow",
+    "gridRowEnd",
+    "gridRowSpan",
+    "gridRowStart",
+    "lineClamp",
+    "lineHeight",
+    "opacity",
+    "order",
+    "orphans",
+    "scale",
+    "tabSize",
+    "widows",
+    "zIndex",
+    "zoom",
+    "fillOpacity",
+    "floodOpacity",
+    "stopOpacity",
+    "strokeDasharray",
+    "strokeDashoffset",
+    "strokeMiterlimit",
+    "strokeOpacity",
+    "strokeWidth",
+];
+
+/// Options for the layout of rendered HTML
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Put each child of an element that contains other elements on its own
+    /// line. This changes the whitespace between elements, which can change
+    /// how the HTML is displayed.
+    pub pretty: bool,
+
+    /// The number of spaces to indent each level of pretty output by
+    pub indent: usize,
+}
+
+impl RenderOptions {
+    /// HTML without any whitespace added, which is also the default
+    pub const COMPACT: RenderOptions = RenderOptions {
+        pretty: false,
+        indent: 0,
+    };
+
+    /// HTML indented by two spaces for each level of elements
+    pub const PRETTY: RenderOptions = RenderOptions {
+        pretty: true,
+        indent: 2,
+    };
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions::COMPACT
+    }
+}
+
+/// Render a JSX value to HTML, the way React renders static markup:
+/// fragments are flattened, `null`, `true` and `false` children are
+/// skipped, and `className`, `htmlFor` and `style` objects are rendered as
+/// their HTML equivalents. Elements and props whose names are not valid in
+/// HTML are skipped.
+pub fn render(jsx: &JsxValue) -> String {
+    render_with_options(jsx, &RenderOptions::COMPACT)
+}
+
+/// Render a JSX value to HTML with the given options
+pub fn render_with_options(jsx: &JsxValue, options: &RenderOptions) -> String {
+    let mut html = String::new();
+    // Writing to a `String` cannot fail
+    let _ = render_to_fmt_writer_with_options(jsx, &mut html, options);
+    html
+}
+
+/// Render a JSXN value to HTML. JSON values are rendered as if they were
+/// the value of a JSX Expression.
+pub fn render_jsxn(jsxn: &JsxnValue) -> String {
+    render_jsxn_with_options(jsxn, &RenderOptions::COMPACT)
+}
+
+/// Render a JSXN value to HTML with the given options
+pub fn render_jsxn_with_options(jsxn: &JsxnValue, options: &RenderOptions) -> String {
+    let mut nodes = vec![];
+    match jsxn {
+        JsxnValue::JsxValue(jsx) => jsx_nodes(jsx, &mut nodes),
+        JsxnValue::JsonValue(json) => json_nodes(json, &mut nodes),
+    }
+    let mut html = String::new();
+    let _ = Renderer {
+        w: &mut html,
+        options,
+    }
+    .write_root(&nodes);
+    html
+}
+
+/// Render a JSX value to HTML, writing the markup to `writer` as the tree is
+/// walked. Small writes are made, so `writer` should be buffered.
+pub fn render_to_writer<W: io::Write>(jsx: &JsxValue, writer: W) -> io::Result<()> {
+    render_to_writer_with_options(jsx, writer, &RenderOptions::COMPACT)
+}
+
+/// Render a JSX value to HTML with the given options, writing the markup to
+/// `writer` as the tree is walked
+pub fn render_to_writer_with_options<W: io::Write>(
+    jsx: &JsxValue,
+    writer: W,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter {
+        writer,
+        error: None,
+    };
+    match render_to_fmt_writer_with_options(jsx, &mut adapter, options) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(adapter
+            .error
+            .unwrap_or_else(|| io::Error::other("formatter error"))),
+    }
+}
+
+/// Render a JSX value to HTML, writing the markup to a `fmt::Write` as the
+/// tree is walked
+pub fn render_to_fmt_writer<W: Write>(jsx: &JsxValue, writer: W) -> fmt::Result {
+    render_to_fmt_writer_with_options(jsx, writer, &RenderOptions::COMPACT)
+}
+
+/// Render a JSX value to HTML with the given options, writing the markup to
+/// a `fmt::Write` as the tree is walked
+pub fn render_to_fmt_writer_with_options<W: Write>(
+    jsx: &JsxValue,
+    writer: W,
+    options: &RenderOptions,
+) -> fmt::Result {
+    let mut nodes = vec![];
+    jsx_nodes(jsx, &mut nodes);
+    Renderer { w: writer, options }.write_root(&nodes)
+}
+
+/// Writes to an `io::Write` through `fmt::Write`, keeping the I/O error that
+/// a `fmt::Error` cannot carry
+struct IoAdapter<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+/// A rendered piece of HTML, once fragments, arrays and expressions have
+/// been flattened
+enum Node<'a> {
+    Element(&'a JsxElement),
+    Text(Cow<'a, str>),
+}
+
+fn jsx_nodes<'a>(jsx: &'a JsxValue, nodes: &mut Vec<Node<'a>>) {
+    match jsx {
+        JsxValue::JsxElement(element) => nodes.push(Node::Element(element)),
+        JsxValue::JsxFragment(fragment) => {
+            for child in fragment.children() {
+                jsx_nodes(child, nodes);
+            }
+        }
+        // Text is kept as written, so it is only escaped
+        JsxValue::JsonValue(JsonValue::Str(text)) => nodes.push(Node::Text(Cow::Borrowed(text))),
+        JsxValue::JsonValue(json) => json_nodes(json, nodes),
+        JsxValue::JsxExpression(jsx) => match &**jsx {
+            JsxValue::JsonValue(json) => json_nodes(json, nodes),
+            jsx => jsx_nodes(jsx, nodes),
+        },
+        JsxValue::Error(_) | JsxValue::Variable(_) => {}
+    }
+}
+
+fn json_nodes<'a>(json: &'a JsonValue, nodes: &mut Vec<Node<'a>>) {
+    match json {
+        JsonValue::Str(s) => nodes.push(Node::Text(Cow::Owned(unescape(s)))),
+        JsonValue::Num(num) => {
+            let mut text = String::new();
+            let _ = write_number(&mut text, *num);
+            nodes.push(Node::Text(Cow::Owned(text)));
+        }
+        JsonValue::Array(array) => {
+            for item in array {
+                json_nodes(item, nodes);
+            }
+        }
+        JsonValue::JsxValue(jsx) => jsx_nodes(jsx, nodes),
+        JsonValue::Boolean(_) | JsonValue::Null | JsonValue::Object(_) => {}
+    }
+}
+
+struct Renderer<'o, W> {
+    w: W,
+    options: &'o RenderOptions,
+}
+
+impl<'o, W: Write> Renderer<'o, W> {
+    fn write_root(&mut self, nodes: &[Node]) -> fmt::Result {
+        for (index, node) in nodes.iter().enumerate() {
+            if self.options.pretty && index > 0 {
+                self.w.write_char('\n')?;
+            }
+            self.write_node(node, 0)?;
+        }
+        Ok(())
+    }
+
+    fn write_node(&mut self, node: &Node, depth: usize) -> fmt::Result {
+        match node {
+            Node::Element(element) => self.write_element(element, depth),
+            Node::Text(text) => write_escaped(&mut self.w, text),
+        }
+    }
+
+    fn write_element(&mut self, element: &JsxElement, depth: usize) -> fmt::Result {
+        let tag = element.r#type();
+        if !valid_tag(tag) {
+            return Ok(());
+        }
+        write!(self.w, "<{}", tag)?;
+        for (name, value) in element.props() {
+            write_attribute(&mut self.w, name, value)?;
+        }
+        if VOID_ELEMENTS.contains(&tag) {
+            return self.w.write_str("/>");
+        }
+        self.w.write_char('>')?;
+
+        let mut children = vec![];
+        for child in element.children() {
+            jsx_nodes(child, &mut children);
+        }
+        let block = self.options.pretty
+            && children
+                .iter()
+                .any(|child| matches!(child, Node::Element(_)));
+        for child in &children {
+            if block {
+                self.write_line(depth + 1)?;
+            }
+            self.write_node(child, depth + 1)?;
+        }
+        if block {
+            self.write_line(depth)?;
+        }
+        write!(self.w, "</{}>", tag)
+    }
+
+    fn write_line(&mut self, depth: usize) -> fmt::Result {
+        self.w.write_char('\n')?;
+        for _ in 0..depth * self.options.indent {
+            self.w.write_char(' ')?;
+        }
+        Ok(())
+    }
+}
+
+fn write_attribute<W: Write>(w: &mut W, name: &str, value: &JsxValue) -> fmt::Result {
+    let name = match name {
+        "children" | "key" | "ref" => return Ok(()),
+        "className" => "class",
+        "htmlFor" => "for",
+        name if !valid_attribute(name) => return Ok(()),
+        name => name,
+    };
+    let value = match value {
+        JsxValue::JsxExpression(jsx) => jsx,
+        value => value,
+    };
+    let json = match value {
+        JsxValue::JsonValue(json) => json,
+        _ => return Ok(()),
+    };
+    match json {
+        JsonValue::Boolean(true) => write!(w, " {}", name),
+        JsonValue::Str(s) => {
+            write!(w, " {}=\"", name)?;
+            write_escaped(w, &unescape(s))?;
+            w.write_char('"')
+        }
+        JsonValue::Num(num) => {
+            write!(w, " {}=\"", name)?;
+            write_number(w, *num)?;
+            w.write_char('"')
+        }
+        JsonValue::Object(style) if name == "style" => {
+            let mut css = String::new();
+            for (property, value) in style {
+                let value = match value {
+                    JsonValue::Str(s) if !s.is_empty() => unescape(s),
+                    JsonValue::Num(num) => {
+                        let mut value = String::new();
+                        write_number(&mut value, *num)?;
+                        if *num != 0.0
+                            && !property.starts_with("--")
+                            && !UNITLESS_PROPERTIES.contains(&property.as_str())
+                        {
+                            value.push_str("px");
+                        }
+                        value
+                    }
+                    _ => continue,
+                };
+                if !css.is_empty() {
+                    css.push(';');
+                }
+                css.push_str(&css_property(property));
+                css.push(':');
+                css.push_str(value.trim());
+            }
+            if css.is_empty() {
+                return Ok(());
+            }
+            write!(w, " {}=\"", name)?;
+            write_escaped(w, &css)?;
+            w.write_char('"')
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether a tag name can be written without changing the markup around it,
+/// like React checks before rendering an element
+fn valid_tag(tag: &str) -> bool {
+    tag.starts_with(|c: char| c.is_ascii_alphabetic())
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":._-".contains(c))
+}
+
+/// Whether an attribute name is valid in HTML, which excludes whitespace,
+/// quotes and the characters that end a tag or start its value
+fn valid_attribute(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_control() || c.is_whitespace() || "\"'<>/=".contains(c))
+}
+
+/// Convert a camel cased style property to CSS, such as `backgroundColor`
+/// to `background-color` and `WebkitTransition` to `-webkit-transition`
+fn css_property(property: &str) -> String {
+    if property.starts_with("--") {
+        return String::from(property);
+    }
+    let mut css = String::with_capacity(property.len());
+    if property.starts_with("ms") && property[2..].starts_with(char::is_uppercase) {
+        css.push('-');
+    }
+    for c in property.chars() {
+        if c.is_uppercase() {
+            css.push('-');
+            css.extend(c.to_lowercase());
+        } else {
+            css.push(c);
+        }
+    }
+    css
+}
+
+/// Write a number the way JavaScript converts it to a string, with the
+/// shortest digits that read back as the number, in exponential notation
+/// from `1e21` and below `1e-6`
+fn write_number<W: Write>(w: &mut W, num: f64) -> fmt::Result {
+    if num.is_nan() {
+        return w.write_str("NaN");
+    } else if num.is_infinite() {
+        return w.write_str(if num > 0.0 { "Infinity" } else { "-Infinity" });
+    } else if num == 0.0 {
+        return w.write_char('0');
+    } else if num < 0.0 {
+        w.write_char('-')?;
+    }
+    // Rust writes the same shortest digits, as `d.ddde<exponent>`
+    let exponential = format!("{:e}", num.abs());
+    let (mantissa, exponent) = exponential.split_at(exponential.find('e').unwrap_or_default());
+    let digits = mantissa.replace('.', "");
+    let point = exponent[1..].parse::<i32>().unwrap_or_default() + 1;
+    let length = digits.len() as i32;
+    if length <= point && point <= 21 {
+        w.write_str(&digits)?;
+        (length..point).try_for_each(|_| w.write_char('0'))
+    } else if 0 < point && point <= 21 {
+        let (integer, fraction) = digits.split_at(point as usize);
+        write!(w, "{}.{}", integer, fraction)
+    } else if -6 < point && point <= 0 {
+        w.write_str("0.")?;
+        (point..0).try_for_each(|_| w.write_char('0'))?;
+        w.write_str(&digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        w.write_str(first)?;
+        if !rest.is_empty() {
+            write!(w, ".{}", rest)?;
+        }
+        let exponent = point - 1;
+        write!(
+            w,
+            "e{}{}",
+            if exponent < 0 { '-' } else { '+' },
+            exponent.abs()
+        )
+    }
+}
+
+fn write_escaped<W: Write>(w: &mut W, text: &str) -> fmt::Result {
+    let mut last = 0;
+    for (index, c) in text.char_indices() {
+        let escaped = match c {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' => "&quot;",
+            '\'' => "&#x27;",
+            _ => continue,
+        };
+        w.write_str(&text[last..index])?;
+        w.write_str(escaped)?;
+        last = index + 1;
+    }
+    w.write_str(&text[last..])
+}
@@ -1,17 +1,16 @@
 use crate::{
-    jsx::{root as jsx_value, JsxValue},
-    shared::sp,
+    jsx::{jsx_value, JsxValue},
+    options::ParserOptions,
+    shared::{enabled, State},
 };
 use nom::{
     branch::alt,
-    bytes::complete::{escaped, is_not, tag},
-    character::complete::{char, hex_digit1, one_of},
-    combinator::{cut, map, opt, value, verify},
+    bytes::complete::{escaped, is_not, tag, tag_no_case, take_while},
+    character::complete::{anychar, char, digit0, digit1, hex_digit1, one_of},
+    combinator::{cut, map, opt, recognize, value, verify},
     error::{context, ParseError},
-    multi::separated_list,
-    number::complete::double,
-    sequence::{delimited, preceded, separated_pair, terminated},
-    IResult,
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    Err, IResult,
 };
 use serde::{Serialize, Serializer};
 use std::{collections::BTreeMap, str};
@@ -58,6 +57,64 @@ impl Serialize for JsonValue {
     }
 }
 
+/// Replace the escape sequences of a JSON string, which are kept as written
+/// when parsing, with the characters they represent
+pub(crate) fn unescape(string: &str) -> String {
+    let mut unescaped = String::with_capacity(string.len());
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('b') => unescaped.push('\u{8}'),
+            Some('f') => unescaped.push('\u{c}'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('u') => {
+                let unit = |chars: &mut str::Chars| {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    u16::from_str_radix(&hex, 16).unwrap_or_default()
+                };
+                let first = unit(&mut chars);
+                let mut units = vec![first];
+                if (0xD800..0xDC00).contains(&first) && chars.as_str().starts_with("\\u") {
+                    chars.nth(1);
+                    units.push(unit(&mut chars));
+                }
+                unescaped.extend(
+                    std::char::decode_utf16(units)
+                        .map(|c| c.unwrap_or(std::char::REPLACEMENT_CHARACTER)),
+                );
+            }
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+/// Escape text as the contents of a JSON string, the way strings are stored
+/// in the trees. Public for the strings built by the `jsxn-macros` crate.
+#[doc(hidden)]
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 fn json_boolean<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, bool, E> {
     let parse_true = value(true, tag("true"));
     let parse_false = value(false, tag("false"));
@@ -73,97 +130,274 @@ fn json_unicode_sequence<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a
     )(i)
 }
 
-pub(crate) fn json_string<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, String, E> {
+pub(crate) fn json_string<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, String, E> {
     context(
         "json string",
+        alt((
+            |i| {
+                let (input_remainder, string) = alt((
+                    value("", tag("\"\"")),
+                    delimited(
+                        char('\"'),
+                        escaped(
+                            is_not("\\\""),
+                            '\\',
+                            alt((
+                                map(one_of("\"\\/bfnrt"), |_| ()),
+                                map(json_unicode_sequence, |_| ()),
+                            )),
+                        ),
+                        char('\"'),
+                    ),
+                ))(i)?;
+                state.check_string(i, string.len())?;
+                Ok((input_remainder, String::from(string)))
+            },
+            enabled(state.options.json5, |i| json5_string(i, state)),
+        )),
+    )(i)
+}
+
+/// A single or double quoted JSON5 string, which may continue across lines.
+/// Escapes are kept as written, like in a JSON string, except for quotes and
+/// line continuations which are rewritten to their JSON equivalent.
+fn json5_string<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, String, E> {
+    let quote = match i.chars().next() {
+        Some(quote) if quote == '"' || quote == '\'' => quote,
+        _ => return Err(Err::Error(E::from_char(i, '"'))),
+    };
+    let mut string = String::new();
+    let mut chars = i.char_indices().skip(1).peekable();
+    while let Some((index, c)) = chars.next() {
+        state.check_string(i, string.len())?;
+        match c {
+            c if c == quote => return Ok((&i[index + 1..], string)),
+            '\\' => match chars.next() {
+                Some((_, '\r')) => {
+                    chars.next_if(|(_, c)| *c == '\n');
+                }
+                Some((_, '\n')) | Some((_, '\u{2028}')) | Some((_, '\u{2029}')) => {}
+                Some((_, '\'')) => string.push('\''),
+                Some((_, c)) => {
+                    string.push('\\');
+                    string.push(c);
+                }
+                None => break,
+            },
+            '"' => string.push_str("\\\""),
+            '\r' | '\n' => return Err(Err::Error(E::from_char(&i[index..], quote))),
+            c => string.push(c),
+        }
+    }
+    Err(Err::Error(E::from_char(&i[i.len()..], quote)))
+}
+
+/// An unquoted JSON5 object key
+pub(crate) fn json5_identifier<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, String, E> {
+    let (input_remainder, identifier) = context(
+        "json5 identifier",
+        recognize(pair(
+            verify(anychar, |c: &char| {
+                c.is_alphabetic() || *c == '_' || *c == '$'
+            }),
+            take_while(|c: char| c.is_alphanumeric() || c == '_' || c == '$'),
+        )),
+    )(i)?;
+    state.check_string(i, identifier.len())?;
+    Ok((input_remainder, String::from(identifier)))
+}
+
+/// The integer part of a decimal number, without leading zeros
+fn integer<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+    alt((tag("0"), recognize(pair(one_of("123456789"), digit0))))(i)
+}
+
+/// The exponent of a decimal number
+fn exponent<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+    recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1)))(i)
+}
+
+/// A JSON number
+fn json_number<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, f64, E> {
+    map(
+        recognize(tuple((
+            opt(char('-')),
+            integer,
+            opt(pair(char('.'), digit1)),
+            opt(exponent),
+        ))),
+        // The recognized number is always valid
+        |num: &str| num.parse().unwrap_or_default(),
+    )(i)
+}
+
+/// A JSON5 number: a decimal number that may have a leading or trailing
+/// `.`, a hexadecimal integer, `Infinity` or `NaN`, optionally signed with
+/// `+` or `-`
+fn json5_number<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, f64, E> {
+    context(
+        "json5 number",
         map(
-            alt((
-                value("", tag("\"\"")),
-                delimited(
-                    char('\"'),
-                    escaped(
-                        is_not("\\\""),
-                        '\\',
-                        alt((
-                            map(one_of("\"\\/bfnrt"), |_| ()),
-                            map(json_unicode_sequence, |_| ()),
+            pair(
+                opt(one_of("+-")),
+                alt((
+                    map(
+                        preceded(tag_no_case("0x"), hex_digit1),
+                        |hex_digits: &str| {
+                            hex_digits.chars().fold(0.0, |acc, c| {
+                                acc * 16.0 + f64::from(c.to_digit(16).unwrap_or_default())
+                            })
+                        },
+                    ),
+                    value(f64::INFINITY, tag("Infinity")),
+                    value(f64::NAN, tag("NaN")),
+                    map(
+                        recognize(pair(
+                            alt((
+                                recognize(pair(integer, opt(pair(char('.'), digit0)))),
+                                recognize(pair(char('.'), digit1)),
+                            )),
+                            opt(exponent),
                         )),
+                        |num: &str| num.parse().unwrap_or_default(),
                     ),
-                    char('\"'),
-                ),
-            )),
-            String::from,
+                )),
+            ),
+            |(sign, num)| if sign == Some('-') { -num } else { num },
         ),
     )(i)
 }
 
-fn json_array<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, Vec<JsonValue>, E> {
-    context(
-        "json array",
-        preceded(
-            char('['),
+fn json_array<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, Vec<JsonValue>, E> {
+    context("json array", |i| {
+        let (input_remainder, _) = char('[')(i)?;
+        state.nested(i, || {
             cut(terminated(
-                separated_list(preceded(sp, char(',')), json_value),
-                preceded(sp, char(']')),
-            )),
-        ),
-    )(i)
+                |i| state.list(i, |i| json_value(i, state)),
+                preceded(|i| state.ws(i), char(']')),
+            ))(input_remainder)
+        })
+    })(i)
 }
 
 fn json_key_value<'a, E: ParseError<&'a str>>(
     i: &'a str,
+    state: &State<'a>,
 ) -> IResult<&'a str, (String, JsonValue), E> {
     context(
         "json key value",
         separated_pair(
-            preceded(sp, json_string),
-            cut(preceded(sp, char(':'))),
-            json_value,
+            preceded(
+                |i| state.ws(i),
+                alt((
+                    |i| json_string(i, state),
+                    enabled(state.options.json5, |i| json5_identifier(i, state)),
+                )),
+            ),
+            cut(preceded(|i| state.ws(i), char(':'))),
+            |i| json_value(i, state),
         ),
     )(i)
 }
 
 fn json_object<'a, E: ParseError<&'a str>>(
     i: &'a str,
+    state: &State<'a>,
 ) -> IResult<&'a str, BTreeMap<String, JsonValue>, E> {
-    context(
-        "json object",
-        preceded(
-            char('{'),
+    context("json object", |i| {
+        let (input_remainder, _) = char('{')(i)?;
+        state.nested(i, || {
             cut(terminated(
                 map(
-                    separated_list(preceded(sp, char(',')), json_key_value),
+                    |i| state.list(i, |i| json_key_value(i, state)),
                     |tuple_vec| tuple_vec.into_iter().collect(),
                 ),
-                preceded(sp, char('}')),
-            )),
-        ),
-    )(i)
+                preceded(|i| state.ws(i), char('}')),
+            ))(input_remainder)
+        })
+    })(i)
+}
+
+pub(crate) fn json_scalar<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsonValue, E> {
+    alt((
+        map(|i| json_string(i, state), JsonValue::Str),
+        map(enabled(state.options.json5, json5_number), JsonValue::Num),
+        map(json_number, JsonValue::Num),
+        map(json_boolean, JsonValue::Boolean),
+        map(tag("null"), |_| JsonValue::Null),
+    ))(i)
 }
 
-pub(crate) fn json_value<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsonValue, E> {
+pub(crate) fn json_value<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsonValue, E> {
     preceded(
-        sp,
-        alt((
-            map(json_object, JsonValue::Object),
-            map(json_array, JsonValue::Array),
-            map(json_string, JsonValue::Str),
-            map(double, JsonValue::Num),
-            map(json_boolean, JsonValue::Boolean),
-            map(tag("null"), |_| JsonValue::Null),
-            map(jsx_value, |jsx| JsonValue::JsxValue(Box::new(jsx))),
-        )),
+        |i| state.ws(i),
+        |i| {
+            state.node(
+                i,
+                alt((
+                    map(|i| json_object(i, state), JsonValue::Object),
+                    map(|i| json_array(i, state), JsonValue::Array),
+                    |i| json_scalar(i, state),
+                    map(
+                        enabled(state.options.jsx_in_json, |i| jsx_value(i, state)),
+                        |jsx| JsonValue::JsxValue(Box::new(jsx)),
+                    ),
+                )),
+            )
+        },
     )(i)
 }
 
-/// The root JSON object or array of a JSON tree
+/// The root JSON value of a JSON tree
 pub fn root<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsonValue, E> {
+    root_with(i, &State::new(&ParserOptions::STRICT))
+}
+
+/// The root JSON value of a JSON tree, parsed with the given
+/// options
+pub fn root_with_options<'a, E: ParseError<&'a str>>(
+    options: &'a ParserOptions,
+) -> impl Fn(&'a str) -> IResult<&'a str, JsonValue, E> {
+    move |i| root_with(i, &State::new(options))
+}
+
+pub(crate) fn root_with<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsonValue, E> {
     delimited(
-        sp,
-        alt((
-            map(json_object, JsonValue::Object),
-            map(json_array, JsonValue::Array),
-        )),
-        opt(sp),
+        |i| state.ws(i),
+        verify(
+            |i| {
+                state.node(
+                    i,
+                    alt((
+                        map(|i| json_object(i, state), JsonValue::Object),
+                        map(|i| json_array(i, state), JsonValue::Array),
+                        enabled(state.options.roots.scalar, |i| json_scalar(i, state)),
+                    )),
+                )
+            },
+            |json| state.options.roots.allows_json(json),
+        ),
+        opt(|i| state.ws(i)),
     )(i)
 }
@@ -1,18 +1,22 @@
 use crate::{
-    json::{json_string, json_value, JsonValue},
-    shared::sp,
+    error::Span,
+    json::{escape, json_string, json_value, JsonValue},
+    jsxn::JsxnValue,
+    options::ParserOptions,
+    shared::{enabled, sp, State},
 };
 use nom::{
     branch::alt,
+    bytes::complete::{take_while, take_while1},
     character::complete::{alphanumeric1 as alphanumeric, anychar, char},
-    combinator::{cut, map, opt, peek, verify},
+    combinator::{cut, map, opt, peek, recognize, verify},
     error::{context, ParseError},
-    multi::{fold_many0, many0, many_till},
+    multi::{many_till, separated_nonempty_list},
     sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
-    IResult,
+    Err, IResult,
 };
 use serde::{Serialize, Serializer};
-use std::collections::BTreeMap;
+use std::{cell::Cell, collections::BTreeMap};
 
 /// A JSX-specific value
 #[derive(Debug, PartialEq, Clone)]
@@ -29,6 +33,14 @@ pub enum JsxValue {
     /// A JSX Expression containing a JSON value, a JSX Element, or a JSX
     /// Fragment
     JsxExpression(Box<JsxValue>),
+
+    /// Input that could not be parsed, inserted in place of a value by the
+    /// recovering parser
+    Error(Span),
+
+    /// A reference to a value of the template context, such as `$user.name`,
+    /// as the path of object keys and array indices to the value
+    Variable(Vec<String>),
 }
 
 impl Serialize for JsxValue {
@@ -41,6 +53,8 @@ impl Serialize for JsxValue {
             JsxValue::JsxFragment(jsx_fragment) => jsx_fragment.serialize(serializer),
             JsxValue::JsonValue(json_value) => json_value.serialize(serializer),
             JsxValue::JsxExpression(jsx_expression) => jsx_expression.serialize(serializer),
+            JsxValue::Error(_) => serializer.serialize_unit(),
+            JsxValue::Variable(path) => format!("${}", path.join(".")).serialize(serializer),
         }
     }
 }
@@ -67,6 +81,36 @@ impl JsxElement {
             children,
         }
     }
+
+    /// The element type
+    pub fn r#type(&self) -> &str {
+        &self.r#type
+    }
+
+    /// The element props
+    pub fn props(&self) -> &BTreeMap<String, JsxValue> {
+        &self.props
+    }
+
+    /// The element children
+    pub fn children(&self) -> &[JsxValue] {
+        &self.children
+    }
+
+    /// The element props, to be modified
+    pub fn props_mut(&mut self) -> &mut BTreeMap<String, JsxValue> {
+        &mut self.props
+    }
+
+    /// The element children, to be modified
+    pub fn children_mut(&mut self) -> &mut Vec<JsxValue> {
+        &mut self.children
+    }
+
+    /// Take the element apart into its type, props and children
+    pub fn into_parts(self) -> (String, BTreeMap<String, JsxValue>, Vec<JsxValue>) {
+        (self.r#type, self.props, self.children)
+    }
 }
 
 /// JSX Element that correlates to the arguments for `React.createElement`.
@@ -85,19 +129,154 @@ impl JsxFragment {
             children,
         }
     }
+
+    /// The fragment children
+    pub fn children(&self) -> &[JsxValue] {
+        &self.children
+    }
+
+    /// The fragment children, to be modified
+    pub fn children_mut(&mut self) -> &mut Vec<JsxValue> {
+        &mut self.children
+    }
+
+    /// Take the fragment apart into its children
+    pub fn into_children(self) -> Vec<JsxValue> {
+        self.children
+    }
+}
+
+/// Rust values that can be converted into JSX values, such as the values
+/// interpolated into trees built with the `jsxn!` macro. Strings, numbers,
+/// booleans, options and sequences become JSON values, with strings escaped
+/// the way they are kept in the trees.
+pub trait ToJsxValue {
+    /// Convert the value into a JSX value
+    fn to_jsx_value(&self) -> JsxValue;
+}
+
+impl ToJsxValue for JsxValue {
+    fn to_jsx_value(&self) -> JsxValue {
+        self.clone()
+    }
+}
+
+impl ToJsxValue for JsxElement {
+    fn to_jsx_value(&self) -> JsxValue {
+        JsxValue::JsxElement(self.clone())
+    }
+}
+
+impl ToJsxValue for JsxFragment {
+    fn to_jsx_value(&self) -> JsxValue {
+        JsxValue::JsxFragment(self.clone())
+    }
+}
+
+impl ToJsxValue for JsonValue {
+    fn to_jsx_value(&self) -> JsxValue {
+        JsxValue::JsonValue(self.clone())
+    }
+}
+
+impl ToJsxValue for JsxnValue {
+    fn to_jsx_value(&self) -> JsxValue {
+        match self {
+            JsxnValue::JsxValue(jsx) => jsx.clone(),
+            JsxnValue::JsonValue(json) => JsxValue::JsonValue(json.clone()),
+        }
+    }
+}
+
+impl ToJsxValue for str {
+    fn to_jsx_value(&self) -> JsxValue {
+        JsxValue::JsonValue(JsonValue::Str(escape(self)))
+    }
+}
+
+impl ToJsxValue for String {
+    fn to_jsx_value(&self) -> JsxValue {
+        self.as_str().to_jsx_value()
+    }
+}
+
+impl ToJsxValue for bool {
+    fn to_jsx_value(&self) -> JsxValue {
+        JsxValue::JsonValue(JsonValue::Boolean(*self))
+    }
+}
+
+macro_rules! number_to_jsx_value {
+    ($($number:ty),*) => {
+        $(
+            impl ToJsxValue for $number {
+                fn to_jsx_value(&self) -> JsxValue {
+                    JsxValue::JsonValue(JsonValue::Num(*self as f64))
+                }
+            }
+        )*
+    };
+}
+
+number_to_jsx_value!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T: ToJsxValue> ToJsxValue for Option<T> {
+    /// `None` becomes `null`
+    fn to_jsx_value(&self) -> JsxValue {
+        match self {
+            Some(value) => value.to_jsx_value(),
+            None => JsxValue::JsonValue(JsonValue::Null),
+        }
+    }
 }
 
-fn jsx_text<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsxValue, E> {
+impl<T: ToJsxValue> ToJsxValue for [T] {
+    /// Sequences become JSON arrays, holding the JSX values among their
+    /// items
+    fn to_jsx_value(&self) -> JsxValue {
+        JsxValue::JsonValue(JsonValue::Array(
+            self.iter()
+                .map(|item| match item.to_jsx_value() {
+                    JsxValue::JsonValue(json) => json,
+                    jsx => JsonValue::JsxValue(Box::new(jsx)),
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl<T: ToJsxValue> ToJsxValue for Vec<T> {
+    fn to_jsx_value(&self) -> JsxValue {
+        self.as_slice().to_jsx_value()
+    }
+}
+
+impl<T: ToJsxValue + ?Sized> ToJsxValue for &T {
+    fn to_jsx_value(&self) -> JsxValue {
+        (**self).to_jsx_value()
+    }
+}
+
+pub(crate) fn jsx_text<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxValue, E> {
+    let length = Cell::new(0);
     let (input_remainder, (chars, ..)) = context(
         "jsx text",
         verify(
             many_till(
-                anychar,
+                |c| {
+                    let (input_remainder, c) = anychar(c)?;
+                    length.set(length.get() + c.len_utf8());
+                    state.check_string(i, length.get())?;
+                    Ok((input_remainder, c))
+                },
                 peek(preceded(
                     sp,
                     alt((
-                        map(jsx_expression, |_| ()),
-                        map(root, |_| ()),
+                        map(|i| jsx_expression(i, state), |_| ()),
+                        map(|i| jsx_value(i, state), |_| ()),
                         map(jsx_element_closing_tag, |_| ()),
                         jsx_fragement_closing_tag,
                     )),
@@ -106,92 +285,179 @@ fn jsx_text<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsxValue
             |result| !result.0.is_empty(),
         ),
     )(i)?;
-    Ok((
-        input_remainder,
-        JsxValue::JsonValue(JsonValue::Str(chars.into_iter().collect())),
-    ))
+    let text: String = chars.into_iter().collect();
+    Ok((input_remainder, JsxValue::JsonValue(JsonValue::Str(text))))
 }
 
-fn jsx_expression<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsxValue, E> {
-    context(
-        "jsx expression",
-        preceded(
-            char('{'),
+fn jsx_expression<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxValue, E> {
+    context("jsx expression", |i| {
+        let (input_remainder, _) = char('{')(i)?;
+        state.nested(i, || {
             cut(terminated(
                 map(
                     alt((
-                        map(json_value, |json| {
-                            if let JsonValue::JsxValue(jsx) = json {
-                                return *jsx;
-                            }
-                            JsxValue::JsonValue(json)
-                        }),
-                        root,
+                        map(
+                            enabled(state.options.json_in_jsx, |i| json_value(i, state)),
+                            |json| {
+                                if let JsonValue::JsxValue(jsx) = json {
+                                    return *jsx;
+                                }
+                                JsxValue::JsonValue(json)
+                            },
+                        ),
+                        preceded(|i| state.ws(i), |i| jsx_value(i, state)),
+                        preceded(
+                            |i| state.ws(i),
+                            enabled(state.options.variables, jsx_variable),
+                        ),
                     )),
                     |jsx| JsxValue::JsxExpression(Box::new(jsx)),
                 ),
-                preceded(sp, char('}')),
-            )),
-        ),
-    )(i)
+                preceded(|i| state.ws(i), char('}')),
+            ))(input_remainder)
+        })
+    })(i)
 }
 
-fn jsx_children<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, Vec<JsxValue>, E> {
+/// A reference to a template variable, such as `$user.name` or
+/// `$items.0`
+pub(crate) fn jsx_variable<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, JsxValue, E> {
     context(
-        "jsx children",
-        many0(preceded(sp, alt((jsx_expression, root, jsx_text)))),
+        "jsx variable",
+        map(
+            preceded(
+                char('$'),
+                separated_nonempty_list(
+                    char('.'),
+                    map(
+                        take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '$'),
+                        String::from,
+                    ),
+                ),
+            ),
+            JsxValue::Variable,
+        ),
     )(i)
 }
 
-fn jsx_element_opening_tag<'a, E: ParseError<&'a str>>(
+pub(crate) fn jsx_child<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxValue, E> {
+    state.node(
+        i,
+        alt((
+            |i| jsx_expression(i, state),
+            |i| jsx_value(i, state),
+            |i| jsx_text(i, state),
+        )),
+    )
+}
+
+/// The children of the element or fragment starting at `start`
+fn jsx_children<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    start: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, Vec<JsxValue>, E> {
+    context("jsx children", |i| {
+        let mut children = vec![];
+        let mut input_remainder = i;
+        loop {
+            match preceded(sp, |i| jsx_child(i, state))(input_remainder) {
+                Ok((rest, child)) => {
+                    children.push(child);
+                    state.check_children(start, children.len())?;
+                    input_remainder = rest;
+                }
+                Err(Err::Error(_)) => return Ok((input_remainder, children)),
+                Err(e) => return Err(e),
+            }
+        }
+    })(i)
+}
+
+fn jsx_prop_value<'a, E: ParseError<&'a str>>(
     i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxValue, E> {
+    state.node(
+        i,
+        alt((
+            |i| jsx_expression(i, state),
+            map(
+                |i| json_string(i, state),
+                |s| JsxValue::JsonValue(JsonValue::Str(s)),
+            ),
+        )),
+    )
+}
+
+/// A prop name, which may also hold `-` after its first character, like
+/// `data-id`
+pub(crate) fn jsx_prop_name<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    recognize(pair(
+        alphanumeric,
+        take_while(|c: char| c.is_alphanumeric() || c == '-'),
+    ))(i)
+}
+
+pub(crate) fn jsx_element_opening_tag<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
 ) -> IResult<&'a str, (String, BTreeMap<String, JsxValue>), E> {
-    context(
-        "jsx element opening tag",
-        preceded(
+    context("jsx element opening tag", |i| {
+        let (mut input_remainder, tag) = preceded(
             char('<'),
-            pair(
-                preceded(sp, map(alphanumeric, String::from)),
+            preceded(|i| state.ws(i), map(alphanumeric, String::from)),
+        )(i)?;
+        input_remainder = state.ws(input_remainder)?.0;
+        let mut props = BTreeMap::new();
+        loop {
+            let prop = alt((
+                separated_pair(
+                    preceded(|i| state.ws(i), map(jsx_prop_name, String::from)),
+                    preceded(|i| state.ws(i), char('=')),
+                    preceded(|i| state.ws(i), |i| jsx_prop_value(i, state)),
+                ),
                 preceded(
-                    sp,
-                    fold_many0(
-                        alt((
-                            separated_pair(
-                                preceded(sp, map(alphanumeric, String::from)),
-                                preceded(sp, char('=')),
-                                preceded(
-                                    sp,
-                                    alt((
-                                        jsx_expression,
-                                        map(json_string, |s| {
-                                            JsxValue::JsonValue(JsonValue::Str(s))
-                                        }),
-                                    )),
-                                ),
-                            ),
-                            preceded(
-                                sp,
-                                map(alphanumeric, |prop| {
-                                    (
-                                        String::from(prop),
-                                        JsxValue::JsonValue(JsonValue::Boolean(true)),
-                                    )
-                                }),
-                            ),
-                        )),
-                        BTreeMap::new(),
-                        |mut acc: BTreeMap<_, _>, (key, value)| {
-                            acc.insert(key, value);
-                            acc
-                        },
-                    ),
+                    |i| state.ws(i),
+                    |i| {
+                        state.node(
+                            i,
+                            map(jsx_prop_name, |prop| {
+                                (
+                                    String::from(prop),
+                                    JsxValue::JsonValue(JsonValue::Boolean(true)),
+                                )
+                            }),
+                        )
+                    },
                 ),
-            ),
-        ),
-    )(i)
+            ))(input_remainder);
+            match prop {
+                Ok((rest, (key, value))) => {
+                    props.insert(key, value);
+                    state.check_props(i, props.len())?;
+                    input_remainder = rest;
+                }
+                Err(Err::Error(_)) => return Ok((input_remainder, (tag, props))),
+                Err(e) => return Err(e),
+            }
+        }
+    })(i)
 }
 
-fn jsx_element_closing_tag<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
+pub(crate) fn jsx_element_closing_tag<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, &'a str, E> {
     context(
         "jsx element closing tag",
         delimited(
@@ -206,7 +472,15 @@ fn jsx_element_closing_tag<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'
     )(i)
 }
 
-fn jsx_fragement_closing_tag<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, (), E> {
+pub(crate) fn jsx_fragment_opening_tag<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, (), E> {
+    map(preceded(char('<'), preceded(opt(sp), char('>'))), |_| ())(i)
+}
+
+pub(crate) fn jsx_fragement_closing_tag<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+) -> IResult<&'a str, (), E> {
     context(
         "jsx fragment closing tag",
         delimited(
@@ -225,12 +499,16 @@ fn jsx_fragement_closing_tag<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<
 
 fn jsx_element_self_closing<'a, E: ParseError<&'a str>>(
     i: &'a str,
+    state: &State<'a>,
 ) -> IResult<&'a str, JsxElement, E> {
     let (input_remainder, (tag, props)) = context(
         "jsx element self closing",
         terminated(
-            jsx_element_opening_tag,
-            preceded(sp, preceded(char('/'), preceded(sp, char('>')))),
+            |i| jsx_element_opening_tag(i, state),
+            preceded(
+                |i| state.ws(i),
+                preceded(char('/'), preceded(|i| state.ws(i), char('>'))),
+            ),
         ),
     )(i)?;
     Ok((input_remainder, JsxElement::new(tag, props, vec![])))
@@ -238,47 +516,96 @@ fn jsx_element_self_closing<'a, E: ParseError<&'a str>>(
 
 fn jsx_element_with_children<'a, E: ParseError<&'a str>>(
     i: &'a str,
+    state: &State<'a>,
 ) -> IResult<&'a str, JsxElement, E> {
     let (input_remainder, ((tag, props), children, ..)) = context(
         "jsx element with children",
         verify(
             tuple((
-                terminated(jsx_element_opening_tag, preceded(sp, char('>'))),
-                jsx_children,
+                terminated(
+                    |i| jsx_element_opening_tag(i, state),
+                    preceded(|i| state.ws(i), char('>')),
+                ),
+                |rest| jsx_children(rest, i, state),
                 jsx_element_closing_tag,
             )),
             |((tag, ..), .., closing_tag)| tag == closing_tag,
         ),
     )(i)?;
-
     Ok((input_remainder, JsxElement::new(tag, props, children)))
 }
 
-fn jsx_fragment<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsxValue, E> {
-    context(
+fn jsx_fragment<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxValue, E> {
+    let (input_remainder, children) = context(
         "jsx fragment",
-        map(
-            delimited(
-                preceded(char('<'), preceded(opt(sp), char('>'))),
-                jsx_children,
-                jsx_fragement_closing_tag,
-            ),
-            |x| JsxValue::JsxFragment(JsxFragment::new(x)),
+        delimited(
+            jsx_fragment_opening_tag,
+            |rest| jsx_children(rest, i, state),
+            jsx_fragement_closing_tag,
         ),
-    )(i)
+    )(i)?;
+    Ok((
+        input_remainder,
+        JsxValue::JsxFragment(JsxFragment::new(children)),
+    ))
 }
 
-fn jsx_element<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsxValue, E> {
+fn jsx_element<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxValue, E> {
     context(
         "jsx element",
         map(
-            alt((jsx_element_with_children, jsx_element_self_closing)),
+            alt((
+                |i| jsx_element_with_children(i, state),
+                |i| jsx_element_self_closing(i, state),
+            )),
             JsxValue::JsxElement,
         ),
     )(i)
 }
 
+pub(crate) fn jsx_value<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxValue, E> {
+    state.node(i, |i| {
+        let value = alt((|i| jsx_element(i, state), |i| jsx_fragment(i, state)));
+        if i.starts_with('<') {
+            state.nested(i, || value(i))
+        } else {
+            value(i)
+        }
+    })
+}
+
 /// The root JSX Element or JSX Fragment of a JSX tree
 pub fn root<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsxValue, E> {
-    delimited(sp, alt((jsx_element, jsx_fragment)), opt(sp))(i)
+    root_with(i, &State::new(&ParserOptions::STRICT))
+}
+
+/// The root JSX Element or JSX Fragment of a JSX tree, parsed with the given
+/// options
+pub fn root_with_options<'a, E: ParseError<&'a str>>(
+    options: &'a ParserOptions,
+) -> impl Fn(&'a str) -> IResult<&'a str, JsxValue, E> {
+    move |i| root_with(i, &State::new(options))
+}
+
+pub(crate) fn root_with<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxValue, E> {
+    delimited(
+        |i| state.ws(i),
+        verify(
+            |i| jsx_value(i, state),
+            |jsx| state.options.roots.allows_jsx(jsx),
+        ),
+        opt(|i| state.ws(i)),
+    )(i)
 }
@@ -1,8 +1,16 @@
-use crate::{json, jsx, shared::sp};
+use crate::{
+    encoding::decode,
+    error::{Error, Location},
+    events::Parser,
+    json, jsx,
+    limits::ParseLimits,
+    options::ParserOptions,
+    shared::State,
+};
 use nom::{
     branch::alt,
     combinator::{map, opt},
-    error::ParseError,
+    error::{ParseError, VerboseError},
     sequence::delimited,
     IResult,
 };
@@ -32,12 +40,171 @@ impl Serialize for JsxnValue {
 
 /// The root JSX or JSON of a JSXN tree
 pub fn root<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, JsxnValue, E> {
+    root_with(i, &State::new(&ParserOptions::STRICT))
+}
+
+/// The root JSX or JSON of a JSXN tree, parsed with the given options
+pub fn root_with_options<'a, E: ParseError<&'a str>>(
+    options: &'a ParserOptions,
+) -> impl Fn(&'a str) -> IResult<&'a str, JsxnValue, E> {
+    move |i| root_with(i, &State::new(options))
+}
+
+pub(crate) fn root_with<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    state: &State<'a>,
+) -> IResult<&'a str, JsxnValue, E> {
     delimited(
-        sp,
+        |i| state.ws(i),
         alt((
-            map(json::root, JsxnValue::JsonValue),
-            map(jsx::root, JsxnValue::JsxValue),
+            map(|i| json::root_with(i, state), JsxnValue::JsonValue),
+            map(|i| jsx::root_with(i, state), JsxnValue::JsxValue),
         )),
-        opt(sp),
+        opt(|i| state.ws(i)),
     )(i)
 }
+
+/// Parse a complete JSXN document
+pub fn parse(input: &str) -> Result<JsxnValue, Error> {
+    parse_with_options(input, &ParserOptions::STRICT)
+}
+
+/// Parse a complete JSXN document, rejecting it if it exceeds any of the
+/// given limits
+pub fn parse_with_limits(input: &str, limits: &ParseLimits) -> Result<JsxnValue, Error> {
+    parse_with_options(
+        input,
+        &ParserOptions {
+            limits: *limits,
+            ..ParserOptions::STRICT
+        },
+    )
+}
+
+/// Parse a complete JSXN document with the given options
+pub fn parse_with_options(input: &str, options: &ParserOptions) -> Result<JsxnValue, Error> {
+    Parser::with_options(input, *options).build()
+}
+
+/// Parse a complete JSXN document from bytes, which may be UTF-8 with or
+/// without a byte order mark, or UTF-16 with a byte order mark. Locations
+/// are byte offsets into UTF-8 input, or into the transcoded text of UTF-16
+/// input.
+pub fn parse_bytes(input: &[u8]) -> Result<JsxnValue, Error> {
+    parse_bytes_with_options(input, &ParserOptions::STRICT)
+}
+
+/// Parse a complete JSXN document from bytes with the given options
+pub fn parse_bytes_with_options(input: &[u8], options: &ParserOptions) -> Result<JsxnValue, Error> {
+    if let Some(limit) = options.limits.max_input_bytes {
+        if input.len() > limit {
+            let valid = match std::str::from_utf8(&input[..limit]) {
+                Ok(valid) => valid,
+                Err(e) => std::str::from_utf8(&input[..e.valid_up_to()]).unwrap_or_default(),
+            };
+            return Err(Error::InputTooLarge {
+                location: Location {
+                    offset: limit,
+                    ..Location::new(valid, valid.len())
+                },
+                limit,
+            });
+        }
+    }
+
+    let decoded = decode(input)?;
+    parse_with_options(&decoded.text, options).map_err(|e| decoded.locate(e))
+}
+
+/// Parse a JSXN document, recovering from syntax errors to return a best
+/// effort tree with `JsxValue::Error` in place of the input that could not be
+/// parsed, along with every error found
+pub fn parse_recovering(input: &str) -> (JsxnValue, Vec<Error>) {
+    parse_recovering_with_options(input, &ParserOptions::STRICT)
+}
+
+/// Parse a JSXN document with the given options, recovering from syntax
+/// errors
+pub fn parse_recovering_with_options(
+    input: &str,
+    options: &ParserOptions,
+) -> (JsxnValue, Vec<Error>) {
+    Parser::with_options(input, *options).build_recovering()
+}
+
+/// Parse a sequence of JSXN documents separated by whitespace, such as
+/// newline delimited JSON
+pub fn parse_documents(input: &str) -> Documents<'_> {
+    parse_documents_with_options(input, &ParserOptions::STRICT)
+}
+
+/// Parse a sequence of JSXN documents separated by whitespace with the given
+/// options. Limits other than `ParseLimits::max_input_bytes` apply to each
+/// document separately.
+pub fn parse_documents_with_options<'a>(input: &'a str, options: &ParserOptions) -> Documents<'a> {
+    Documents {
+        input,
+        rest: input,
+        options: *options,
+    }
+}
+
+/// An iterator over the documents of a multi-document input. A document that
+/// fails to parse yields an error located within the whole input, and
+/// parsing resumes at the next line after the error.
+pub struct Documents<'a> {
+    input: &'a str,
+    rest: &'a str,
+    options: ParserOptions,
+}
+
+impl<'a> Documents<'a> {
+    /// The byte offset of the input that has not been parsed yet
+    pub fn byte_offset(&self) -> usize {
+        self.input.len() - self.rest.len()
+    }
+
+    /// Skip to the start of the line after `offset`
+    fn recover(&mut self, offset: usize) {
+        self.rest = match self.input[offset..].find('\n') {
+            Some(newline) => &self.input[offset + newline + 1..],
+            None => "",
+        };
+    }
+}
+
+impl<'a> Iterator for Documents<'a> {
+    type Item = Result<JsxnValue, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(limit) = self.options.limits.max_input_bytes {
+            if self.input.len() > limit && self.byte_offset() == 0 {
+                self.rest = "";
+                return Some(Err(Error::InputTooLarge {
+                    location: Location::new(self.input, limit),
+                    limit,
+                }));
+            }
+        }
+
+        let options = self.options;
+        let state = State::new(&options);
+        let rest = self.rest;
+        let (consumed, result) = match state.ws::<VerboseError<&str>>(rest) {
+            Ok(("", _)) => (rest.len(), None),
+            Ok((document, _)) => match root_with::<VerboseError<&str>>(document, &state) {
+                Ok((remainder, value)) => (rest.len() - remainder.len(), Some(Ok(value))),
+                Err(e) => (0, Some(Err(Error::from_parse(self.input, &state, e)))),
+            },
+            Err(e) => (
+                rest.len(),
+                Some(Err(Error::from_parse(self.input, &state, e))),
+            ),
+        };
+        self.rest = &rest[consumed..];
+        if let Some(Err(error)) = &result {
+            self.recover(error.location().offset);
+        }
+        result
+    }
+}
@@ -2,6 +2,29 @@
 
 //! nom parsers for JSON and JSX
 
+/// JavaScript code generation for JSXN trees
+pub mod codegen;
+
+/// Component registry for expanding custom elements
+pub mod components;
+
+/// Errors returned by the JSXN entry points
+pub mod error;
+
+mod encoding;
+
+/// Pull parser yielding JSXN events
+pub mod events;
+
+/// Formatter writing JSXN trees back as source
+pub mod format;
+
+/// Conversion of HTML into JSX trees
+pub mod from_html;
+
+/// HTML renderer for JSX trees
+pub mod html;
+
 /// nom parser for JSON
 pub mod json;
 
@@ -11,4 +34,36 @@ pub mod jsx;
 /// nom parser for JSXN
 pub mod jsxn;
 
+/// Resource limits for parsing untrusted input
+pub mod limits;
+
+/// Options for configuring the parsers
+pub mod options;
+
+/// Serialization profiles for JSX Elements and JSX Fragments
+pub mod profile;
+
+/// CSS-like selectors querying the nodes of JSXN trees
+pub mod query;
+
+/// Serialization of Rust values into JSON and JSX trees
+pub mod ser;
+
 mod shared;
+
+/// Streaming parser for large JSXN inputs
+pub mod stream;
+
+/// Serialization tagged with node kinds, which round-trips through
+/// deserialization
+pub mod tagged;
+
+/// Evaluation of template variables against a JSON context
+pub mod template;
+
+/// Visitors walking JSXN trees, and folds rewriting them
+pub mod visit;
+
+/// Conversions between JSXN trees and `serde_json::Value`
+#[cfg(feature = "serde_json")]
+pub mod value;
@@ -0,0 +1,36 @@
+/// Resource limits applied while parsing untrusted input. Every limit is
+/// optional and `None` means unlimited, which is also the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseLimits {
+    /// Maximum size of the input in bytes
+    pub max_input_bytes: Option<usize>,
+
+    /// Maximum number of values in the tree, counting every JSON value and
+    /// every JSX element, fragment, text and expression
+    pub max_nodes: Option<usize>,
+
+    /// Maximum length in bytes of a JSON string, object key or JSX text
+    pub max_string_length: Option<usize>,
+
+    /// Maximum number of props on a single JSX element
+    pub max_props: Option<usize>,
+
+    /// Maximum number of children of a single JSX element or fragment
+    pub max_children: Option<usize>,
+
+    /// Maximum depth of arrays, objects, elements, fragments and expressions
+    /// nested inside one another, counting the root as depth 1
+    pub max_depth: Option<usize>,
+}
+
+impl ParseLimits {
+    /// Limits that allow any input
+    pub const UNLIMITED: ParseLimits = ParseLimits {
+        max_input_bytes: None,
+        max_nodes: None,
+        max_string_length: None,
+        max_props: None,
+        max_children: None,
+        max_depth: None,
+    };
+}
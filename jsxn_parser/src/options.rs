@@ -0,0 +1,128 @@
+use crate::{json::JsonValue, jsx::JsxValue, limits::ParseLimits};
+
+/// The kinds of values permitted at the root of a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootKinds {
+    /// A JSON object
+    pub object: bool,
+
+    /// A JSON array
+    pub array: bool,
+
+    /// A JSON string, number, boolean or null
+    pub scalar: bool,
+
+    /// A JSX Element
+    pub element: bool,
+
+    /// A JSX Fragment
+    pub fragment: bool,
+}
+
+impl RootKinds {
+    /// Every kind of root value, which is how RFC 8259 allows any JSON
+    /// value at the root of a document
+    pub const ALL: RootKinds = RootKinds {
+        object: true,
+        array: true,
+        scalar: true,
+        element: true,
+        fragment: true,
+    };
+
+    /// Objects, arrays, elements and fragments, which is the default, so
+    /// that scalar roots are only accepted when they are asked for
+    pub const COMPOUND: RootKinds = RootKinds {
+        scalar: false,
+        ..RootKinds::ALL
+    };
+
+    pub(crate) fn allows_json(&self, json: &JsonValue) -> bool {
+        match json {
+            JsonValue::Object(_) => self.object,
+            JsonValue::Array(_) => self.array,
+            JsonValue::JsxValue(jsx) => self.allows_jsx(jsx),
+            JsonValue::Str(_) | JsonValue::Num(_) | JsonValue::Boolean(_) | JsonValue::Null => {
+                self.scalar
+            }
+        }
+    }
+
+    pub(crate) fn allows_jsx(&self, jsx: &JsxValue) -> bool {
+        match jsx {
+            JsxValue::JsxElement(_) => self.element,
+            JsxValue::JsxFragment(_) => self.fragment,
+            JsxValue::JsonValue(json) => self.allows_json(json),
+            JsxValue::JsxExpression(_) | JsxValue::Error(_) | JsxValue::Variable(_) => false,
+        }
+    }
+}
+
+impl Default for RootKinds {
+    fn default() -> RootKinds {
+        RootKinds::COMPOUND
+    }
+}
+
+/// Dialect features of the parsers that can be switched at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Allow `// line` and `/* block */` comments wherever whitespace is
+    /// allowed outside of JSX text
+    pub comments: bool,
+
+    /// Allow a trailing comma after the last item of an array or object
+    pub trailing_commas: bool,
+
+    /// Allow the JSON5 extensions to JSON: unquoted object keys, single
+    /// quoted and multi-line strings, hexadecimal numbers, leading `+`,
+    /// `Infinity` and `NaN`
+    pub json5: bool,
+
+    /// Allow JSX Elements and JSX Fragments as JSON values
+    pub jsx_in_json: bool,
+
+    /// Allow JSON values inside JSX Expressions
+    pub json_in_jsx: bool,
+
+    /// Allow references to template variables inside JSX Expressions, such
+    /// as `{$user.name}`
+    pub variables: bool,
+
+    /// The kinds of values permitted at the root of a document
+    pub roots: RootKinds,
+
+    /// Resource limits applied while parsing
+    pub limits: ParseLimits,
+}
+
+impl ParserOptions {
+    /// The standard JSXN grammar, which is also the default
+    pub const STRICT: ParserOptions = ParserOptions {
+        comments: false,
+        trailing_commas: false,
+        json5: false,
+        jsx_in_json: true,
+        json_in_jsx: true,
+        variables: false,
+        roots: RootKinds::COMPOUND,
+        limits: ParseLimits::UNLIMITED,
+    };
+
+    /// The standard JSXN grammar with every syntax extension enabled, and
+    /// every kind of root, for documents written by hand
+    pub const RELAXED: ParserOptions = ParserOptions {
+        comments: true,
+        trailing_commas: true,
+        json5: true,
+        variables: true,
+        roots: RootKinds::ALL,
+        ..ParserOptions::STRICT
+    };
+}
+
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions::STRICT
+    }
+}
@@ -0,0 +1,252 @@
+use crate::{
+    json::{unescape, JsonValue},
+    jsx::JsxValue,
+    jsxn::JsxnValue,
+};
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use std::collections::BTreeMap;
+
+/// The shape that JSX Elements and JSX Fragments are serialized in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// `{"type", "props", "children"}` objects, as serialized by the
+    /// `Serialize` implementations of the trees
+    Current,
+
+    /// React element objects: `{"$$typeof", "type", "key", "ref", "props"}`,
+    /// with `key` and `ref` taken out of the props and the children in
+    /// `props.children`. Symbols are serialized as their `Symbol.for` keys,
+    /// so `$$typeof` is `"react.element"` and the type of a fragment is
+    /// `"react.fragment"`.
+    React,
+
+    /// `{"type", "props"}` objects with the children in `props.children`,
+    /// the plain element objects of the JavaScript implementation of JSXN
+    Jsxn,
+}
+
+/// A JSXN value that serializes its elements in the shape of a profile
+pub struct Profiled<'a> {
+    value: &'a JsxnValue,
+    profile: Profile,
+}
+
+/// Serialize the elements of a JSXN value in the shape of `profile`
+pub fn profiled(value: &JsxnValue, profile: Profile) -> Profiled<'_> {
+    Profiled { value, profile }
+}
+
+impl<'a> Serialize for Profiled<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match (self.value, self.profile) {
+            (JsxnValue::JsxValue(jsx), Profile::Current) => jsx.serialize(serializer),
+            (JsxnValue::JsxValue(jsx), profile) => Node::Jsx(jsx, profile).serialize(serializer),
+            (JsxnValue::JsonValue(json), Profile::Current) => json.serialize(serializer),
+            (JsxnValue::JsonValue(json), profile) => {
+                Node::Json(json, profile).serialize(serializer)
+            }
+        }
+    }
+}
+
+/// Serialize a JSX value in the shape of `profile`, with its JSON strings and
+/// keys unescaped, which `Profile::Current` otherwise keeps as stored
+#[cfg(feature = "serde_json")]
+pub(crate) fn serialize_jsx<S>(
+    jsx: &JsxValue,
+    profile: Profile,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    Node::Jsx(jsx, profile).serialize(serializer)
+}
+
+enum Node<'a> {
+    Jsx(&'a JsxValue, Profile),
+    Json(&'a JsonValue, Profile),
+
+    /// JSX text, which is kept as written rather than escaped like a JSON
+    /// string
+    Text(&'a str),
+
+    Element {
+        r#type: Option<&'a str>,
+        props: &'a BTreeMap<String, JsxValue>,
+        children: &'a [JsxValue],
+        profile: Profile,
+    },
+
+    Props {
+        props: &'a BTreeMap<String, JsxValue>,
+        children: &'a [JsxValue],
+        profile: Profile,
+    },
+
+    Children(&'a [JsxValue], Profile),
+}
+
+impl<'a> Node<'a> {
+    fn child(child: &'a JsxValue, profile: Profile) -> Node<'a> {
+        match child {
+            JsxValue::JsonValue(JsonValue::Str(text)) => Node::Text(text),
+            child => Node::Jsx(child, profile),
+        }
+    }
+}
+
+/// Whether a prop stays in the props of an element in the shape of
+/// `profile`, rather than being taken out of them or replaced by the children
+fn kept(name: &str, profile: Profile) -> bool {
+    match profile {
+        Profile::Current => true,
+        Profile::React => name != "key" && name != "ref" && name != "children",
+        Profile::Jsxn => name != "children",
+    }
+}
+
+impl<'a> Serialize for Node<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            Node::Jsx(jsx, profile) => match jsx {
+                JsxValue::JsxElement(element) => Node::Element {
+                    r#type: Some(element.r#type()),
+                    props: element.props(),
+                    children: element.children(),
+                    profile,
+                }
+                .serialize(serializer),
+                JsxValue::JsxFragment(fragment) => Node::Element {
+                    r#type: None,
+                    props: &EMPTY_PROPS,
+                    children: fragment.children(),
+                    profile,
+                }
+                .serialize(serializer),
+                JsxValue::JsonValue(json) => Node::Json(json, profile).serialize(serializer),
+                JsxValue::JsxExpression(jsx) => Node::Jsx(jsx, profile).serialize(serializer),
+                jsx => jsx.serialize(serializer),
+            },
+            Node::Json(json, profile) => match json {
+                JsonValue::Str(s) => serializer.serialize_str(&unescape(s)),
+                JsonValue::Array(array) => {
+                    let mut seq = serializer.serialize_seq(Some(array.len()))?;
+                    for item in array {
+                        seq.serialize_element(&Node::Json(item, profile))?;
+                    }
+                    seq.end()
+                }
+                JsonValue::Object(object) => {
+                    let mut map = serializer.serialize_map(Some(object.len()))?;
+                    for (key, value) in object {
+                        map.serialize_entry(&unescape(key), &Node::Json(value, profile))?;
+                    }
+                    map.end()
+                }
+                JsonValue::JsxValue(jsx) => Node::Jsx(jsx, profile).serialize(serializer),
+                json => json.serialize(serializer),
+            },
+            Node::Text(text) => serializer.serialize_str(text),
+            Node::Element {
+                r#type,
+                props,
+                children,
+                profile,
+            } => {
+                let mut map = serializer.serialize_map(None)?;
+                if profile == Profile::React {
+                    map.serialize_entry("$$typeof", "react.element")?;
+                }
+                match (r#type, profile) {
+                    (Some(r#type), _) => map.serialize_entry("type", r#type)?,
+                    (None, Profile::React) => map.serialize_entry("type", "react.fragment")?,
+                    (None, Profile::Jsxn) => map.serialize_entry("type", "Fragment")?,
+                    (None, Profile::Current) => {}
+                }
+                if profile == Profile::React {
+                    let key = match props.get("key") {
+                        Some(JsxValue::JsxExpression(key)) => key,
+                        key => key.unwrap_or(&NULL),
+                    };
+                    match key {
+                        JsxValue::JsonValue(JsonValue::Num(num)) => {
+                            map.serialize_entry("key", &num.to_string())?
+                        }
+                        JsxValue::JsonValue(JsonValue::Str(key)) => {
+                            map.serialize_entry("key", &unescape(key))?
+                        }
+                        _ => map.serialize_entry("key", &())?,
+                    }
+                    match props.get("ref") {
+                        Some(value) => map.serialize_entry("ref", &Node::Jsx(value, profile))?,
+                        None => map.serialize_entry("ref", &())?,
+                    }
+                }
+                if profile != Profile::Current {
+                    map.serialize_entry(
+                        "props",
+                        &Node::Props {
+                            props,
+                            children,
+                            profile,
+                        },
+                    )?;
+                } else {
+                    if r#type.is_some() {
+                        map.serialize_entry(
+                            "props",
+                            &Node::Props {
+                                props,
+                                children: &[],
+                                profile,
+                            },
+                        )?;
+                    }
+                    map.serialize_entry("children", &Node::Children(children, profile))?;
+                }
+                map.end()
+            }
+            Node::Props {
+                props,
+                children,
+                profile,
+            } => {
+                let mut map = serializer.serialize_map(None)?;
+                for (name, value) in props {
+                    if kept(name, profile) {
+                        map.serialize_entry(name, &Node::Jsx(value, profile))?;
+                    }
+                }
+                match children {
+                    [] => {}
+                    [child] => map.serialize_entry("children", &Node::child(child, profile))?,
+                    children => {
+                        map.serialize_entry("children", &Node::Children(children, profile))?
+                    }
+                }
+                map.end()
+            }
+            Node::Children(children, profile) => {
+                let mut seq = serializer.serialize_seq(Some(children.len()))?;
+                for child in children {
+                    seq.serialize_element(&Node::child(child, profile))?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+static EMPTY_PROPS: BTreeMap<String, JsxValue> = BTreeMap::new();
+
+static NULL: JsxValue = JsxValue::JsonValue(JsonValue::Null);
@@ -0,0 +1,741 @@
+use crate::{
+    error::Span,
+    json::{self, JsonValue},
+    jsx::{JsxElement, JsxFragment, JsxValue},
+    jsxn::JsxnValue,
+};
+use std::{error, fmt, str::FromStr};
+
+/// A node of a tree matched by a selector
+///
+/// A JSON value holding a JSX value, or a JSX value holding a JSON value, is
+/// the node of the value it holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Node<'a> {
+    /// A JSX Element
+    Element(&'a JsxElement),
+
+    /// A JSX Fragment
+    Fragment(&'a JsxFragment),
+
+    /// A text child of an element or fragment
+    Text(&'a str),
+
+    /// A JSX Expression, holding the value inside it
+    Expression(&'a JsxValue),
+
+    /// A template variable
+    Variable(&'a [String]),
+
+    /// Input that could not be parsed
+    Error(&'a Span),
+
+    /// A JSON value other than a JSX value
+    Json(&'a JsonValue),
+}
+
+/// A step of the path from the root of a tree to one of its nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// The value of a prop of an element
+    Prop(String),
+
+    /// A child of an element or fragment, by index
+    Child(usize),
+
+    /// The value inside an expression
+    Expression,
+
+    /// The value of an object entry, by key as stored, escaped
+    Key(String),
+
+    /// An item of an array, by index
+    Item(usize),
+}
+
+/// An error returned when parsing a selector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    /// The byte offset in the selector where the error was detected
+    pub offset: usize,
+
+    /// What was wrong
+    pub message: String,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl error::Error for SelectorError {}
+
+/// A CSS-like selector matching the nodes of a tree
+///
+/// A selector is a comma-separated list of alternatives, each of which is a
+/// sequence of compound selectors joined by descendant (whitespace) or child
+/// (`>`) combinators. A compound selector is an element type, or `*` for any
+/// node, followed by any number of:
+///
+/// - `[name]`, matching elements with the prop or objects with the key
+/// - `[name=value]`, also requiring the value to be a string, number or
+///   boolean written as `value`, through expressions
+/// - `:nth-child(An+B)`, `:nth-child(odd)` or `:nth-child(even)`, matching the
+///   children of elements and fragments, and the items of arrays, by position
+///   counting from 1, text and expressions included
+/// - `:key(name)`, matching the values of object entries with the key
+/// - `:element`, `:fragment`, `:text`, `:expression`, `:variable`, `:object`,
+///   `:array`, `:string`, `:number`, `:boolean` or `:null`, matching the
+///   nodes of that kind
+///
+/// Names and values are identifiers of letters, digits, `_` and `-`, or
+/// quoted strings. Prop values and object values are children of their
+/// element or object, and the value of an expression a child of the
+/// expression, so `Nav Link[href]` finds the links inside expressions of a
+/// `<Nav>` too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    alternatives: Vec<Complex>,
+}
+
+/// Compound selectors, with the combinator joining each to the previous one
+#[derive(Debug, Clone, PartialEq)]
+struct Complex {
+    compounds: Vec<(Combinator, Compound)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Compound {
+    r#type: Option<String>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Has(String),
+    Equals(String, String),
+    NthChild(i64, i64),
+    Key(String),
+    Kind(Kind),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Element,
+    Fragment,
+    Text,
+    Expression,
+    Variable,
+    Object,
+    Array,
+    String,
+    Number,
+    Boolean,
+    Null,
+}
+
+const KINDS: &[(&str, Kind)] = &[
+    ("element", Kind::Element),
+    ("fragment", Kind::Fragment),
+    ("text", Kind::Text),
+    ("expression", Kind::Expression),
+    ("variable", Kind::Variable),
+    ("object", Kind::Object),
+    ("array", Kind::Array),
+    ("string", Kind::String),
+    ("number", Kind::Number),
+    ("boolean", Kind::Boolean),
+    ("null", Kind::Null),
+];
+
+impl Selector {
+    /// Parse a selector
+    pub fn parse(selector: &str) -> Result<Selector, SelectorError> {
+        SelectorParser {
+            input: selector,
+            pos: 0,
+        }
+        .selector()
+    }
+
+    /// The nodes of a tree matching the selector, in document order with
+    /// props and object entries ordered by name
+    pub fn select<'a>(&self, jsxn: &'a JsxnValue) -> Vec<Node<'a>> {
+        let mut nodes = vec![];
+        self.walk(jsxn, &mut |entries| {
+            nodes.push(entries[entries.len() - 1].node)
+        });
+        nodes
+    }
+
+    /// The paths to the nodes of a tree matching the selector, in the order
+    /// of `select`
+    pub fn select_paths(&self, jsxn: &JsxnValue) -> Vec<Vec<Step>> {
+        let mut paths = vec![];
+        self.walk(jsxn, &mut |entries| {
+            paths.push(
+                entries[1..]
+                    .iter()
+                    .map(|entry| match entry.edge {
+                        Edge::Prop(name) => Step::Prop(String::from(name)),
+                        Edge::Child(index) => Step::Child(index),
+                        Edge::Expression => Step::Expression,
+                        Edge::Key(key) => Step::Key(String::from(key)),
+                        Edge::Item(index) => Step::Item(index),
+                        Edge::Root => unreachable!(),
+                    })
+                    .collect(),
+            )
+        });
+        paths
+    }
+
+    /// Whether the selector matches the node at the end of a path
+    pub fn matches(&self, jsxn: &JsxnValue, path: &[Step]) -> bool {
+        let mut entries = vec![Entry {
+            node: root(jsxn),
+            edge: Edge::Root,
+        }];
+        for step in path {
+            match step_into(entries[entries.len() - 1].node, step) {
+                Some(entry) => entries.push(entry),
+                None => return false,
+            }
+        }
+        self.matches_entries(&entries)
+    }
+
+    fn walk<'a>(&self, jsxn: &'a JsxnValue, found: &mut dyn FnMut(&[Entry<'a>])) {
+        let mut entries = vec![Entry {
+            node: root(jsxn),
+            edge: Edge::Root,
+        }];
+        self.walk_entries(&mut entries, found);
+    }
+
+    fn walk_entries<'a>(&self, entries: &mut Vec<Entry<'a>>, found: &mut dyn FnMut(&[Entry<'a>])) {
+        if self.matches_entries(entries) {
+            found(entries);
+        }
+        for child in children(entries[entries.len() - 1].node) {
+            entries.push(child);
+            self.walk_entries(entries, found);
+            entries.pop();
+        }
+    }
+
+    fn matches_entries(&self, entries: &[Entry<'_>]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|complex| matches_compounds(&complex.compounds, entries))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(selector: &str) -> Result<Selector, SelectorError> {
+        Selector::parse(selector)
+    }
+}
+
+/// Parse a selector and return the nodes of a tree matching it
+pub fn select<'a>(jsxn: &'a JsxnValue, selector: &str) -> Result<Vec<Node<'a>>, SelectorError> {
+    Ok(Selector::parse(selector)?.select(jsxn))
+}
+
+/// The node at the end of a path, if the path leads to a node of the tree
+pub fn get<'a>(jsxn: &'a JsxnValue, path: &[Step]) -> Option<Node<'a>> {
+    path.iter().try_fold(root(jsxn), |node, step| {
+        step_into(node, step).map(|entry| entry.node)
+    })
+}
+
+/// A node, with how it was reached from its parent
+#[derive(Clone, Copy)]
+struct Entry<'a> {
+    node: Node<'a>,
+    edge: Edge<'a>,
+}
+
+#[derive(Clone, Copy)]
+enum Edge<'a> {
+    Root,
+    Prop(&'a str),
+    Child(usize),
+    Expression,
+    Key(&'a str),
+    Item(usize),
+}
+
+fn root(jsxn: &JsxnValue) -> Node<'_> {
+    match jsxn {
+        JsxnValue::JsxValue(jsx) => jsx_node(jsx, false),
+        JsxnValue::JsonValue(json) => json_node(json),
+    }
+}
+
+fn jsx_node(jsx: &JsxValue, child: bool) -> Node<'_> {
+    match jsx {
+        JsxValue::JsxElement(element) => Node::Element(element),
+        JsxValue::JsxFragment(fragment) => Node::Fragment(fragment),
+        JsxValue::JsonValue(JsonValue::Str(text)) if child => Node::Text(text),
+        JsxValue::JsonValue(json) => json_node(json),
+        JsxValue::JsxExpression(jsx) => Node::Expression(jsx),
+        JsxValue::Error(span) => Node::Error(span),
+        JsxValue::Variable(path) => Node::Variable(path),
+    }
+}
+
+fn json_node(json: &JsonValue) -> Node<'_> {
+    match json {
+        JsonValue::JsxValue(jsx) => jsx_node(jsx, false),
+        json => Node::Json(json),
+    }
+}
+
+fn children(node: Node<'_>) -> Vec<Entry<'_>> {
+    match node {
+        Node::Element(element) => element
+            .props()
+            .iter()
+            .map(|(name, value)| Entry {
+                node: jsx_node(value, false),
+                edge: Edge::Prop(name),
+            })
+            .chain(child_entries(element.children()))
+            .collect(),
+        Node::Fragment(fragment) => child_entries(fragment.children()).collect(),
+        Node::Expression(jsx) => vec![Entry {
+            node: jsx_node(jsx, false),
+            edge: Edge::Expression,
+        }],
+        Node::Json(JsonValue::Array(array)) => array
+            .iter()
+            .enumerate()
+            .map(|(index, item)| Entry {
+                node: json_node(item),
+                edge: Edge::Item(index),
+            })
+            .collect(),
+        Node::Json(JsonValue::Object(object)) => object
+            .iter()
+            .map(|(key, value)| Entry {
+                node: json_node(value),
+                edge: Edge::Key(key),
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn child_entries(children: &[JsxValue]) -> impl Iterator<Item = Entry<'_>> {
+    children.iter().enumerate().map(|(index, child)| Entry {
+        node: jsx_node(child, true),
+        edge: Edge::Child(index),
+    })
+}
+
+fn step_into<'a>(node: Node<'a>, step: &Step) -> Option<Entry<'a>> {
+    Some(match (node, step) {
+        (Node::Element(element), Step::Prop(name)) => {
+            let (name, value) = element.props().get_key_value(name)?;
+            Entry {
+                node: jsx_node(value, false),
+                edge: Edge::Prop(name),
+            }
+        }
+        (Node::Element(element), Step::Child(index)) => Entry {
+            node: jsx_node(element.children().get(*index)?, true),
+            edge: Edge::Child(*index),
+        },
+        (Node::Fragment(fragment), Step::Child(index)) => Entry {
+            node: jsx_node(fragment.children().get(*index)?, true),
+            edge: Edge::Child(*index),
+        },
+        (Node::Expression(jsx), Step::Expression) => Entry {
+            node: jsx_node(jsx, false),
+            edge: Edge::Expression,
+        },
+        (Node::Json(JsonValue::Object(object)), Step::Key(key)) => {
+            let (key, value) = object.get_key_value(key)?;
+            Entry {
+                node: json_node(value),
+                edge: Edge::Key(key),
+            }
+        }
+        (Node::Json(JsonValue::Array(array)), Step::Item(index)) => Entry {
+            node: json_node(array.get(*index)?),
+            edge: Edge::Item(*index),
+        },
+        _ => return None,
+    })
+}
+
+/// Whether the last entry matches the last compound, and its ancestors the
+/// compounds before it. The compounds are matched from the left, keeping for
+/// each entry whether it matches the compounds so far, and whether it or one
+/// of its ancestors does, so that every entry is matched once per compound.
+fn matches_compounds(compounds: &[(Combinator, Compound)], entries: &[Entry<'_>]) -> bool {
+    let (_, compound) = match compounds.last() {
+        Some(last) => last,
+        None => return true,
+    };
+    match entries.last() {
+        Some(entry) if compound.matches(entry) => {}
+        _ => return false,
+    }
+
+    let mut matched: Vec<bool> = vec![];
+    let mut within: Vec<bool> = vec![];
+    for (index, (combinator, compound)) in compounds.iter().enumerate() {
+        matched = entries
+            .iter()
+            .enumerate()
+            .map(|(depth, entry)| {
+                let related = match (index, depth.checked_sub(1)) {
+                    (0, _) => true,
+                    (_, None) => false,
+                    (_, Some(parent)) => match combinator {
+                        Combinator::Child => matched[parent],
+                        Combinator::Descendant => within[parent],
+                    },
+                };
+                related && compound.matches(entry)
+            })
+            .collect();
+        within = matched
+            .iter()
+            .scan(false, |any, &matched| {
+                *any |= matched;
+                Some(*any)
+            })
+            .collect();
+    }
+    matched.last() == Some(&true)
+}
+
+impl Compound {
+    fn matches(&self, entry: &Entry<'_>) -> bool {
+        if let Some(r#type) = &self.r#type {
+            match entry.node {
+                Node::Element(element) if element.r#type() == r#type => {}
+                _ => return false,
+            }
+        }
+        self.filters.iter().all(|filter| filter.matches(entry))
+    }
+}
+
+impl Filter {
+    fn matches(&self, entry: &Entry<'_>) -> bool {
+        match self {
+            Filter::Has(name) => lookup(entry.node, name).is_some(),
+            Filter::Equals(name, value) => {
+                lookup(entry.node, name).is_some_and(|node| equals(node, value))
+            }
+            Filter::NthChild(a, b) => {
+                let position = match entry.edge {
+                    Edge::Child(index) | Edge::Item(index) => index as i64 + 1,
+                    _ => return false,
+                };
+                match a {
+                    0 => position == *b,
+                    // Positions the arithmetic overflows for are not matched
+                    a => match position.checked_sub(*b) {
+                        Some(offset) => {
+                            offset.checked_rem(*a) == Some(0)
+                                && matches!(offset.checked_div(*a), Some(n) if n >= 0)
+                        }
+                        None => false,
+                    },
+                }
+            }
+            Filter::Key(name) => match entry.edge {
+                Edge::Key(key) => json::unescape(key) == *name,
+                _ => false,
+            },
+            Filter::Kind(kind) => matches!(
+                (kind, entry.node),
+                (Kind::Element, Node::Element(_))
+                    | (Kind::Fragment, Node::Fragment(_))
+                    | (Kind::Text, Node::Text(_))
+                    | (Kind::Expression, Node::Expression(_))
+                    | (Kind::Variable, Node::Variable(_))
+                    | (Kind::Object, Node::Json(JsonValue::Object(_)))
+                    | (Kind::Array, Node::Json(JsonValue::Array(_)))
+                    | (Kind::String, Node::Json(JsonValue::Str(_)))
+                    | (Kind::Number, Node::Json(JsonValue::Num(_)))
+                    | (Kind::Boolean, Node::Json(JsonValue::Boolean(_)))
+                    | (Kind::Null, Node::Json(JsonValue::Null))
+            ),
+        }
+    }
+}
+
+/// The value of a prop of an element, or of an entry of an object
+fn lookup<'a>(node: Node<'a>, name: &str) -> Option<Node<'a>> {
+    match node {
+        Node::Element(element) => element
+            .props()
+            .get(name)
+            .map(|value| jsx_node(value, false)),
+        Node::Json(JsonValue::Object(object)) => object
+            .iter()
+            .find(|(key, _)| json::unescape(key) == name)
+            .map(|(_, value)| json_node(value)),
+        _ => None,
+    }
+}
+
+fn equals(node: Node<'_>, value: &str) -> bool {
+    match node {
+        Node::Expression(jsx) => equals(jsx_node(jsx, false), value),
+        Node::Json(JsonValue::Str(string)) => json::unescape(string) == value,
+        Node::Json(JsonValue::Num(number)) => value.parse::<f64>() == Ok(*number),
+        Node::Json(JsonValue::Boolean(boolean)) => value == if *boolean { "true" } else { "false" },
+        _ => false,
+    }
+}
+
+struct SelectorParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> SelectorParser<'a> {
+    fn selector(mut self) -> Result<Selector, SelectorError> {
+        let mut alternatives = vec![];
+        loop {
+            self.skip_whitespace();
+            alternatives.push(self.complex()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                None => return Ok(Selector { alternatives }),
+                Some(_) => return Err(self.error("expected `,` or the end of the selector")),
+            }
+        }
+    }
+
+    fn complex(&mut self) -> Result<Complex, SelectorError> {
+        let mut compounds = vec![(Combinator::Descendant, self.compound()?)];
+        loop {
+            let start = self.pos;
+            self.skip_whitespace();
+            let combinator = match self.peek() {
+                Some('>') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(',') | None => {
+                    self.pos = start;
+                    return Ok(Complex { compounds });
+                }
+                Some(_) if self.pos > start => Combinator::Descendant,
+                Some(_) => return Ok(Complex { compounds }),
+            };
+            compounds.push((combinator, self.compound()?));
+        }
+    }
+
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let universal = self.eat('*');
+        let mut compound = Compound {
+            r#type: if universal { None } else { self.identifier() },
+            filters: vec![],
+        };
+        loop {
+            if self.eat('[') {
+                compound.filters.push(self.attribute()?);
+            } else if self.eat(':') {
+                compound.filters.push(self.pseudo_class()?);
+            } else {
+                break;
+            }
+        }
+        if !universal && compound.r#type.is_none() && compound.filters.is_empty() {
+            return Err(self.error("expected a selector"));
+        }
+        Ok(compound)
+    }
+
+    fn attribute(&mut self) -> Result<Filter, SelectorError> {
+        self.skip_whitespace();
+        let name = self.name()?;
+        self.skip_whitespace();
+        let filter = if self.eat('=') {
+            self.skip_whitespace();
+            let value = self.name()?;
+            self.skip_whitespace();
+            Filter::Equals(name, value)
+        } else {
+            Filter::Has(name)
+        };
+        self.expect(']')?;
+        Ok(filter)
+    }
+
+    fn pseudo_class(&mut self) -> Result<Filter, SelectorError> {
+        let start = self.pos;
+        let name = self
+            .identifier()
+            .ok_or_else(|| self.error("expected a pseudo-class"))?;
+        if let Some((_, kind)) = KINDS.iter().find(|(kind, _)| *kind == name) {
+            return Ok(Filter::Kind(*kind));
+        }
+        let filter = match name.as_str() {
+            "nth-child" => {
+                self.expect('(')?;
+                let argument_start = self.pos;
+                let end = self.input[self.pos..]
+                    .find(')')
+                    .map_or(self.input.len(), |end| self.pos + end);
+                let (a, b) = nth(&self.input[self.pos..end]).ok_or(SelectorError {
+                    offset: argument_start,
+                    message: String::from("invalid `:nth-child` argument"),
+                })?;
+                self.pos = end;
+                Filter::NthChild(a, b)
+            }
+            "key" => {
+                self.expect('(')?;
+                self.skip_whitespace();
+                let key = self.name()?;
+                self.skip_whitespace();
+                Filter::Key(key)
+            }
+            _ => {
+                return Err(SelectorError {
+                    offset: start,
+                    message: format!("unknown pseudo-class `:{}`", name),
+                })
+            }
+        };
+        self.expect(')')?;
+        Ok(filter)
+    }
+
+    /// An identifier or a quoted string
+    fn name(&mut self) -> Result<String, SelectorError> {
+        match self.peek() {
+            Some(quote @ '"') | Some(quote @ '\'') => {
+                let start = self.pos;
+                self.pos += 1;
+                let mut string = String::new();
+                let mut chars = self.input[self.pos..].chars();
+                while let Some(c) = chars.next() {
+                    self.pos += c.len_utf8();
+                    match c {
+                        c if c == quote => return Ok(string),
+                        '\\' => match chars.next() {
+                            Some(c) => {
+                                self.pos += c.len_utf8();
+                                string.push(c);
+                            }
+                            None => break,
+                        },
+                        c => string.push(c),
+                    }
+                }
+                Err(SelectorError {
+                    offset: start,
+                    message: String::from("unterminated string"),
+                })
+            }
+            _ => self
+                .identifier()
+                .ok_or_else(|| self.error("expected a name or a string")),
+        }
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        self.pos += len;
+        Some(String::from(&rest[..len]))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SelectorError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c)))
+        }
+    }
+
+    fn error(&self, message: &str) -> SelectorError {
+        SelectorError {
+            offset: self.pos,
+            message: String::from(message),
+        }
+    }
+}
+
+/// Parse the `An+B` argument of `:nth-child`
+fn nth(argument: &str) -> Option<(i64, i64)> {
+    let argument: String = argument
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match argument.as_str() {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    }
+    let (a, b) = match argument.find('n') {
+        Some(n) => (&argument[..n], &argument[n + 1..]),
+        None => return integer(&argument).map(|b| (0, b)),
+    };
+    let a = match a {
+        "" | "+" => 1,
+        "-" => -1,
+        a => integer(a)?,
+    };
+    let b = match b {
+        "" => 0,
+        b if b.starts_with(['+', '-']) => integer(b)?,
+        _ => return None,
+    };
+    Some((a, b))
+}
+
+fn integer(string: &str) -> Option<i64> {
+    let digits = string.strip_prefix(['+', '-']).unwrap_or(string);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    string.parse().ok()
+}
@@ -0,0 +1,552 @@
+use crate::{
+    json::{escape, unescape, JsonValue},
+    jsx::{jsx_prop_name, JsxElement, JsxFragment, JsxValue},
+    jsxn::JsxnValue,
+};
+use serde::{ser, Serialize};
+use std::{collections::BTreeMap, error, fmt};
+
+/// The name of the newtype struct that `Serializer` turns into a JSX
+/// Element. Its value is serialized as a `(type, props, children)` tuple.
+pub const ELEMENT: &str = "$jsxn::Element";
+
+/// The name of the newtype struct that `Serializer` turns into a JSX
+/// Fragment. Its value is serialized as the children.
+pub const FRAGMENT: &str = "$jsxn::Fragment";
+
+/// A JSX Element to serialize from Rust values. `to_value` turns it into a
+/// `JsxElement`, while other serializers see a `[type, props, children]`
+/// array.
+///
+/// The props serialize to an object, or a unit to have none, and props that
+/// serialize to `null`, such as options that are `None`, are left out. The
+/// children serialize to a sequence, a single child, or a unit to have none.
+/// Strings become text, and other values expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element<P, C> {
+    /// The element type
+    pub r#type: String,
+
+    /// The element props
+    pub props: P,
+
+    /// The element children
+    pub children: C,
+}
+
+impl<P, C> Element<P, C> {
+    /// Create a new element to serialize
+    pub fn new<T: Into<String>>(r#type: T, props: P, children: C) -> Element<P, C> {
+        Element {
+            r#type: r#type.into(),
+            props,
+            children,
+        }
+    }
+}
+
+impl<P: Serialize, C: Serialize> Serialize for Element<P, C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(ELEMENT, &(&self.r#type, &self.props, &self.children))
+    }
+}
+
+/// A JSX Fragment to serialize from Rust values, holding children like
+/// `Element` does
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment<C>(pub C);
+
+impl<C: Serialize> Serialize for Fragment<C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(FRAGMENT, &self.0)
+    }
+}
+
+/// An error returned when serializing into a tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializeError {
+    message: String,
+}
+
+impl SerializeError {
+    fn new<T: Into<String>>(message: T) -> SerializeError {
+        SerializeError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for SerializeError {}
+
+impl ser::Error for SerializeError {
+    fn custom<T: fmt::Display>(message: T) -> SerializeError {
+        SerializeError::new(message.to_string())
+    }
+}
+
+/// Serialize a Rust value into a JSON value. Strings are escaped the way
+/// they are kept in the trees, and `Element` and `Fragment` become JSX
+/// values held by the JSON.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<JsonValue, SerializeError> {
+    value.serialize(Serializer)
+}
+
+/// Serialize a Rust value into a JSXN value, which is a JSX value if the
+/// Rust value serializes to an element or a fragment, so that it can be
+/// formatted or rendered like a parsed tree
+pub fn to_jsxn_value<T: Serialize + ?Sized>(value: &T) -> Result<JsxnValue, SerializeError> {
+    Ok(match to_value(value)? {
+        JsonValue::JsxValue(jsx) => JsxnValue::JsxValue(*jsx),
+        json => JsxnValue::JsonValue(json),
+    })
+}
+
+/// A serializer whose output is a JSON value
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = JsonValue;
+    type Error = SerializeError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeVariant<SerializeArray>;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeVariant<SerializeObject>;
+
+    fn serialize_bool(self, v: bool) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(f64::from(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(f64::from(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(f64::from(v)))
+    }
+
+    /// Integers are numbers like those parsed, so those beyond 2^53 lose
+    /// precision
+    fn serialize_i64(self, v: i64) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(v as f64))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(v as f64))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(f64::from(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(f64::from(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(f64::from(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(v as f64))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(v as f64))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(f64::from(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Num(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<JsonValue, SerializeError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Str(escape(v)))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Array(
+            v.iter()
+                .map(|byte| JsonValue::Num(f64::from(*byte)))
+                .collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<JsonValue, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<JsonValue, SerializeError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, SerializeError> {
+        let value = value.serialize(self)?;
+        let jsx = match name {
+            ELEMENT => element(value)?,
+            FRAGMENT => JsxValue::JsxFragment(JsxFragment::new(children(value))),
+            _ => return Ok(value),
+        };
+        Ok(JsonValue::JsxValue(Box::new(jsx)))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, SerializeError> {
+        Ok(self::variant(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerializeError> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerializeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerializeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeArray>, SerializeError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, SerializeError> {
+        Ok(SerializeObject {
+            entries: BTreeMap::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeObject, SerializeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeObject>, SerializeError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+/// An enum variant as an object with the variant as its only key
+fn variant(variant: &str, value: JsonValue) -> JsonValue {
+    let mut object = BTreeMap::new();
+    object.insert(escape(variant), value);
+    JsonValue::Object(object)
+}
+
+/// Build an element from its serialized `(type, props, children)`
+fn element(value: JsonValue) -> Result<JsxValue, SerializeError> {
+    let (r#type, props, children) = match value {
+        JsonValue::Array(parts) if parts.len() == 3 => {
+            let mut parts = parts.into_iter();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(r#type), Some(props), Some(children)) => (r#type, props, children),
+                _ => unreachable!(),
+            }
+        }
+        _ => {
+            return Err(SerializeError::new(
+                "an element must be a (type, props, children) tuple",
+            ))
+        }
+    };
+    let r#type = match r#type {
+        JsonValue::Str(r#type)
+            if !r#type.is_empty() && r#type.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            r#type
+        }
+        _ => {
+            return Err(SerializeError::new(
+                "element types must be alphanumeric strings",
+            ))
+        }
+    };
+    let props = match props {
+        JsonValue::Object(props) => props
+            .into_iter()
+            .filter(|(_, value)| *value != JsonValue::Null)
+            .map(|(name, value)| {
+                let valid = name.is_ascii()
+                    && matches!(jsx_prop_name::<()>(&name), Ok((rest, _)) if rest.is_empty());
+                if !valid {
+                    return Err(SerializeError::new(format!(
+                        "prop name `{}` is not alphanumeric with hyphens",
+                        unescape(&name)
+                    )));
+                }
+                Ok((name, prop(value)))
+            })
+            .collect::<Result<_, _>>()?,
+        JsonValue::Null => BTreeMap::new(),
+        _ => return Err(SerializeError::new("element props must be an object")),
+    };
+    Ok(JsxValue::JsxElement(JsxElement::new(
+        r#type,
+        props,
+        self::children(children),
+    )))
+}
+
+fn prop(value: JsonValue) -> JsxValue {
+    match value {
+        JsonValue::JsxValue(jsx) => *jsx,
+        // Strings and bare props are written as such
+        value @ JsonValue::Str(_) | value @ JsonValue::Boolean(true) => JsxValue::JsonValue(value),
+        value => JsxValue::JsxExpression(Box::new(JsxValue::JsonValue(value))),
+    }
+}
+
+fn children(value: JsonValue) -> Vec<JsxValue> {
+    match value {
+        JsonValue::Array(children) => children.into_iter().filter_map(child).collect(),
+        child => self::child(child).into_iter().collect(),
+    }
+}
+
+fn child(value: JsonValue) -> Option<JsxValue> {
+    Some(match value {
+        JsonValue::Null => return None,
+        JsonValue::JsxValue(jsx) => *jsx,
+        JsonValue::Str(s) => {
+            // JSX text is kept as written, and cannot hold some characters or
+            // start or end with whitespace
+            let text = unescape(&s);
+            let plain = !text.is_empty()
+                && !text.starts_with(char::is_whitespace)
+                && !text.ends_with(char::is_whitespace)
+                && !text.contains(['{', '}', '<', '>', '\n']);
+            if plain {
+                JsxValue::JsonValue(JsonValue::Str(text))
+            } else {
+                JsxValue::JsxExpression(Box::new(JsxValue::JsonValue(JsonValue::Str(s))))
+            }
+        }
+        value => JsxValue::JsxExpression(Box::new(JsxValue::JsonValue(value))),
+    })
+}
+
+/// Serializes sequences and tuples into a JSON array
+pub struct SerializeArray {
+    items: Vec<JsonValue>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = JsonValue;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Array(self.items))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = JsonValue;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerializeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = JsonValue;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerializeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Serializes maps and structs into a JSON object
+pub struct SerializeObject {
+    entries: BTreeMap<String, JsonValue>,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = JsonValue;
+    type Error = SerializeError;
+
+    /// Keys must serialize to strings, numbers or booleans, which are
+    /// converted to strings
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerializeError> {
+        self.key = Some(match key.serialize(Serializer)? {
+            JsonValue::Str(key) => key,
+            JsonValue::Num(num) => num.to_string(),
+            JsonValue::Boolean(boolean) => boolean.to_string(),
+            _ => return Err(SerializeError::new("object keys must be strings")),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerializeError::new("object value serialized before its key"))?;
+        self.entries.insert(key, value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerializeError> {
+        Ok(JsonValue::Object(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = JsonValue;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        self.entries
+            .insert(escape(key), value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerializeError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Serializes enum variants into a JSON object with the variant as its only
+/// key
+pub struct SerializeVariant<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeArray> {
+    type Ok = JsonValue;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerializeError> {
+        let value = ser::SerializeSeq::end(self.inner)?;
+        Ok(variant(self.variant, value))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeObject> {
+    type Ok = JsonValue;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerializeError> {
+        let value = ser::SerializeMap::end(self.inner)?;
+        Ok(variant(self.variant, value))
+    }
+}
@@ -1,6 +1,218 @@
-use nom::{bytes::complete::take_while, error::ParseError, IResult};
+use crate::options::ParserOptions;
+use nom::{
+    bytes::complete::take_while,
+    character::complete::char,
+    combinator::opt,
+    error::{ErrorKind, ParseError},
+    multi::separated_list,
+    sequence::preceded,
+    Err, IResult,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeSet,
+};
 
 pub fn sp<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
     let chars = " \t\r\n";
     take_while(move |c| chars.contains(c))(i)
 }
+
+/// Run `f` only if `enabled`, otherwise fail as if it did not match
+pub fn enabled<'a, O, E, F>(enabled: bool, f: F) -> impl Fn(&'a str) -> IResult<&'a str, O, E>
+where
+    E: ParseError<&'a str>,
+    F: Fn(&'a str) -> IResult<&'a str, O, E>,
+{
+    move |i| {
+        if enabled {
+            f(i)
+        } else {
+            Err(Err::Error(E::from_error_kind(i, ErrorKind::Alt)))
+        }
+    }
+}
+
+/// A limit from `ParseLimits` that was exceeded during parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    TooManyNodes,
+    StringTooLong,
+    TooManyProps,
+    TooManyChildren,
+    TooDeep,
+}
+
+/// State threaded through every parser
+pub struct State<'a> {
+    pub options: &'a ParserOptions,
+
+    /// Nodes are identified by the length of the input remaining at their
+    /// start, so that values parsed more than once while backtracking are
+    /// only counted once
+    nodes: RefCell<BTreeSet<usize>>,
+
+    /// The number of values enclosing the input being parsed
+    depth: Cell<usize>,
+
+    violation: Cell<Option<(Violation, &'a str)>>,
+}
+
+impl<'a> State<'a> {
+    pub fn new(options: &'a ParserOptions) -> State<'a> {
+        State {
+            options,
+            nodes: RefCell::new(BTreeSet::new()),
+            depth: Cell::new(0),
+            violation: Cell::new(None),
+        }
+    }
+
+    /// The limit that stopped the parse, and where it was exceeded
+    pub fn violation(&self) -> Option<(Violation, &'a str)> {
+        self.violation.get()
+    }
+
+    fn fail<E: ParseError<&'a str>>(&self, i: &'a str, violation: Violation) -> Err<E> {
+        self.violation.set(Some((violation, i)));
+        Err::Failure(E::from_error_kind(i, ErrorKind::TooLarge))
+    }
+
+    fn check<E: ParseError<&'a str>>(
+        &self,
+        i: &'a str,
+        limit: Option<usize>,
+        count: usize,
+        violation: Violation,
+    ) -> Result<(), Err<E>> {
+        match limit {
+            Some(limit) if count > limit => Err(self.fail(i, violation)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whitespace, and comments if they are enabled
+    pub fn ws<E: ParseError<&'a str>>(&self, i: &'a str) -> IResult<&'a str, &'a str, E> {
+        if !self.options.comments {
+            return sp(i);
+        }
+        let (mut input_remainder, _) = sp(i)?;
+        loop {
+            if input_remainder.starts_with("//") {
+                input_remainder = match input_remainder.find('\n') {
+                    Some(end) => &input_remainder[end..],
+                    None => "",
+                };
+            } else if input_remainder.starts_with("/*") {
+                input_remainder = match input_remainder[2..].find("*/") {
+                    Some(end) => &input_remainder[end + 4..],
+                    None => {
+                        return Err(Err::Failure(E::add_context(
+                            input_remainder,
+                            "comment",
+                            E::from_error_kind(input_remainder, ErrorKind::TakeUntil),
+                        )))
+                    }
+                };
+            } else {
+                break;
+            }
+            input_remainder = sp(input_remainder)?.0;
+        }
+        Ok((input_remainder, &i[..i.len() - input_remainder.len()]))
+    }
+
+    /// A comma separated list of `f`, optionally followed by a trailing comma
+    /// if they are enabled
+    pub fn list<O, E, F>(&self, i: &'a str, f: F) -> IResult<&'a str, Vec<O>, E>
+    where
+        E: ParseError<&'a str>,
+        F: Fn(&'a str) -> IResult<&'a str, O, E>,
+    {
+        let (input_remainder, list) = separated_list(preceded(|i| self.ws(i), char(',')), f)(i)?;
+        if !self.options.trailing_commas || list.is_empty() {
+            return Ok((input_remainder, list));
+        }
+        let (input_remainder, _) = opt(preceded(|i| self.ws(i), char(',')))(input_remainder)?;
+        Ok((input_remainder, list))
+    }
+
+    /// Run `f` and count its output as a node of the tree
+    pub fn node<O, E, F>(&self, i: &'a str, f: F) -> IResult<&'a str, O, E>
+    where
+        E: ParseError<&'a str>,
+        F: Fn(&'a str) -> IResult<&'a str, O, E>,
+    {
+        let (input_remainder, output) = f(i)?;
+        if self.options.limits.max_nodes.is_some() {
+            let count = {
+                let mut nodes = self.nodes.borrow_mut();
+                nodes.insert(i.len());
+                nodes.len()
+            };
+            self.check(
+                i,
+                self.options.limits.max_nodes,
+                count,
+                Violation::TooManyNodes,
+            )?;
+        }
+        Ok((input_remainder, output))
+    }
+
+    /// Run `f` for a value nested inside the values being parsed, failing
+    /// before it runs if that exceeds the depth limit
+    pub fn nested<O, E, F>(&self, i: &'a str, f: F) -> Result<O, Err<E>>
+    where
+        E: ParseError<&'a str>,
+        F: FnOnce() -> Result<O, Err<E>>,
+    {
+        let depth = self.depth.get() + 1;
+        self.check(i, self.options.limits.max_depth, depth, Violation::TooDeep)?;
+        self.depth.set(depth);
+        let result = f();
+        self.depth.set(depth - 1);
+        result
+    }
+
+    /// Check the length in bytes of the string starting at `i`, as it is
+    /// being read
+    pub fn check_string<E: ParseError<&'a str>>(
+        &self,
+        i: &'a str,
+        length: usize,
+    ) -> Result<(), Err<E>> {
+        self.check(
+            i,
+            self.options.limits.max_string_length,
+            length,
+            Violation::StringTooLong,
+        )
+    }
+
+    pub fn check_props<E: ParseError<&'a str>>(
+        &self,
+        i: &'a str,
+        count: usize,
+    ) -> Result<(), Err<E>> {
+        self.check(
+            i,
+            self.options.limits.max_props,
+            count,
+            Violation::TooManyProps,
+        )
+    }
+
+    pub fn check_children<E: ParseError<&'a str>>(
+        &self,
+        i: &'a str,
+        count: usize,
+    ) -> Result<(), Err<E>> {
+        self.check(
+            i,
+            self.options.limits.max_children,
+            count,
+            Violation::TooManyChildren,
+        )
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{
+    error::{Error, Location},
+    events::Parser,
+    jsx::JsxValue,
+    jsxn::JsxnValue,
+    options::ParserOptions,
+    shared::State,
+};
+use nom::error::VerboseError;
+use std::{
+    cmp,
+    collections::BTreeMap,
+    io::{self, Read},
+    str,
+};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// The root of a streamed document, which determines the items it is split
+/// into
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamRoot {
+    /// A JSON array, whose values are the items
+    Array,
+
+    /// A JSX Element, whose children are the items
+    Element {
+        /// The element type
+        r#type: String,
+
+        /// The element props
+        props: BTreeMap<String, JsxValue>,
+    },
+
+    /// A JSX Fragment, whose children are the items
+    Fragment,
+
+    /// Any other root, which is the only item
+    Document,
+}
+
+enum Phase {
+    Start,
+    Items,
+    End,
+    Done,
+}
+
+/// A parser that reads a JSXN document incrementally and yields the values
+/// of a root array, or the children of a root element or fragment, one at a
+/// time. Only one item is held in memory at once.
+///
+/// Limits other than `ParseLimits::max_input_bytes` and
+/// `ParseLimits::max_children` apply to each item separately.
+pub struct Stream<R> {
+    reader: R,
+    options: ParserOptions,
+
+    /// Input that has been read but not yet parsed
+    buffer: String,
+
+    /// Bytes of an incomplete UTF-8 sequence at the end of the input read
+    undecoded: Vec<u8>,
+
+    /// Location of the start of `buffer`
+    location: Location,
+
+    bytes_read: usize,
+    eof: bool,
+    root: Option<StreamRoot>,
+    items: usize,
+    phase: Phase,
+}
+
+impl<R: Read> Stream<R> {
+    /// Create a new streaming parser reading from `reader`
+    pub fn new(reader: R) -> Stream<R> {
+        Stream::with_options(reader, ParserOptions::STRICT)
+    }
+
+    /// Create a new streaming parser reading from `reader` with the given
+    /// options
+    pub fn with_options(reader: R, options: ParserOptions) -> Stream<R> {
+        Stream {
+            reader,
+            options,
+            buffer: String::new(),
+            undecoded: vec![],
+            location: Location::new("", 0),
+            bytes_read: 0,
+            eof: false,
+            root: None,
+            items: 0,
+            phase: Phase::Start,
+        }
+    }
+
+    /// The root of the document, once the first item has been requested
+    pub fn root(&self) -> Option<&StreamRoot> {
+        self.root.as_ref()
+    }
+
+    /// Run `f` over the buffered input, reading more input until `f`
+    /// succeeds before the last token of the buffer, fails at input that
+    /// more input cannot complete, or the whole input has been read. `f`
+    /// returns its output and the number of bytes it consumed.
+    fn parse<O, F>(&mut self, f: F) -> Result<O, Error>
+    where
+        F: Fn(&str, ParserOptions) -> Result<(O, usize), Error>,
+    {
+        loop {
+            match f(&self.buffer, self.options) {
+                Ok((output, consumed)) if self.eof || !extendable(&self.buffer, consumed) => {
+                    self.location =
+                        Location::new(&self.buffer, consumed).relative_to(self.location);
+                    self.buffer.drain(..consumed);
+                    return Ok(output);
+                }
+                Ok(_) => {}
+                Err(Error::Syntax { location, .. })
+                    if !self.eof && unfinished(&self.buffer[location.offset..]) => {}
+                Err(e) => return Err(e.relative_to(self.location)),
+            }
+            self.fill()?;
+        }
+    }
+
+    /// Read more input into the buffer, reading at least as much as is
+    /// already buffered so that reparsing a large item stays linear
+    fn fill(&mut self) -> Result<(), Error> {
+        let start = self.undecoded.len();
+        self.undecoded
+            .resize(start + cmp::max(CHUNK_SIZE, self.buffer.len()), 0);
+        let read = loop {
+            match self.reader.read(&mut self.undecoded[start..]) {
+                Ok(read) => break read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.undecoded.truncate(start);
+                    return Err(Error::Io {
+                        location: self.end_location(),
+                        kind: e.kind(),
+                        message: e.to_string(),
+                    });
+                }
+            }
+        };
+        self.undecoded.truncate(start + read);
+        self.bytes_read += read;
+        self.eof = read == 0;
+
+        let valid = match str::from_utf8(&self.undecoded) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() && !self.eof => e.valid_up_to(),
+            Err(e) => {
+                self.undecoded.truncate(e.valid_up_to());
+                self.decode(e.valid_up_to());
+                return Err(Error::Syntax {
+                    location: self.end_location(),
+                    message: String::from("invalid UTF-8"),
+                });
+            }
+        };
+        self.decode(valid);
+        if self.location.offset == 0 && self.buffer.starts_with('\u{feff}') {
+            self.buffer.drain(..'\u{feff}'.len_utf8());
+            self.location = Location {
+                offset: '\u{feff}'.len_utf8(),
+                ..self.location
+            };
+        }
+
+        match self.options.limits.max_input_bytes {
+            Some(limit) if self.bytes_read > limit => Err(Error::InputTooLarge {
+                location: Location::new(&self.buffer, limit.saturating_sub(self.location.offset))
+                    .relative_to(self.location),
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move the first `valid` bytes of `undecoded` into the buffer
+    fn decode(&mut self, valid: usize) {
+        if let Ok(text) = str::from_utf8(&self.undecoded[..valid]) {
+            self.buffer.push_str(text);
+        }
+        self.undecoded.drain(..valid);
+    }
+
+    fn end_location(&self) -> Location {
+        Location::new(&self.buffer, self.buffer.len()).relative_to(self.location)
+    }
+
+    fn start(&mut self) -> Result<(), Error> {
+        let root = self.parse(|i, options| {
+            let mut parser = Parser::with_options(i, options);
+            match parser.root_opening()? {
+                Some(root) => Ok((Some(root), parser.consumed())),
+                None => Ok((None, 0)),
+            }
+        });
+        match root {
+            Ok(Some((root, has_items))) => {
+                self.root = Some(root);
+                self.phase = if has_items { Phase::Items } else { Phase::End };
+            }
+            Ok(None) | Err(Error::Syntax { .. }) => self.root = Some(StreamRoot::Document),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    fn item(&mut self) -> Result<Option<JsxnValue>, Error> {
+        let items = self.items;
+        let item = match self.root.clone() {
+            Some(StreamRoot::Document) | None => {
+                if items > 0 {
+                    return Ok(None);
+                }
+                let document = self
+                    .parse(|i, options| Ok((Parser::with_options(i, options).build()?, i.len())))?;
+                Some(document)
+            }
+            Some(root) => self.parse(|i, options| {
+                let mut parser = Parser::inside(i, options, &root, items);
+                let item = parser.item()?;
+                Ok((item, parser.consumed()))
+            })?,
+        };
+        if item.is_some() {
+            self.items += 1;
+        }
+        Ok(item)
+    }
+
+    fn end(&mut self) -> Result<(), Error> {
+        self.parse(|i, options| {
+            let state = State::new(&options);
+            match state.ws::<VerboseError<&str>>(i) {
+                Ok((input_remainder, _)) => Ok(((), i.len() - input_remainder.len())),
+                Err(e) => Err(Error::from_parse(i, &state, e)),
+            }
+        })?;
+        if self.buffer.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::trailing_input(&self.buffer, &self.buffer).relative_to(self.location))
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<JsxnValue>, Error> {
+        loop {
+            match self.phase {
+                Phase::Start => {
+                    self.start()?;
+                    if let Phase::Start = self.phase {
+                        self.phase = Phase::Items;
+                    }
+                }
+                Phase::Items => match self.item()? {
+                    Some(item) => return Ok(Some(item)),
+                    None => self.phase = Phase::End,
+                },
+                Phase::End => {
+                    self.end()?;
+                    self.phase = Phase::Done;
+                }
+                Phase::Done => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for Stream<R> {
+    type Item = Result<JsxnValue, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step() {
+            Ok(item) => item.map(Ok),
+            Err(e) => {
+                self.phase = Phase::Done;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Whether more input could extend what was parsed from the first
+/// `consumed` bytes of the input, because only whitespace follows it, or
+/// because it ends with a number that the rest continues, as `.5` does
+/// after `12`
+fn extendable(input: &str, consumed: usize) -> bool {
+    let rest = &input[consumed..];
+    rest.trim_start().is_empty()
+        || (input[..consumed].ends_with(|c: char| c.is_ascii_digit())
+            && rest.starts_with(|c: char| c.is_ascii_alphanumeric() || ".+-".contains(c)))
+}
+
+/// Whether the input at a syntax error is empty, or starts with a single
+/// token that more input could complete, such as an unterminated string
+fn unfinished(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    let mut chars = rest.chars();
+    match chars.next() {
+        None => true,
+        Some(quote) if quote == '"' || quote == '\'' => {
+            let mut escaped = false;
+            !chars.any(|c| {
+                let end = !escaped && c == quote;
+                escaped = !escaped && c == '\\';
+                end
+            })
+        }
+        Some('/') => match chars.next() {
+            None => true,
+            Some('*') => !rest[2..].contains("*/"),
+            Some('/') => !rest.contains('\n'),
+            Some(_) => false,
+        },
+        Some('<') => !rest.contains('>'),
+        Some(_) => rest
+            .chars()
+            .all(|c| c.is_alphanumeric() || "+-.$_".contains(c)),
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{
+    error::Span,
+    json::JsonValue,
+    jsx::{JsxElement, JsxFragment, JsxValue},
+    jsxn::JsxnValue,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{borrow::Cow, collections::BTreeMap};
+
+#[cfg(feature = "serde_json")]
+use crate::json::{escape, unescape};
+
+/// A JSXN value that serializes every node as an object tagged with its
+/// kind, so that it can be deserialized back into the same tree
+pub struct Tagged<'a> {
+    value: &'a JsxnValue,
+}
+
+/// Serialize a JSXN value with explicit node kinds
+pub fn tagged(value: &JsxnValue) -> Tagged<'_> {
+    Tagged { value }
+}
+
+impl<'a> Serialize for Tagged<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(self.value, serializer)
+    }
+}
+
+/// Serialize a JSXN value as nodes tagged with a `"kind"` of `"element"`,
+/// `"fragment"`, `"expression"`, `"variable"`, `"error"`, `"string"`,
+/// `"number"`, `"boolean"`, `"null"`, `"array"` or `"object"`. A JSON value
+/// holding a JSX value of a JSON kind, and the other way around, is wrapped
+/// in a `"json"` or `"jsx"` node. Strings are kept as stored in the tree,
+/// and numbers that are not finite are serialized as `"NaN"`, `"Infinity"`
+/// or `"-Infinity"`.
+///
+/// Together with `deserialize`, this can be used with
+/// `#[serde(with = "jsxn::tagged")]`.
+pub fn serialize<S>(value: &JsxnValue, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    Node::from_jsxn(value).serialize(serializer)
+}
+
+/// Deserialize a JSXN value serialized by `serialize`
+pub fn deserialize<'de, D>(deserializer: D) -> Result<JsxnValue, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Node::deserialize(deserializer)?.into_jsxn())
+}
+
+/// Serialize like `serialize`, but with the JSON strings and keys unescaped
+/// rather than kept as stored in the tree
+#[cfg(feature = "serde_json")]
+pub(crate) fn serialize_unescaped<S>(value: &JsxnValue, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut node = Node::from_jsxn(value);
+    node.rewrite_strings(unescape);
+    node.serialize(serializer)
+}
+
+/// Deserialize a JSXN value serialized by `serialize_unescaped`, escaping its
+/// JSON strings and keys the way they are kept in the trees
+#[cfg(feature = "serde_json")]
+pub(crate) fn deserialize_unescaped<'de, D>(deserializer: D) -> Result<JsxnValue, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut node = Node::deserialize(deserializer)?;
+    node.rewrite_strings(escape);
+    Ok(node.into_jsxn())
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Node<'a> {
+    Element {
+        r#type: Cow<'a, str>,
+        props: BTreeMap<Cow<'a, str>, Node<'a>>,
+        children: Vec<Node<'a>>,
+    },
+    Fragment {
+        children: Vec<Node<'a>>,
+    },
+    Expression {
+        value: Box<Node<'a>>,
+    },
+    Variable {
+        path: Vec<Cow<'a, str>>,
+    },
+    Error(Span),
+    String {
+        value: Cow<'a, str>,
+    },
+    Number {
+        #[serde(with = "number")]
+        value: f64,
+    },
+    Boolean {
+        value: bool,
+    },
+    Null,
+    Array {
+        items: Vec<Node<'a>>,
+    },
+    Object {
+        entries: BTreeMap<Cow<'a, str>, Node<'a>>,
+    },
+
+    /// A JSX value holding a JSON value
+    Jsx {
+        value: Box<Node<'a>>,
+    },
+
+    /// A JSON value holding a JSX value
+    Json {
+        value: Box<Node<'a>>,
+    },
+}
+
+impl<'a> Node<'a> {
+    fn from_jsxn(value: &'a JsxnValue) -> Node<'a> {
+        match value {
+            JsxnValue::JsxValue(JsxValue::JsonValue(json)) => Node::Jsx {
+                value: Box::new(Node::from_json(json)),
+            },
+            JsxnValue::JsxValue(jsx) => Node::from_jsx(jsx),
+            JsxnValue::JsonValue(JsonValue::JsxValue(jsx)) => Node::Json {
+                value: Box::new(Node::from_jsx(jsx)),
+            },
+            JsxnValue::JsonValue(json) => Node::from_json(json),
+        }
+    }
+
+    fn from_jsx(jsx: &'a JsxValue) -> Node<'a> {
+        match jsx {
+            JsxValue::JsxElement(element) => Node::Element {
+                r#type: Cow::Borrowed(element.r#type()),
+                props: element
+                    .props()
+                    .iter()
+                    .map(|(name, value)| (Cow::Borrowed(name.as_str()), Node::from_jsx(value)))
+                    .collect(),
+                children: element.children().iter().map(Node::from_jsx).collect(),
+            },
+            JsxValue::JsxFragment(fragment) => Node::Fragment {
+                children: fragment.children().iter().map(Node::from_jsx).collect(),
+            },
+            JsxValue::JsonValue(JsonValue::JsxValue(jsx)) => Node::Json {
+                value: Box::new(Node::from_jsx(jsx)),
+            },
+            JsxValue::JsonValue(json) => Node::from_json(json),
+            JsxValue::JsxExpression(jsx) => Node::Expression {
+                value: Box::new(Node::from_jsx(jsx)),
+            },
+            JsxValue::Variable(path) => Node::Variable {
+                path: path
+                    .iter()
+                    .map(|segment| Cow::Borrowed(segment.as_str()))
+                    .collect(),
+            },
+            JsxValue::Error(span) => Node::Error(*span),
+        }
+    }
+
+    fn from_json(json: &'a JsonValue) -> Node<'a> {
+        match json {
+            JsonValue::Str(s) => Node::String {
+                value: Cow::Borrowed(s),
+            },
+            JsonValue::Boolean(boolean) => Node::Boolean { value: *boolean },
+            JsonValue::Num(num) => Node::Number { value: *num },
+            JsonValue::Array(array) => Node::Array {
+                items: array.iter().map(Node::from_json).collect(),
+            },
+            JsonValue::Object(object) => Node::Object {
+                entries: object
+                    .iter()
+                    .map(|(key, value)| (Cow::Borrowed(key.as_str()), Node::from_json(value)))
+                    .collect(),
+            },
+            JsonValue::Null => Node::Null,
+            JsonValue::JsxValue(jsx) => match &**jsx {
+                JsxValue::JsonValue(json) => Node::Jsx {
+                    value: Box::new(Node::from_json(json)),
+                },
+                jsx => Node::from_jsx(jsx),
+            },
+        }
+    }
+
+    /// Rewrite the JSON strings and keys within the node, leaving the text
+    /// children of elements and fragments as they are
+    #[cfg(feature = "serde_json")]
+    fn rewrite_strings(&mut self, rewrite: fn(&str) -> String) {
+        match self {
+            Node::Element {
+                props, children, ..
+            } => {
+                for value in props.values_mut() {
+                    value.rewrite_strings(rewrite);
+                }
+                Node::rewrite_children(children, rewrite);
+            }
+            Node::Fragment { children } => Node::rewrite_children(children, rewrite),
+            Node::Expression { value } | Node::Jsx { value } | Node::Json { value } => {
+                value.rewrite_strings(rewrite)
+            }
+            Node::String { value } => *value = Cow::Owned(rewrite(value)),
+            Node::Array { items } => {
+                for item in items {
+                    item.rewrite_strings(rewrite);
+                }
+            }
+            Node::Object { entries } => {
+                *entries = std::mem::take(entries)
+                    .into_iter()
+                    .map(|(key, mut value)| {
+                        value.rewrite_strings(rewrite);
+                        (Cow::Owned(rewrite(&key)), value)
+                    })
+                    .collect();
+            }
+            Node::Variable { .. }
+            | Node::Error(_)
+            | Node::Number { .. }
+            | Node::Boolean { .. }
+            | Node::Null => {}
+        }
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn rewrite_children(children: &mut [Node<'a>], rewrite: fn(&str) -> String) {
+        for child in children {
+            if !matches!(child, Node::String { .. }) {
+                child.rewrite_strings(rewrite);
+            }
+        }
+    }
+
+    /// Whether the node is of a kind that only a JSX value can be
+    fn is_jsx(&self) -> bool {
+        matches!(
+            self,
+            Node::Element { .. }
+                | Node::Fragment { .. }
+                | Node::Expression { .. }
+                | Node::Variable { .. }
+                | Node::Error(_)
+                | Node::Jsx { .. }
+        )
+    }
+
+    fn into_jsxn(self) -> JsxnValue {
+        if self.is_jsx() {
+            JsxnValue::JsxValue(self.into_jsx())
+        } else {
+            JsxnValue::JsonValue(self.into_json())
+        }
+    }
+
+    fn into_jsx(self) -> JsxValue {
+        match self {
+            Node::Element {
+                r#type,
+                props,
+                children,
+            } => JsxValue::JsxElement(JsxElement::new(
+                r#type.into_owned(),
+                props
+                    .into_iter()
+                    .map(|(name, value)| (name.into_owned(), value.into_jsx()))
+                    .collect(),
+                children.into_iter().map(Node::into_jsx).collect(),
+            )),
+            Node::Fragment { children } => JsxValue::JsxFragment(JsxFragment::new(
+                children.into_iter().map(Node::into_jsx).collect(),
+            )),
+            Node::Expression { value } => JsxValue::JsxExpression(Box::new(value.into_jsx())),
+            Node::Variable { path } => {
+                JsxValue::Variable(path.into_iter().map(Cow::into_owned).collect())
+            }
+            Node::Error(span) => JsxValue::Error(span),
+            Node::Jsx { value } => JsxValue::JsonValue(value.into_json()),
+            node => JsxValue::JsonValue(node.into_json()),
+        }
+    }
+
+    fn into_json(self) -> JsonValue {
+        match self {
+            Node::String { value } => JsonValue::Str(value.into_owned()),
+            Node::Number { value } => JsonValue::Num(value),
+            Node::Boolean { value } => JsonValue::Boolean(value),
+            Node::Null => JsonValue::Null,
+            Node::Array { items } => {
+                JsonValue::Array(items.into_iter().map(Node::into_json).collect())
+            }
+            Node::Object { entries } => JsonValue::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_json()))
+                    .collect(),
+            ),
+            Node::Json { value } => JsonValue::JsxValue(Box::new(value.into_jsx())),
+            node => JsonValue::JsxValue(Box::new(node.into_jsx())),
+        }
+    }
+}
+
+/// Numbers that are not finite, which JSON cannot represent, as strings
+mod number {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(num: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if num.is_nan() {
+            serializer.serialize_str("NaN")
+        } else if num.is_infinite() {
+            serializer.serialize_str(if *num > 0.0 { "Infinity" } else { "-Infinity" })
+        } else {
+            serializer.serialize_f64(*num)
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Finite(f64),
+        NotFinite(String),
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = match Number::deserialize(deserializer)? {
+            Number::Finite(num) => return Ok(num),
+            Number::NotFinite(name) => name,
+        };
+        match name.as_str() {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            other => Err(de::Error::invalid_value(
+                de::Unexpected::Str(other),
+                &"a number, \"NaN\", \"Infinity\" or \"-Infinity\"",
+            )),
+        }
+    }
+}
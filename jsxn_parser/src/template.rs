@@ -0,0 +1,109 @@
+use crate::{
+    json::JsonValue,
+    jsx::{JsxElement, JsxFragment, JsxValue},
+    jsxn::JsxnValue,
+};
+use std::{collections::BTreeMap, error, fmt};
+
+/// An error returned when evaluating a template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvaluateError {
+    /// A variable does not refer to a value of the context
+    UndefinedVariable {
+        /// The path of the variable
+        path: Vec<String>,
+    },
+}
+
+impl fmt::Display for EvaluateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvaluateError::UndefinedVariable { path } => {
+                write!(f, "undefined variable ${}", path.join("."))
+            }
+        }
+    }
+}
+
+impl error::Error for EvaluateError {}
+
+/// Replace every template variable with the value of the context it refers
+/// to, producing a tree of literal values. Each segment of a variable's path
+/// is an object key, or an index if the value is an array.
+pub fn evaluate(jsx: &JsxValue, context: &JsonValue) -> Result<JsxValue, EvaluateError> {
+    Ok(match jsx {
+        JsxValue::JsxElement(element) => {
+            let mut props = BTreeMap::new();
+            for (name, value) in element.props() {
+                props.insert(name.clone(), evaluate(value, context)?);
+            }
+            JsxValue::JsxElement(JsxElement::new(
+                String::from(element.r#type()),
+                props,
+                evaluate_all(element.children(), context)?,
+            ))
+        }
+        JsxValue::JsxFragment(fragment) => JsxValue::JsxFragment(JsxFragment::new(evaluate_all(
+            fragment.children(),
+            context,
+        )?)),
+        JsxValue::JsonValue(json) => JsxValue::JsonValue(evaluate_json(json, context)?),
+        JsxValue::JsxExpression(jsx) => JsxValue::JsxExpression(Box::new(evaluate(jsx, context)?)),
+        JsxValue::Variable(path) => match resolve(path, context)? {
+            JsonValue::JsxValue(jsx) => (**jsx).clone(),
+            json => JsxValue::JsonValue(json.clone()),
+        },
+        JsxValue::Error(span) => JsxValue::Error(*span),
+    })
+}
+
+/// Replace every template variable of a JSXN value with the value of the
+/// context it refers to
+pub fn evaluate_jsxn(jsxn: &JsxnValue, context: &JsonValue) -> Result<JsxnValue, EvaluateError> {
+    Ok(match jsxn {
+        JsxnValue::JsxValue(jsx) => JsxnValue::JsxValue(evaluate(jsx, context)?),
+        JsxnValue::JsonValue(json) => JsxnValue::JsonValue(evaluate_json(json, context)?),
+    })
+}
+
+fn evaluate_all(values: &[JsxValue], context: &JsonValue) -> Result<Vec<JsxValue>, EvaluateError> {
+    values
+        .iter()
+        .map(|value| evaluate(value, context))
+        .collect()
+}
+
+fn evaluate_json(json: &JsonValue, context: &JsonValue) -> Result<JsonValue, EvaluateError> {
+    Ok(match json {
+        JsonValue::Array(array) => JsonValue::Array(
+            array
+                .iter()
+                .map(|item| evaluate_json(item, context))
+                .collect::<Result<_, _>>()?,
+        ),
+        JsonValue::Object(object) => {
+            let mut evaluated = BTreeMap::new();
+            for (key, value) in object {
+                evaluated.insert(key.clone(), evaluate_json(value, context)?);
+            }
+            JsonValue::Object(evaluated)
+        }
+        JsonValue::JsxValue(jsx) => JsonValue::JsxValue(Box::new(evaluate(jsx, context)?)),
+        json => json.clone(),
+    })
+}
+
+fn resolve<'c>(path: &[String], context: &'c JsonValue) -> Result<&'c JsonValue, EvaluateError> {
+    path.iter()
+        .try_fold(context, |value, segment| match value {
+            JsonValue::Object(object) => object.get(segment),
+            JsonValue::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| array.get(index)),
+            _ => None,
+        })
+        .ok_or_else(|| EvaluateError::UndefinedVariable {
+            path: path.to_vec(),
+        })
+}
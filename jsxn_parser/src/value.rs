@@ -0,0 +1,258 @@
+use crate::{
+    json::{escape, unescape, JsonValue},
+    jsx::JsxValue,
+    jsxn::JsxnValue,
+    profile::{self, Profile},
+    tagged,
+};
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Number, Value};
+use std::{convert::TryFrom, error, fmt};
+
+/// How JSX values are converted to `serde_json::Value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsxShape {
+    /// JSX values cannot be converted, and fail with `ValueError::Jsx`
+    Error,
+
+    /// JSX values become objects in the shape of a serialization profile,
+    /// inside JSON that is converted as usual. These objects convert back
+    /// into JSON objects rather than JSX values.
+    Profile(Profile),
+
+    /// The whole value is tagged with node kinds, as serialized by the
+    /// `tagged` module, and converts back with `from_tagged`
+    Tagged,
+}
+
+/// An error returned when a conversion would lose information
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueError {
+    /// The value holds a JSX value, which `JsxShape::Error` does not convert
+    Jsx,
+
+    /// The value holds a number that is not finite, which
+    /// `serde_json::Value` cannot represent
+    NonFiniteNumber(f64),
+
+    /// The value holds an integer that a `JsonValue::Num` cannot represent
+    /// exactly
+    InexactNumber(Number),
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueError::Jsx => f.write_str("JSX values cannot be converted"),
+            ValueError::NonFiniteNumber(num) => {
+                write!(f, "{} cannot be represented in serde_json", num)
+            }
+            ValueError::InexactNumber(num) => {
+                write!(f, "{} cannot be represented exactly as a number", num)
+            }
+        }
+    }
+}
+
+impl error::Error for ValueError {}
+
+/// Convert a JSXN value into a `serde_json::Value`, converting its JSX
+/// values in the given shape. JSON strings and keys, which are kept as
+/// written in the trees, are unescaped in every shape, and whole numbers
+/// become integers.
+pub fn to_serde_json(jsxn: &JsxnValue, shape: JsxShape) -> Result<Value, ValueError> {
+    match (jsxn, shape) {
+        (_, JsxShape::Tagged) => {
+            Ok(serde_json::to_value(Tagged(jsxn))
+                .expect("tagged nodes convert to serde_json values"))
+        }
+        (JsxnValue::JsxValue(jsx), shape) => jsx_to_value(jsx, shape),
+        (JsxnValue::JsonValue(json), shape) => json_to_value(json, shape),
+    }
+}
+
+/// Convert a `serde_json::Value` in the `JsxShape::Tagged` shape back into a
+/// JSXN value, escaping its JSON strings and keys the way they are kept in the
+/// trees
+pub fn from_tagged(value: Value) -> Result<JsxnValue, serde_json::Error> {
+    tagged::deserialize_unescaped(value)
+}
+
+fn json_to_value(json: &JsonValue, shape: JsxShape) -> Result<Value, ValueError> {
+    Ok(match json {
+        JsonValue::Str(s) => Value::String(unescape(s)),
+        JsonValue::Boolean(boolean) => Value::Bool(*boolean),
+        JsonValue::Num(num) => Value::Number(number(*num)?),
+        JsonValue::Array(array) => Value::Array(
+            array
+                .iter()
+                .map(|item| json_to_value(item, shape))
+                .collect::<Result<_, _>>()?,
+        ),
+        JsonValue::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| Ok((unescape(key), json_to_value(value, shape)?)))
+                .collect::<Result<Map<_, _>, _>>()?,
+        ),
+        JsonValue::Null => Value::Null,
+        JsonValue::JsxValue(jsx) => jsx_to_value(jsx, shape)?,
+    })
+}
+
+fn jsx_to_value(jsx: &JsxValue, shape: JsxShape) -> Result<Value, ValueError> {
+    let profile = match shape {
+        JsxShape::Profile(profile) => profile,
+        _ => return Err(ValueError::Jsx),
+    };
+    // serde_json would silently convert numbers that are not finite to null
+    check_numbers(jsx)?;
+    Ok(serde_json::to_value(Profiled { jsx, profile })
+        .expect("profiled elements convert to serde_json values"))
+}
+
+struct Profiled<'a> {
+    jsx: &'a JsxValue,
+    profile: Profile,
+}
+
+impl<'a> Serialize for Profiled<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        profile::serialize_jsx(self.jsx, self.profile, serializer)
+    }
+}
+
+struct Tagged<'a>(&'a JsxnValue);
+
+impl<'a> Serialize for Tagged<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        tagged::serialize_unescaped(self.0, serializer)
+    }
+}
+
+fn check_numbers(jsx: &JsxValue) -> Result<(), ValueError> {
+    match jsx {
+        JsxValue::JsxElement(element) => {
+            for value in element.props().values() {
+                check_numbers(value)?;
+            }
+            element.children().iter().try_for_each(check_numbers)
+        }
+        JsxValue::JsxFragment(fragment) => fragment.children().iter().try_for_each(check_numbers),
+        JsxValue::JsonValue(json) => check_json_numbers(json),
+        JsxValue::JsxExpression(jsx) => check_numbers(jsx),
+        JsxValue::Error(_) | JsxValue::Variable(_) => Ok(()),
+    }
+}
+
+fn check_json_numbers(json: &JsonValue) -> Result<(), ValueError> {
+    match json {
+        JsonValue::Num(num) => number(*num).map(|_| ()),
+        JsonValue::Array(array) => array.iter().try_for_each(check_json_numbers),
+        JsonValue::Object(object) => object.values().try_for_each(check_json_numbers),
+        JsonValue::JsxValue(jsx) => check_numbers(jsx),
+        JsonValue::Str(_) | JsonValue::Boolean(_) | JsonValue::Null => Ok(()),
+    }
+}
+
+/// Convert a number, as an integer if it is one, since JSON does not tell
+/// `1` and `1.0` apart while `serde_json::Value` does
+fn number(num: f64) -> Result<Number, ValueError> {
+    let integer = num.fract() == 0.0 && !(num == 0.0 && num.is_sign_negative());
+    if integer && (0.0..U64_END).contains(&num) {
+        Ok(Number::from(num as u64))
+    } else if integer && (-I64_END..0.0).contains(&num) {
+        Ok(Number::from(num as i64))
+    } else {
+        Number::from_f64(num).ok_or(ValueError::NonFiniteNumber(num))
+    }
+}
+
+impl TryFrom<&JsonValue> for Value {
+    type Error = ValueError;
+
+    /// Convert a JSON value, failing if it holds JSX values
+    fn try_from(json: &JsonValue) -> Result<Value, ValueError> {
+        json_to_value(json, JsxShape::Error)
+    }
+}
+
+impl TryFrom<JsonValue> for Value {
+    type Error = ValueError;
+
+    /// Convert a JSON value, failing if it holds JSX values
+    fn try_from(json: JsonValue) -> Result<Value, ValueError> {
+        Value::try_from(&json)
+    }
+}
+
+impl TryFrom<Value> for JsonValue {
+    type Error = ValueError;
+
+    /// Convert a `serde_json::Value`, escaping its strings and keys the way
+    /// they are kept in the trees, and failing on integers that a `f64` cannot
+    /// represent exactly
+    fn try_from(value: Value) -> Result<JsonValue, ValueError> {
+        Ok(match value {
+            Value::Null => JsonValue::Null,
+            Value::Bool(boolean) => JsonValue::Boolean(boolean),
+            Value::Number(num) => {
+                JsonValue::Num(exact(&num).ok_or(ValueError::InexactNumber(num))?)
+            }
+            Value::String(s) => JsonValue::Str(escape(&s)),
+            Value::Array(array) => JsonValue::Array(
+                array
+                    .into_iter()
+                    .map(JsonValue::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(object) => JsonValue::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| Ok((escape(&key), JsonValue::try_from(value)?)))
+                    .collect::<Result<_, ValueError>>()?,
+            ),
+        })
+    }
+}
+
+impl TryFrom<Value> for JsxnValue {
+    type Error = ValueError;
+
+    /// Convert a `serde_json::Value` into a JSON value
+    fn try_from(value: Value) -> Result<JsxnValue, ValueError> {
+        JsonValue::try_from(value).map(JsxnValue::JsonValue)
+    }
+}
+
+/// 2^64 and 2^63, beyond which integers saturate when converted to `u64` and
+/// `i64`
+const U64_END: f64 = 18_446_744_073_709_551_616.0;
+const I64_END: f64 = 9_223_372_036_854_775_808.0;
+
+/// The number as a `f64`, if it is exactly representable as one
+fn exact(num: &Number) -> Option<f64> {
+    if let Some(int) = num.as_u64() {
+        let float = int as f64;
+        return if float < U64_END && float as u64 == int {
+            Some(float)
+        } else {
+            None
+        };
+    }
+    if let Some(int) = num.as_i64() {
+        let float = int as f64;
+        return if float < I64_END && float as i64 == int {
+            Some(float)
+        } else {
+            None
+        };
+    }
+    num.as_f64()
+}
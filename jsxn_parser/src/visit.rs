@@ -0,0 +1,589 @@
+use crate::{
+    error::Span,
+    json::JsonValue,
+    jsx::{JsxElement, JsxFragment, JsxValue},
+    jsxn::JsxnValue,
+};
+
+/// A visitor walking a tree by reference
+///
+/// The default of each method walks below its node by calling the free
+/// function of the same name, which an overriding method may call too. Text
+/// children of elements and fragments are visited as text, and strings
+/// anywhere else as JSON strings, which are passed escaped like object keys.
+pub trait Visit<'a> {
+    /// Visit a JSXN value
+    fn visit_jsxn(&mut self, jsxn: &'a JsxnValue) {
+        visit_jsxn(self, jsxn)
+    }
+
+    /// Visit a JSX value
+    fn visit_jsx(&mut self, jsx: &'a JsxValue) {
+        visit_jsx(self, jsx)
+    }
+
+    /// Visit a JSON value
+    fn visit_json(&mut self, json: &'a JsonValue) {
+        visit_json(self, json)
+    }
+
+    /// Visit an element, then its props and its children
+    fn visit_element(&mut self, element: &'a JsxElement) {
+        visit_element(self, element)
+    }
+
+    /// Visit a fragment, then its children
+    fn visit_fragment(&mut self, fragment: &'a JsxFragment) {
+        visit_fragment(self, fragment)
+    }
+
+    /// Visit a prop of an element, then its value
+    fn visit_prop(&mut self, name: &'a str, value: &'a JsxValue) {
+        visit_prop(self, name, value)
+    }
+
+    /// Visit a text child of an element or fragment
+    fn visit_text(&mut self, _text: &'a str) {}
+
+    /// Visit the value inside an expression
+    fn visit_expression(&mut self, jsx: &'a JsxValue) {
+        visit_expression(self, jsx)
+    }
+
+    /// Visit a template variable
+    fn visit_variable(&mut self, _path: &'a [String]) {}
+
+    /// Visit input that could not be parsed
+    fn visit_error(&mut self, _span: &'a Span) {}
+
+    /// Visit an entry of an object, then its value
+    fn visit_object_entry(&mut self, key: &'a str, value: &'a JsonValue) {
+        visit_object_entry(self, key, value)
+    }
+
+    /// Visit an item of an array, then the item itself
+    fn visit_array_item(&mut self, index: usize, item: &'a JsonValue) {
+        visit_array_item(self, index, item)
+    }
+
+    /// Visit a JSON string
+    fn visit_string(&mut self, _string: &'a str) {}
+
+    /// Visit a JSON number
+    fn visit_number(&mut self, _number: f64) {}
+
+    /// Visit a JSON boolean
+    fn visit_boolean(&mut self, _boolean: bool) {}
+
+    /// Visit a JSON null value
+    fn visit_null(&mut self) {}
+}
+
+/// Walk a JSXN value
+pub fn visit_jsxn<'a, V>(visitor: &mut V, jsxn: &'a JsxnValue)
+where
+    V: Visit<'a> + ?Sized,
+{
+    match jsxn {
+        JsxnValue::JsxValue(jsx) => visitor.visit_jsx(jsx),
+        JsxnValue::JsonValue(json) => visitor.visit_json(json),
+    }
+}
+
+/// Walk a JSX value
+pub fn visit_jsx<'a, V>(visitor: &mut V, jsx: &'a JsxValue)
+where
+    V: Visit<'a> + ?Sized,
+{
+    match jsx {
+        JsxValue::JsxElement(element) => visitor.visit_element(element),
+        JsxValue::JsxFragment(fragment) => visitor.visit_fragment(fragment),
+        JsxValue::JsonValue(json) => visitor.visit_json(json),
+        JsxValue::JsxExpression(jsx) => visitor.visit_expression(jsx),
+        JsxValue::Error(span) => visitor.visit_error(span),
+        JsxValue::Variable(path) => visitor.visit_variable(path),
+    }
+}
+
+/// Walk a JSON value
+pub fn visit_json<'a, V>(visitor: &mut V, json: &'a JsonValue)
+where
+    V: Visit<'a> + ?Sized,
+{
+    match json {
+        JsonValue::Str(string) => visitor.visit_string(string),
+        JsonValue::Boolean(boolean) => visitor.visit_boolean(*boolean),
+        JsonValue::Num(number) => visitor.visit_number(*number),
+        JsonValue::Array(array) => {
+            for (index, item) in array.iter().enumerate() {
+                visitor.visit_array_item(index, item);
+            }
+        }
+        JsonValue::Object(object) => {
+            for (key, value) in object {
+                visitor.visit_object_entry(key, value);
+            }
+        }
+        JsonValue::Null => visitor.visit_null(),
+        JsonValue::JsxValue(jsx) => visitor.visit_jsx(jsx),
+    }
+}
+
+/// Walk the props and children of an element
+pub fn visit_element<'a, V>(visitor: &mut V, element: &'a JsxElement)
+where
+    V: Visit<'a> + ?Sized,
+{
+    for (name, value) in element.props() {
+        visitor.visit_prop(name, value);
+    }
+    visit_children(visitor, element.children());
+}
+
+/// Walk the children of a fragment
+pub fn visit_fragment<'a, V>(visitor: &mut V, fragment: &'a JsxFragment)
+where
+    V: Visit<'a> + ?Sized,
+{
+    visit_children(visitor, fragment.children());
+}
+
+/// Walk the value of a prop
+pub fn visit_prop<'a, V>(visitor: &mut V, _name: &'a str, value: &'a JsxValue)
+where
+    V: Visit<'a> + ?Sized,
+{
+    visitor.visit_jsx(value);
+}
+
+/// Walk the value inside an expression
+pub fn visit_expression<'a, V>(visitor: &mut V, jsx: &'a JsxValue)
+where
+    V: Visit<'a> + ?Sized,
+{
+    visitor.visit_jsx(jsx);
+}
+
+/// Walk the value of an object entry
+pub fn visit_object_entry<'a, V>(visitor: &mut V, _key: &'a str, value: &'a JsonValue)
+where
+    V: Visit<'a> + ?Sized,
+{
+    visitor.visit_json(value);
+}
+
+/// Walk an array item
+pub fn visit_array_item<'a, V>(visitor: &mut V, _index: usize, item: &'a JsonValue)
+where
+    V: Visit<'a> + ?Sized,
+{
+    visitor.visit_json(item);
+}
+
+fn visit_children<'a, V>(visitor: &mut V, children: &'a [JsxValue])
+where
+    V: Visit<'a> + ?Sized,
+{
+    for child in children {
+        match child {
+            JsxValue::JsonValue(JsonValue::Str(text)) => visitor.visit_text(text),
+            child => visitor.visit_jsx(child),
+        }
+    }
+}
+
+/// A visitor walking a tree by mutable reference, to modify it in place
+pub trait VisitMut {
+    /// Visit a JSXN value
+    fn visit_jsxn_mut(&mut self, jsxn: &mut JsxnValue) {
+        visit_jsxn_mut(self, jsxn)
+    }
+
+    /// Visit a JSX value
+    fn visit_jsx_mut(&mut self, jsx: &mut JsxValue) {
+        visit_jsx_mut(self, jsx)
+    }
+
+    /// Visit a JSON value
+    fn visit_json_mut(&mut self, json: &mut JsonValue) {
+        visit_json_mut(self, json)
+    }
+
+    /// Visit an element, then its props and its children
+    fn visit_element_mut(&mut self, element: &mut JsxElement) {
+        visit_element_mut(self, element)
+    }
+
+    /// Visit a fragment, then its children
+    fn visit_fragment_mut(&mut self, fragment: &mut JsxFragment) {
+        visit_fragment_mut(self, fragment)
+    }
+
+    /// Visit a prop of an element, then its value
+    fn visit_prop_mut(&mut self, name: &str, value: &mut JsxValue) {
+        visit_prop_mut(self, name, value)
+    }
+
+    /// Visit a text child of an element or fragment
+    fn visit_text_mut(&mut self, _text: &mut String) {}
+
+    /// Visit the value inside an expression
+    fn visit_expression_mut(&mut self, jsx: &mut JsxValue) {
+        visit_expression_mut(self, jsx)
+    }
+
+    /// Visit a template variable
+    fn visit_variable_mut(&mut self, _path: &mut Vec<String>) {}
+
+    /// Visit input that could not be parsed
+    fn visit_error_mut(&mut self, _span: &mut Span) {}
+
+    /// Visit an entry of an object, then its value
+    fn visit_object_entry_mut(&mut self, key: &str, value: &mut JsonValue) {
+        visit_object_entry_mut(self, key, value)
+    }
+
+    /// Visit an item of an array, then the item itself
+    fn visit_array_item_mut(&mut self, index: usize, item: &mut JsonValue) {
+        visit_array_item_mut(self, index, item)
+    }
+
+    /// Visit a JSON string
+    fn visit_string_mut(&mut self, _string: &mut String) {}
+
+    /// Visit a JSON number
+    fn visit_number_mut(&mut self, _number: &mut f64) {}
+
+    /// Visit a JSON boolean
+    fn visit_boolean_mut(&mut self, _boolean: &mut bool) {}
+
+    /// Visit a JSON null value
+    fn visit_null_mut(&mut self) {}
+}
+
+/// Walk a JSXN value
+pub fn visit_jsxn_mut<V>(visitor: &mut V, jsxn: &mut JsxnValue)
+where
+    V: VisitMut + ?Sized,
+{
+    match jsxn {
+        JsxnValue::JsxValue(jsx) => visitor.visit_jsx_mut(jsx),
+        JsxnValue::JsonValue(json) => visitor.visit_json_mut(json),
+    }
+}
+
+/// Walk a JSX value
+pub fn visit_jsx_mut<V>(visitor: &mut V, jsx: &mut JsxValue)
+where
+    V: VisitMut + ?Sized,
+{
+    match jsx {
+        JsxValue::JsxElement(element) => visitor.visit_element_mut(element),
+        JsxValue::JsxFragment(fragment) => visitor.visit_fragment_mut(fragment),
+        JsxValue::JsonValue(json) => visitor.visit_json_mut(json),
+        JsxValue::JsxExpression(jsx) => visitor.visit_expression_mut(jsx),
+        JsxValue::Error(span) => visitor.visit_error_mut(span),
+        JsxValue::Variable(path) => visitor.visit_variable_mut(path),
+    }
+}
+
+/// Walk a JSON value
+pub fn visit_json_mut<V>(visitor: &mut V, json: &mut JsonValue)
+where
+    V: VisitMut + ?Sized,
+{
+    match json {
+        JsonValue::Str(string) => visitor.visit_string_mut(string),
+        JsonValue::Boolean(boolean) => visitor.visit_boolean_mut(boolean),
+        JsonValue::Num(number) => visitor.visit_number_mut(number),
+        JsonValue::Array(array) => {
+            for (index, item) in array.iter_mut().enumerate() {
+                visitor.visit_array_item_mut(index, item);
+            }
+        }
+        JsonValue::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                visitor.visit_object_entry_mut(key, value);
+            }
+        }
+        JsonValue::Null => visitor.visit_null_mut(),
+        JsonValue::JsxValue(jsx) => visitor.visit_jsx_mut(jsx),
+    }
+}
+
+/// Walk the props and children of an element
+pub fn visit_element_mut<V>(visitor: &mut V, element: &mut JsxElement)
+where
+    V: VisitMut + ?Sized,
+{
+    for (name, value) in element.props_mut().iter_mut() {
+        visitor.visit_prop_mut(name, value);
+    }
+    visit_children_mut(visitor, element.children_mut());
+}
+
+/// Walk the children of a fragment
+pub fn visit_fragment_mut<V>(visitor: &mut V, fragment: &mut JsxFragment)
+where
+    V: VisitMut + ?Sized,
+{
+    visit_children_mut(visitor, fragment.children_mut());
+}
+
+/// Walk the value of a prop
+pub fn visit_prop_mut<V>(visitor: &mut V, _name: &str, value: &mut JsxValue)
+where
+    V: VisitMut + ?Sized,
+{
+    visitor.visit_jsx_mut(value);
+}
+
+/// Walk the value inside an expression
+pub fn visit_expression_mut<V>(visitor: &mut V, jsx: &mut JsxValue)
+where
+    V: VisitMut + ?Sized,
+{
+    visitor.visit_jsx_mut(jsx);
+}
+
+/// Walk the value of an object entry
+pub fn visit_object_entry_mut<V>(visitor: &mut V, _key: &str, value: &mut JsonValue)
+where
+    V: VisitMut + ?Sized,
+{
+    visitor.visit_json_mut(value);
+}
+
+/// Walk an array item
+pub fn visit_array_item_mut<V>(visitor: &mut V, _index: usize, item: &mut JsonValue)
+where
+    V: VisitMut + ?Sized,
+{
+    visitor.visit_json_mut(item);
+}
+
+fn visit_children_mut<V>(visitor: &mut V, children: &mut [JsxValue])
+where
+    V: VisitMut + ?Sized,
+{
+    for child in children {
+        match child {
+            JsxValue::JsonValue(JsonValue::Str(text)) => visitor.visit_text_mut(text),
+            child => visitor.visit_jsx_mut(child),
+        }
+    }
+}
+
+/// A folder rebuilding a tree it takes ownership of, with each method
+/// returning the node that replaces the one it was given
+pub trait Fold {
+    /// Fold a JSXN value
+    fn fold_jsxn(&mut self, jsxn: JsxnValue) -> JsxnValue {
+        fold_jsxn(self, jsxn)
+    }
+
+    /// Fold a JSX value
+    fn fold_jsx(&mut self, jsx: JsxValue) -> JsxValue {
+        fold_jsx(self, jsx)
+    }
+
+    /// Fold a JSON value
+    fn fold_json(&mut self, json: JsonValue) -> JsonValue {
+        fold_json(self, json)
+    }
+
+    /// Fold an element, after its props and children by default
+    fn fold_element(&mut self, element: JsxElement) -> JsxValue {
+        fold_element(self, element)
+    }
+
+    /// Fold a fragment, after its children by default
+    fn fold_fragment(&mut self, fragment: JsxFragment) -> JsxValue {
+        fold_fragment(self, fragment)
+    }
+
+    /// Fold a prop of an element, which is removed if `None` is returned
+    fn fold_prop(&mut self, name: String, value: JsxValue) -> Option<(String, JsxValue)> {
+        fold_prop(self, name, value)
+    }
+
+    /// Fold a text child of an element or fragment
+    fn fold_text(&mut self, text: String) -> JsxValue {
+        JsxValue::JsonValue(JsonValue::Str(text))
+    }
+
+    /// Fold an expression, given the value inside it
+    fn fold_expression(&mut self, jsx: JsxValue) -> JsxValue {
+        fold_expression(self, jsx)
+    }
+
+    /// Fold a template variable
+    fn fold_variable(&mut self, path: Vec<String>) -> JsxValue {
+        JsxValue::Variable(path)
+    }
+
+    /// Fold input that could not be parsed
+    fn fold_error(&mut self, span: Span) -> JsxValue {
+        JsxValue::Error(span)
+    }
+
+    /// Fold an entry of an object, which is removed if `None` is returned
+    fn fold_object_entry(&mut self, key: String, value: JsonValue) -> Option<(String, JsonValue)> {
+        fold_object_entry(self, key, value)
+    }
+
+    /// Fold an item of an array
+    fn fold_array_item(&mut self, index: usize, item: JsonValue) -> JsonValue {
+        fold_array_item(self, index, item)
+    }
+
+    /// Fold a JSON string
+    fn fold_string(&mut self, string: String) -> JsonValue {
+        JsonValue::Str(string)
+    }
+
+    /// Fold a JSON number
+    fn fold_number(&mut self, number: f64) -> JsonValue {
+        JsonValue::Num(number)
+    }
+
+    /// Fold a JSON boolean
+    fn fold_boolean(&mut self, boolean: bool) -> JsonValue {
+        JsonValue::Boolean(boolean)
+    }
+
+    /// Fold a JSON null value
+    fn fold_null(&mut self) -> JsonValue {
+        JsonValue::Null
+    }
+}
+
+/// Rebuild a JSXN value
+pub fn fold_jsxn<F>(folder: &mut F, jsxn: JsxnValue) -> JsxnValue
+where
+    F: Fold + ?Sized,
+{
+    match jsxn {
+        JsxnValue::JsxValue(jsx) => JsxnValue::JsxValue(folder.fold_jsx(jsx)),
+        JsxnValue::JsonValue(json) => JsxnValue::JsonValue(folder.fold_json(json)),
+    }
+}
+
+/// Rebuild a JSX value
+pub fn fold_jsx<F>(folder: &mut F, jsx: JsxValue) -> JsxValue
+where
+    F: Fold + ?Sized,
+{
+    match jsx {
+        JsxValue::JsxElement(element) => folder.fold_element(element),
+        JsxValue::JsxFragment(fragment) => folder.fold_fragment(fragment),
+        JsxValue::JsonValue(json) => JsxValue::JsonValue(folder.fold_json(json)),
+        JsxValue::JsxExpression(jsx) => folder.fold_expression(*jsx),
+        JsxValue::Error(span) => folder.fold_error(span),
+        JsxValue::Variable(path) => folder.fold_variable(path),
+    }
+}
+
+/// Rebuild a JSON value
+pub fn fold_json<F>(folder: &mut F, json: JsonValue) -> JsonValue
+where
+    F: Fold + ?Sized,
+{
+    match json {
+        JsonValue::Str(string) => folder.fold_string(string),
+        JsonValue::Boolean(boolean) => folder.fold_boolean(boolean),
+        JsonValue::Num(number) => folder.fold_number(number),
+        JsonValue::Array(array) => JsonValue::Array(
+            array
+                .into_iter()
+                .enumerate()
+                .map(|(index, item)| folder.fold_array_item(index, item))
+                .collect(),
+        ),
+        JsonValue::Object(object) => JsonValue::Object(
+            object
+                .into_iter()
+                .filter_map(|(key, value)| folder.fold_object_entry(key, value))
+                .collect(),
+        ),
+        JsonValue::Null => folder.fold_null(),
+        JsonValue::JsxValue(jsx) => JsonValue::JsxValue(Box::new(folder.fold_jsx(*jsx))),
+    }
+}
+
+/// Rebuild an element from its folded props and children
+pub fn fold_element<F>(folder: &mut F, element: JsxElement) -> JsxValue
+where
+    F: Fold + ?Sized,
+{
+    let (r#type, props, children) = element.into_parts();
+    let props = props
+        .into_iter()
+        .filter_map(|(name, value)| folder.fold_prop(name, value))
+        .collect();
+    JsxValue::JsxElement(JsxElement::new(
+        r#type,
+        props,
+        fold_children(folder, children),
+    ))
+}
+
+/// Rebuild a fragment from its folded children
+pub fn fold_fragment<F>(folder: &mut F, fragment: JsxFragment) -> JsxValue
+where
+    F: Fold + ?Sized,
+{
+    JsxValue::JsxFragment(JsxFragment::new(fold_children(
+        folder,
+        fragment.into_children(),
+    )))
+}
+
+/// Rebuild a prop from its folded value
+pub fn fold_prop<F>(folder: &mut F, name: String, value: JsxValue) -> Option<(String, JsxValue)>
+where
+    F: Fold + ?Sized,
+{
+    Some((name, folder.fold_jsx(value)))
+}
+
+/// Rebuild an expression around its folded value
+pub fn fold_expression<F>(folder: &mut F, jsx: JsxValue) -> JsxValue
+where
+    F: Fold + ?Sized,
+{
+    JsxValue::JsxExpression(Box::new(folder.fold_jsx(jsx)))
+}
+
+/// Rebuild an object entry from its folded value
+pub fn fold_object_entry<F>(
+    folder: &mut F,
+    key: String,
+    value: JsonValue,
+) -> Option<(String, JsonValue)>
+where
+    F: Fold + ?Sized,
+{
+    Some((key, folder.fold_json(value)))
+}
+
+/// Fold an array item
+pub fn fold_array_item<F>(folder: &mut F, _index: usize, item: JsonValue) -> JsonValue
+where
+    F: Fold + ?Sized,
+{
+    folder.fold_json(item)
+}
+
+fn fold_children<F>(folder: &mut F, children: Vec<JsxValue>) -> Vec<JsxValue>
+where
+    F: Fold + ?Sized,
+{
+    children
+        .into_iter()
+        .map(|child| match child {
+            JsxValue::JsonValue(JsonValue::Str(text)) => folder.fold_text(text),
+            child => folder.fold_jsx(child),
+        })
+        .collect()
+}
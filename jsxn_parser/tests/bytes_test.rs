@@ -0,0 +1,97 @@
+use ::jsxn::{
+    error::{Error, Location},
+    jsxn,
+    stream::Stream,
+};
+use pretty_assertions::assert_eq;
+
+const VALID_JSXN: &str = "<List>\n  <Item>Ünïcödé 👍</Item>\n</List>";
+
+fn utf16(input: &str, bom: [u8; 2], unit: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    let mut bytes = bom.to_vec();
+    bytes.extend(input.encode_utf16().flat_map(unit));
+    bytes
+}
+
+#[test]
+fn parse_utf8_bytes() {
+    let expected = jsxn::parse(VALID_JSXN);
+    assert_eq!(jsxn::parse_bytes(VALID_JSXN.as_bytes()), expected);
+
+    let mut bom = b"\xEF\xBB\xBF".to_vec();
+    bom.extend(VALID_JSXN.as_bytes());
+    assert_eq!(jsxn::parse_bytes(&bom), expected);
+    assert_eq!(
+        Stream::new(&bom[..])
+            .collect::<Result<Vec<_>, _>>()
+            .map(|items| items.len()),
+        Ok(1)
+    );
+
+    bom.extend(b" x");
+    assert_eq!(
+        jsxn::parse_bytes(&bom).unwrap_err().location(),
+        Location {
+            offset: bom.len() - 1,
+            line: 3,
+            column: 9,
+        }
+    );
+}
+
+#[test]
+fn invalid_utf8_bytes() {
+    let mut input = b"\xEF\xBB\xBF<a>\n  \xC3\xBC".to_vec();
+    input.extend(b"\xFF</a>");
+    assert_eq!(
+        jsxn::parse_bytes(&input),
+        Err(Error::Syntax {
+            location: Location {
+                offset: 11,
+                line: 2,
+                column: 4,
+            },
+            message: String::from("invalid UTF-8"),
+        })
+    );
+}
+
+#[test]
+fn parse_utf16_bytes() {
+    let expected = jsxn::parse(VALID_JSXN);
+    assert_eq!(
+        jsxn::parse_bytes(&utf16(VALID_JSXN, [0xFF, 0xFE], u16::to_le_bytes)),
+        expected
+    );
+    assert_eq!(
+        jsxn::parse_bytes(&utf16(VALID_JSXN, [0xFE, 0xFF], u16::to_be_bytes)),
+        expected
+    );
+
+    let mut input = utf16("[1]", [0xFF, 0xFE], u16::to_le_bytes);
+    input.extend(&[0x00, 0xD8]);
+    assert_eq!(
+        jsxn::parse_bytes(&input),
+        Err(Error::Syntax {
+            location: Location {
+                offset: 8,
+                line: 1,
+                column: 4,
+            },
+            message: String::from("invalid UTF-16"),
+        })
+    );
+    input.pop();
+    assert!(jsxn::parse_bytes(&input).is_err());
+
+    // Locations are byte offsets into the UTF-16 input
+    let input = utf16("<p>Ünï 👍</a>", [0xFE, 0xFF], u16::to_be_bytes);
+    assert_eq!(
+        jsxn::parse_bytes(&input).unwrap_err().location(),
+        Location {
+            offset: 2 + 2 * 9,
+            line: 1,
+            column: 9,
+        }
+    );
+}
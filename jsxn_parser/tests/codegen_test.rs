@@ -0,0 +1,88 @@
+use ::jsxn::{
+    codegen::{self, CodegenOptions},
+    jsxn,
+    options::ParserOptions,
+};
+use pretty_assertions::assert_eq;
+
+const VALID_JSXN: &str = r#"
+    <List title="Groceries" count={1}>
+        <Item key="eggs" checked>eggs</Item>
+        <>{["milk", null]}</>
+        <p style={{ "fontSize": 12, "--accent": "red" }}>Say "hi"</p>
+    </List>
+"#;
+
+#[test]
+fn generate_classic() {
+    assert_eq!(
+        codegen::generate(&jsxn::parse(VALID_JSXN).unwrap(), &CodegenOptions::CLASSIC),
+        concat!(
+            r#"React.createElement(List, {count: 1, title: "Groceries"}, "#,
+            r#"React.createElement(Item, {checked: true, key: "eggs"}, "eggs"), "#,
+            r#"React.createElement(React.Fragment, null, ["milk", null]), "#,
+            r#"React.createElement("p", {style: {"--accent": "red", fontSize: 12}}, "Say \"hi\""))"#,
+        )
+    );
+    assert_eq!(
+        codegen::generate(
+            &jsxn::parse("<><br /></>").unwrap(),
+            &CodegenOptions {
+                pragma: "h",
+                pragma_frag: "Fragment",
+                ..CodegenOptions::CLASSIC
+            }
+        ),
+        r#"h(Fragment, null, h("br", null))"#
+    );
+}
+
+#[test]
+fn generate_automatic() {
+    assert_eq!(
+        codegen::generate(
+            &jsxn::parse(VALID_JSXN).unwrap(),
+            &CodegenOptions::AUTOMATIC
+        ),
+        concat!(
+            r#"_jsxs(List, {count: 1, title: "Groceries", children: ["#,
+            r#"_jsx(Item, {checked: true, children: "eggs"}, "eggs"), "#,
+            r#"_jsx(_Fragment, {children: ["milk", null]}), "#,
+            r#"_jsx("p", {style: {"--accent": "red", fontSize: 12}, children: "Say \"hi\""})]})"#,
+        )
+    );
+    assert_eq!(
+        codegen::generate_module(
+            &jsxn::parse_with_options("<a>{$user.names.0}</a>", &ParserOptions::RELAXED).unwrap(),
+            &CodegenOptions {
+                import_source: "preact",
+                ..CodegenOptions::AUTOMATIC
+            }
+        ),
+        concat!(
+            "import { jsx as _jsx, jsxs as _jsxs, Fragment as _Fragment } from \"preact/jsx-runtime\";\n",
+            "export default _jsx(\"a\", {children: user.names[0]});\n",
+        )
+    );
+}
+
+#[test]
+fn generate_escapes() {
+    // Strings in expressions, props and keys are kept escaped, while text is
+    // kept as written
+    assert_eq!(
+        codegen::generate(
+            &jsxn::parse(r#"<p title="a\"b" style={{ "k\"ey": 1 }}>{"a\"b"}"c\"</p>"#).unwrap(),
+            &CodegenOptions::CLASSIC
+        ),
+        r#"React.createElement("p", {style: {"k\"ey": 1}, title: "a\"b"}, "a\"b", "\"c\\\"")"#
+    );
+    assert_eq!(
+        codegen::generate(
+            &jsxn::parse_with_options("<a b={$0.a} c={$b.1c}>{$c$.d}</a>", &ParserOptions::RELAXED)
+                .unwrap(),
+            &CodegenOptions::CLASSIC
+        ),
+        r#"React.createElement("a", {b: $0.a, c: b["1c"]}, c$.d)"#
+    );
+}
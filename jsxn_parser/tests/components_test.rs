@@ -0,0 +1,139 @@
+use ::jsxn::{
+    components::{ExpandError, Registry},
+    html, json, jsx, jsxn,
+};
+use pretty_assertions::assert_eq;
+
+fn expand(registry: &Registry, input: &str) -> Result<String, ExpandError> {
+    registry
+        .expand_jsxn(&jsxn::parse(input).unwrap())
+        .map(|jsxn| html::render_jsxn(&jsxn))
+}
+
+#[test]
+fn expand_templates() {
+    let mut registry = Registry::new();
+    registry
+        .register_template(
+            "Card",
+            r#"<div className="card"><h2 title={<Prop name="title" />}><Prop name="title" /></h2><Children /></div>"#,
+        )
+        .unwrap();
+    registry
+        .register_template(
+            "Page",
+            "<main><Card title={\"Inner\"}><Children /></Card></main>",
+        )
+        .unwrap();
+    assert_eq!(
+        expand(&registry, r#"<Card title="x"><p>one</p>two</Card>"#),
+        Ok(String::from(
+            r#"<div class="card"><h2 title="x">x</h2><p>one</p>two</div>"#
+        ))
+    );
+    assert_eq!(
+        registry.expand_jsxn(&jsxn::parse(r#"{ "page": <Page>body</Page> }"#).unwrap()),
+        Ok(jsxn::parse(
+            r#"{ "page": <main><div className="card"><h2 title={"Inner"}>{"Inner"}</h2><><>body</></></div></main> }"#
+        )
+        .unwrap())
+    );
+    assert_eq!(
+        expand(&registry, r#"[<Page>body</Page>]"#),
+        Ok(String::from(
+            r#"<main><div class="card"><h2 title="Inner">Inner</h2>body</div></main>"#
+        ))
+    );
+    assert_eq!(
+        expand(&registry, "<Card />"),
+        Ok(String::from(r#"<div class="card"><h2></h2></div>"#))
+    );
+}
+
+#[test]
+fn expand_closures() {
+    let mut registry = Registry::new();
+    registry.register_fn("Greeting", |props, children| {
+        let name = match props.get("name") {
+            Some(jsx::JsxValue::JsonValue(json::JsonValue::Str(name))) => name.clone(),
+            _ => String::from("stranger"),
+        };
+        jsx::JsxValue::JsxElement(jsx::JsxElement::new(
+            String::from("p"),
+            Default::default(),
+            std::iter::once(jsx::JsxValue::JsonValue(json::JsonValue::Str(format!(
+                "Hello, {}! ",
+                name
+            ))))
+            .chain(children.iter().cloned())
+            .collect(),
+        ))
+    });
+    registry
+        .register_template("Hello", r#"<Greeting name="world"><Children /></Greeting>"#)
+        .unwrap();
+    assert_eq!(
+        expand(&registry, "<div><Hello><b>Hi</b></Hello><Greeting /></div>"),
+        Ok(String::from(
+            "<div><p>Hello, world! <b>Hi</b></p><p>Hello, stranger! </p></div>"
+        ))
+    );
+}
+
+#[test]
+fn expand_errors() {
+    let mut registry = Registry::new();
+    registry.register_template("A", "<B />").unwrap();
+    registry.register_template("B", "<div><A /></div>").unwrap();
+    assert_eq!(
+        expand(&registry, "<main><A /></main>"),
+        Err(ExpandError::Cycle {
+            path: vec![String::from("A"), String::from("B"), String::from("A")],
+        })
+    );
+
+    let mut registry = Registry::new();
+    registry.register_template("A", "<B />").unwrap();
+    registry.register_template("B", "<C />").unwrap();
+    registry.register_template("C", "<div />").unwrap();
+    registry.max_depth = 2;
+    assert_eq!(
+        expand(&registry, "<A />"),
+        Err(ExpandError::TooDeep {
+            path: vec![String::from("A"), String::from("B"), String::from("C")],
+            limit: 2,
+        })
+    );
+    registry.max_depth = 3;
+    assert_eq!(expand(&registry, "<A />"), Ok(String::from("<div></div>")));
+}
+
+#[test]
+fn expand_repeated_children() {
+    let mut registry = Registry::new();
+    registry
+        .register_template("Twice", "<><Children /><Children /></>")
+        .unwrap();
+    let nested = |depth| "<Twice>".repeat(depth) + "<br />" + &"</Twice>".repeat(depth);
+    assert_eq!(
+        expand(&registry, &nested(2)),
+        Ok(String::from("<br/><br/><br/><br/>"))
+    );
+    registry.max_nodes = 100;
+    assert_eq!(
+        expand(&registry, &nested(64)),
+        Err(ExpandError::TooManyNodes {
+            path: vec![String::from("Twice")],
+            limit: 100,
+        })
+    );
+    // Children passed down through wrappers count once, not at every level
+    registry
+        .register_template("Wrap", "<div><Children /></div>")
+        .unwrap();
+    let wrapped = "<Wrap>".repeat(20) + &"<br />".repeat(90) + &"</Wrap>".repeat(20);
+    assert_eq!(
+        expand(&registry, &wrapped),
+        Ok("<div>".repeat(20) + &"<br/>".repeat(90) + &"</div>".repeat(20))
+    );
+}
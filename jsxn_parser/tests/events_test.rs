@@ -0,0 +1,206 @@
+use ::jsxn::{
+    error::{Error, Location},
+    events::{Event, Parser},
+    json::JsonValue,
+    jsx::JsxValue,
+    jsxn::{self, JsxnValue},
+    limits::ParseLimits,
+    options::{ParserOptions, RootKinds},
+};
+use nom::error::VerboseError;
+use pretty_assertions::assert_eq;
+
+const VALID_JSXN: &[&str] = &[
+    r#" { "a": [1, "two", null], "b": { "c": true } } "#,
+    r#"[<Element prop="value" />, <></>]"#,
+    r#"
+    <List title="Groceries" checked>
+        <Item>{"eggs"}</Item>
+        {<Item>milk</Item>}
+        {[1, <b />]}
+        Remember the bags.
+    </List>
+    "#,
+    "<><a/>text<b></b></>",
+    "<a b={{ \"c\": <d /> }} />",
+    "<p>< b</p>",
+    "<p>a <b> c</p>",
+    "<div><!-- c --><p>ok</p></div>",
+    "<p>1 < 2 <i>and</i> 3 > 2</p>",
+    "12.5",
+];
+
+#[test]
+fn pull_events() {
+    let events = Parser::new(r#"<a b="c" d>{[1]}text</a>"#)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            Event::StartElement {
+                name: String::from("a"),
+            },
+            Event::Prop {
+                name: String::from("b"),
+            },
+            Event::Str(String::from("c")),
+            Event::Prop {
+                name: String::from("d"),
+            },
+            Event::Boolean(true),
+            Event::StartExpression,
+            Event::StartArray,
+            Event::Num(1.0),
+            Event::EndArray,
+            Event::EndExpression,
+            Event::Text(String::from("text")),
+            Event::EndElement,
+        ]
+    );
+
+    let events = Parser::new(r#"{ "a": <></> }"#)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            Event::StartObject,
+            Event::Key(String::from("a")),
+            Event::StartFragment,
+            Event::EndFragment,
+            Event::EndObject,
+        ]
+    );
+}
+
+#[test]
+fn build_tree_from_events() {
+    let grammar = |input, options| {
+        jsxn::root_with_options::<VerboseError<&str>>(options)(input)
+            .map(|(_, value)| value)
+            .unwrap()
+    };
+    let options = ParserOptions {
+        roots: RootKinds::ALL,
+        ..ParserOptions::STRICT
+    };
+    for input in VALID_JSXN {
+        let tree = grammar(input, &options);
+        assert_eq!(
+            Parser::with_options(input, options).build(),
+            Ok(tree.clone()),
+            "{}",
+            input
+        );
+        if *input != "12.5" {
+            assert_eq!(jsxn::parse(input), Ok(tree), "{}", input);
+        }
+    }
+    assert!(Parser::new("12.5").build().is_err());
+    let relaxed = "{ a: [1, 2,], /* comment */ 'b': +1, }";
+    assert_eq!(
+        Parser::with_options(relaxed, ParserOptions::RELAXED).build(),
+        Ok(grammar(relaxed, &ParserOptions::RELAXED))
+    );
+}
+
+#[test]
+fn build_deep_trees() {
+    let depth = 5000;
+    let input = format!("{}x < y{}", "<a>".repeat(depth), "</a>".repeat(depth));
+    let mut tree = match jsxn::parse(&input).unwrap() {
+        JsxnValue::JsxValue(jsx) => jsx,
+        _ => panic!("expected an element"),
+    };
+    for _ in 0..depth {
+        tree = match tree {
+            JsxValue::JsxElement(element) => element.into_parts().2.remove(0),
+            _ => panic!("expected an element"),
+        };
+    }
+    assert_eq!(
+        tree,
+        JsxValue::JsonValue(JsonValue::Str(String::from("x < y")))
+    );
+}
+
+#[test]
+fn build_errors() {
+    let error = |input| jsxn::parse(input).unwrap_err().to_string();
+    assert_eq!(
+        error("<a>{}</a>"),
+        "invalid jsx expression at line 1, column 5"
+    );
+    assert_eq!(
+        error("<a>{[1, 2}</a>"),
+        "expected ']' in json array at line 1, column 10"
+    );
+    assert_eq!(
+        error("{\"a\" 1}"),
+        "expected ':' in json key value at line 1, column 6"
+    );
+    assert_eq!(
+        error("<a></b>"),
+        "invalid jsx element closing tag at line 1, column 4"
+    );
+    assert_eq!(
+        error("<a>text"),
+        "expected '<' in jsx element closing tag at line 1, column 8"
+    );
+}
+
+#[test]
+fn pull_event_errors() {
+    let mut parser = Parser::new("[1, }");
+    assert_eq!(parser.next(), Some(Ok(Event::StartArray)));
+    assert_eq!(parser.next(), Some(Ok(Event::Num(1.0))));
+    assert_eq!(parser.location().offset, 1);
+    assert_eq!(parser.next().unwrap().unwrap_err().location().offset, 4);
+    assert_eq!(parser.next(), None);
+
+    assert!(Parser::new("<a></b>").build().is_err());
+    assert!(Parser::new("[1] 2").build().is_err());
+    assert_eq!(
+        Parser::with_options(
+            "[1, 2, 3]",
+            ParserOptions {
+                limits: ParseLimits {
+                    max_nodes: Some(2),
+                    ..ParseLimits::default()
+                },
+                ..ParserOptions::STRICT
+            }
+        )
+        .build(),
+        Err(Error::TooManyNodes {
+            location: Location {
+                offset: 4,
+                line: 1,
+                column: 5,
+            },
+            limit: 2,
+        })
+    );
+    assert_eq!(
+        Parser::with_options(
+            "<a b={[[1]]} />",
+            ParserOptions {
+                limits: ParseLimits {
+                    max_depth: Some(3),
+                    ..ParseLimits::default()
+                },
+                ..ParserOptions::STRICT
+            }
+        )
+        .build(),
+        Err(Error::TooDeep {
+            location: Location {
+                offset: 7,
+                line: 1,
+                column: 8,
+            },
+            limit: 3,
+        })
+    );
+}
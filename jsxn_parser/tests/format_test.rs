@@ -0,0 +1,321 @@
+use ::jsxn::{
+    format::{self, FormatOptions},
+    json::JsonValue,
+    jsx::{JsxElement, JsxFragment, JsxValue},
+    jsxn::{self, JsxnValue},
+    options::{ParserOptions, RootKinds},
+};
+use pretty_assertions::assert_eq;
+use proptest::{
+    collection::{btree_map, vec},
+    prelude::*,
+};
+use std::collections::BTreeMap;
+
+#[test]
+fn format_elements() {
+    assert_eq!(
+        format::format(&jsxn::parse(r#"<p   class = "a"  hidden>Hello,   {"world"}</p>"#).unwrap()),
+        "<p class=\"a\" hidden>Hello,{\"world\"}</p>\n"
+    );
+    assert_eq!(
+        format::format(&jsxn::parse("<>  <br/><></></>").unwrap()),
+        "<><br /><></></>\n"
+    );
+    assert_eq!(
+        format::format_with_options(
+            &jsxn::parse(r#"<ul><li>one</li><li>two</li></ul>"#).unwrap(),
+            &FormatOptions {
+                line_width: 20,
+                ..FormatOptions::DEFAULT
+            }
+        ),
+        "<ul>\n  <li>one</li>\n  <li>two</li>\n</ul>\n"
+    );
+}
+
+#[test]
+fn format_json() {
+    assert_eq!(
+        format::format(&jsxn::parse(r#"{"b":[1,2.5,<a x={[]} />],"a":{}}"#).unwrap()),
+        "{\n  \"a\": {},\n  \"b\": [\n    1,\n    2.5,\n    <a x={[]} />\n  ]\n}\n"
+    );
+    assert_eq!(
+        format::format(
+            &jsxn::parse_with_options(
+                r#"<div style={{"color":"red"}}>{$user.name}</div>"#,
+                &ParserOptions::RELAXED
+            )
+            .unwrap()
+        ),
+        "<div\n  style={{\n    \"color\": \"red\"\n  }}\n>\n  {$user.name}\n</div>\n"
+    );
+}
+
+#[test]
+fn format_round_trip() {
+    let source = r#"
+        <List title="Groceries" count={1}>
+            <Item key="eggs" checked>eggs &amp; ham</Item>
+            <>{["milk", null, -0.5e1]}</>
+            {$user.name}
+            <p>A long paragraph of text that will not fit on the same line as the element around it</p>
+        </List>
+    "#;
+    let value = jsxn::parse_with_options(source, &ParserOptions::RELAXED).unwrap();
+    let formatted = format::format(&value);
+    assert_eq!(
+        jsxn::parse_with_options(&formatted, &ParserOptions::RELAXED).unwrap(),
+        value
+    );
+    assert_eq!(
+        format::format(&jsxn::parse_with_options(&formatted, &ParserOptions::RELAXED).unwrap()),
+        formatted
+    );
+}
+
+#[test]
+fn format_source_comments() {
+    let source = r#"
+// Groceries
+{ "items": [ // first
+    1,
+
+
+    2, /* two */
+  ],
+  "empty": [
+      // nothing yet
+  ],
+}
+"#;
+    let formatted =
+        format::format_source(source, &ParserOptions::RELAXED, &FormatOptions::DEFAULT).unwrap();
+    assert_eq!(
+        formatted,
+        concat!(
+            "// Groceries\n",
+            "{\n",
+            "  \"items\": [\n",
+            "    // first\n",
+            "    1,\n",
+            "\n",
+            "    2\n",
+            "    /* two */\n",
+            "  ],\n",
+            "  \"empty\": [\n",
+            "    // nothing yet\n",
+            "  ]\n",
+            "}\n",
+        )
+    );
+    assert_eq!(
+        format::format_source(&formatted, &ParserOptions::RELAXED, &FormatOptions::DEFAULT)
+            .unwrap(),
+        formatted
+    );
+
+    let source = r#"<Button
+  // the kind
+  kind="primary" /* why */
+  disabled>
+    <b>Save</b>
+
+
+    <i>Cancel</i>
+    </Button>"#;
+    let formatted =
+        format::format_source(source, &ParserOptions::RELAXED, &FormatOptions::DEFAULT).unwrap();
+    assert_eq!(
+        formatted,
+        concat!(
+            "<Button\n",
+            "  // the kind\n",
+            "  kind=\"primary\"\n",
+            "  /* why */\n",
+            "  disabled\n",
+            ">\n",
+            "  <b>Save</b>\n",
+            "\n",
+            "  <i>Cancel</i>\n",
+            "</Button>\n",
+        )
+    );
+    assert_eq!(
+        format::format_source(&formatted, &ParserOptions::RELAXED, &FormatOptions::DEFAULT)
+            .unwrap(),
+        formatted
+    );
+}
+
+#[test]
+fn format_wrapped_tags() {
+    let options = FormatOptions {
+        line_width: 30,
+        ..FormatOptions::DEFAULT
+    };
+    assert_eq!(
+        format::format_source(
+            r#"<Input name="email" label="Email address" required />"#,
+            &ParserOptions::STRICT,
+            &options
+        )
+        .unwrap(),
+        "<Input\n  name=\"email\"\n  label=\"Email address\"\n  required\n/>\n"
+    );
+    assert_eq!(
+        format::format_source(
+            r#"<p lang="en">A paragraph of text</p>"#,
+            &ParserOptions::STRICT,
+            &options
+        )
+        .unwrap(),
+        "<p lang=\"en\">\n  A paragraph of text\n</p>\n"
+    );
+    assert_eq!(
+        format::format_source(
+            "<p>1 < 2 <b>and</b> 3</p>",
+            &ParserOptions::STRICT,
+            &FormatOptions::DEFAULT
+        )
+        .unwrap(),
+        "<p>1 < 2<b>and</b>3</p>\n"
+    );
+    assert!(format::format_source("<p>", &ParserOptions::STRICT, &FormatOptions::DEFAULT).is_err());
+}
+
+fn element(r#type: String, props: BTreeMap<String, JsxValue>, children: Vec<JsxValue>) -> JsxValue {
+    // Adjacent text is parsed as a single child
+    let mut merged: Vec<JsxValue> = vec![];
+    for child in children {
+        match (merged.last_mut(), child) {
+            (
+                Some(JsxValue::JsonValue(JsonValue::Str(text))),
+                JsxValue::JsonValue(JsonValue::Str(next)),
+            ) => {
+                text.push(' ');
+                text.push_str(&next);
+            }
+            (_, child) => merged.push(child),
+        }
+    }
+    match r#type.as_str() {
+        "" => JsxValue::JsxFragment(JsxFragment::new(merged)),
+        _ => JsxValue::JsxElement(JsxElement::new(r#type, props, merged)),
+    }
+}
+
+/// An expression holding a JSON value is parsed as the JSX value it holds
+fn expression(json: JsonValue) -> JsxValue {
+    JsxValue::JsxExpression(Box::new(match json {
+        JsonValue::JsxValue(jsx) => *jsx,
+        json => JsxValue::JsonValue(json),
+    }))
+}
+
+fn name() -> impl Strategy<Value = String> {
+    "[a-z][a-zA-Z0-9]{0,6}"
+}
+
+fn json(jsx: BoxedStrategy<JsxValue>) -> BoxedStrategy<JsonValue> {
+    let leaf = prop_oneof![
+        "[a-zA-Z0-9 ]{0,12}".prop_map(JsonValue::Str),
+        any::<i32>().prop_map(|num| JsonValue::Num(f64::from(num))),
+        (-1000..1000).prop_map(|num| JsonValue::Num(f64::from(num) / 8.0)),
+        any::<bool>().prop_map(JsonValue::Boolean),
+        Just(JsonValue::Null),
+    ];
+    leaf.prop_recursive(2, 12, 3, move |json| {
+        prop_oneof![
+            vec(json.clone(), 0..3).prop_map(JsonValue::Array),
+            btree_map("[a-z0-9 ]{0,6}", json, 0..3).prop_map(JsonValue::Object),
+            jsx.clone()
+                .prop_map(|jsx| JsonValue::JsxValue(Box::new(jsx))),
+        ]
+    })
+    .boxed()
+}
+
+fn jsx() -> BoxedStrategy<JsxValue> {
+    let leaf = prop_oneof![
+        name().prop_map(|name| element(name, BTreeMap::new(), vec![])),
+        Just(element(String::new(), BTreeMap::new(), vec![])),
+    ];
+    leaf.prop_recursive(3, 24, 4, |jsx| {
+        let json = json(jsx.clone());
+        let child = prop_oneof![
+            "[a-zA-Z0-9]{1,6}( [a-zA-Z0-9]{1,6}){0,3}"
+                .prop_map(|text| JsxValue::JsonValue(JsonValue::Str(text))),
+            jsx,
+            json.clone().prop_map(expression),
+        ];
+        let prop = prop_oneof![
+            "[a-zA-Z0-9 ]{0,12}".prop_map(|s| JsxValue::JsonValue(JsonValue::Str(s))),
+            Just(JsxValue::JsonValue(JsonValue::Boolean(true))),
+            json.prop_map(expression),
+        ];
+        prop_oneof![
+            (
+                name(),
+                btree_map(name(), prop, 0..4),
+                vec(child.clone(), 0..5)
+            )
+                .prop_map(|(name, props, children)| element(name, props, children)),
+            vec(child, 0..5).prop_map(|children| element(String::new(), BTreeMap::new(), children)),
+        ]
+    })
+    .boxed()
+}
+
+fn jsxn() -> impl Strategy<Value = JsxnValue> {
+    prop_oneof![
+        jsx().prop_map(JsxnValue::JsxValue),
+        json(jsx()).prop_map(|json| match json {
+            JsonValue::JsxValue(jsx) => JsxnValue::JsxValue(*jsx),
+            json => JsxnValue::JsonValue(json),
+        }),
+    ]
+}
+
+/// The standard grammar with the scalar roots that `jsxn()` generates
+const SCALAR_ROOTS: ParserOptions = ParserOptions {
+    roots: RootKinds::ALL,
+    ..ParserOptions::STRICT
+};
+
+fn options() -> impl Strategy<Value = FormatOptions> {
+    (1usize..5, 10usize..100).prop_map(|(indent, line_width)| FormatOptions { indent, line_width })
+}
+
+proptest! {
+    #[test]
+    fn format_parses_back(value in jsxn(), options in options()) {
+        let formatted = format::format_with_options(&value, &options);
+        prop_assert_eq!(jsxn::parse_with_options(&formatted, &SCALAR_ROOTS).unwrap(), value);
+    }
+
+    #[test]
+    fn format_is_idempotent(value in jsxn(), options in options(), blank_lines in vec(any::<bool>(), 0..64)) {
+        let formatted = format::format_with_options(&value, &options);
+        prop_assert_eq!(
+            &format::format_source(&formatted, &SCALAR_ROOTS, &options).unwrap(),
+            &formatted
+        );
+
+        // Blank lines are kept, but formatting the output again is a no-op
+        let mut spaced = String::new();
+        for (line, blank) in formatted.lines().zip(blank_lines.iter().chain(std::iter::repeat(&false))) {
+            spaced.push_str(line);
+            spaced.push('\n');
+            if *blank {
+                spaced.push('\n');
+            }
+        }
+        let reformatted = format::format_source(&spaced, &SCALAR_ROOTS, &options).unwrap();
+        prop_assert_eq!(jsxn::parse_with_options(&reformatted, &SCALAR_ROOTS).unwrap(), value);
+        prop_assert_eq!(
+            &format::format_source(&reformatted, &SCALAR_ROOTS, &options).unwrap(),
+            &reformatted
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use ::jsxn::{
+    from_html, html,
+    json::JsonValue,
+    jsx::{JsxElement, JsxFragment, JsxValue},
+    jsxn::{self, JsxnValue},
+};
+use pretty_assertions::assert_eq;
+use std::collections::BTreeMap;
+
+fn jsx(input: &str) -> JsxValue {
+    match jsxn::parse(input).unwrap() {
+        JsxnValue::JsxValue(jsx) => jsx,
+        json => panic!("not JSX: {:?}", json),
+    }
+}
+
+#[test]
+fn convert_attributes() {
+    assert_eq!(
+        from_html::convert(
+            r#"<label class="name" for="name" TABINDEX=0 data-id='7' onclick="go()">Name</label>"#
+        ),
+        jsx(r#"<label className="name" htmlFor="name" tabIndex="0" data-id="7">Name</label>"#)
+    );
+    assert_eq!(
+        html::render(&from_html::convert(
+            r#"<p data-id="1" aria-label="Close">x</p>"#
+        )),
+        r#"<p aria-label="Close" data-id="1">x</p>"#
+    );
+}
+
+#[test]
+fn convert_boolean_attributes() {
+    assert_eq!(
+        from_html::convert(r#"<input type="checkbox" checked="checked" disabled data-on>"#),
+        jsx(r#"<input type="checkbox" checked disabled data-on />"#)
+    );
+}
+
+#[test]
+fn convert_style() {
+    let mut style = BTreeMap::new();
+    style.insert(
+        String::from("backgroundImage"),
+        JsonValue::Str(String::from("url(\\\"a;b.png\\\")")),
+    );
+    style.insert(String::from("color"), JsonValue::Str(String::from("red")));
+    style.insert(String::from("msFlex"), JsonValue::Str(String::from("1 0")));
+    style.insert(
+        String::from("WebkitTransition"),
+        JsonValue::Str(String::from("opacity 1s")),
+    );
+    style.insert(String::from("zIndex"), JsonValue::Num(10.0));
+    let mut props = BTreeMap::new();
+    props.insert(
+        String::from("style"),
+        JsxValue::JsxExpression(Box::new(JsxValue::JsonValue(JsonValue::Object(style)))),
+    );
+    assert_eq!(
+        from_html::convert(
+            r#"<div style='color: red; Z-INDEX: 10; background-image: url("a;b.png");
+                -webkit-transition: opacity 1s; -ms-flex: 1 0;'></div>"#
+        ),
+        JsxValue::JsxElement(JsxElement::new(String::from("div"), props, vec![]))
+    );
+}
+
+#[test]
+fn convert_entities() {
+    assert_eq!(
+        from_html::convert(
+            r#"<p title="&quot;a&quot; &amp; b">Fish &amp; chips &copy;&#169;&#xA9; &bogus; 5&nbsp;km</p>"#
+        ),
+        jsx("<p title=\"\\\"a\\\" & b\">Fish & chips ©©© &bogus; 5\u{a0}km</p>")
+    );
+    assert_eq!(
+        from_html::convert("<code>&lt;div&gt; {}</code>"),
+        jsx(r#"<code>{"<div> {}"}</code>"#)
+    );
+    let cafe = from_html::convert("<p>caf&eacute; &Agrave; la cr&egrave;me, &ccedil;a &alpha;</p>");
+    assert_eq!(cafe, jsx("<p>café À la crème, ça α</p>"));
+    assert_eq!(html::render(&cafe), "<p>café À la crème, ça α</p>");
+}
+
+#[test]
+fn convert_void_and_self_closing_elements() {
+    assert_eq!(
+        from_html::convert(r#"<p>a<br>b<img src="x.png"/><span/>c</p>"#),
+        jsx(r#"<p>a<br />b<img src="x.png" /><span />c</p>"#)
+    );
+}
+
+#[test]
+fn convert_whitespace() {
+    assert_eq!(
+        from_html::convert(
+            "<div>\n  <p>Hello,   <b>world</b> and <i>you</i>!</p>\n  <p> <b>a</b> <b>b</b> </p>\n</div>"
+        ),
+        jsx(r#"
+            <div>
+                <p>Hello,{" "}<b>world</b>{" "}and{" "}<i>you</i>!</p>
+                <p><b>a</b>{" "}<b>b</b></p>
+            </div>
+        "#)
+    );
+}
+
+#[test]
+fn convert_preformatted_text() {
+    assert_eq!(
+        from_html::convert("<pre>\n  one\n  two</pre><textarea>a &amp; b</textarea>"),
+        jsx(r#"<><pre>{"  one\n  two"}</pre><textarea>a & b</textarea></>"#)
+    );
+    assert_eq!(
+        from_html::convert("<style>p { color: red }</style>"),
+        jsx(r#"<style>{"p { color: red }"}</style>"#)
+    );
+}
+
+#[test]
+fn convert_documents() {
+    assert_eq!(
+        from_html::convert(
+            "<!DOCTYPE html>\n<!-- page -->\n<HTML><Body><ul><li>one<li>two</ul></span></body></html>"
+        ),
+        jsx("<html><body><ul><li>one</li><li>two</li></ul></body></html>")
+    );
+    assert_eq!(
+        from_html::convert("<p>unclosed<div>"),
+        jsx("<p>unclosed<div></div></p>")
+    );
+    assert_eq!(
+        from_html::convert("one <b>two</b>"),
+        JsxValue::JsxFragment(JsxFragment::new(vec![
+            JsxValue::JsonValue(JsonValue::Str(String::from("one"))),
+            JsxValue::JsxExpression(Box::new(JsxValue::JsonValue(JsonValue::Str(String::from(
+                " "
+            ))))),
+            jsx("<b>two</b>"),
+        ]))
+    );
+}
+
+#[test]
+fn convert_svg() {
+    assert_eq!(
+        from_html::convert(
+            r##"<svg viewBox="0 0 1 1"><linearGradient id="g"/><path stroke-width="2" xlink:href="#g"/></svg>"##
+        ),
+        jsx(
+            r##"<svg viewBox="0 0 1 1"><linearGradient id="g" /><path strokeWidth="2" xlinkHref="#g" /></svg>"##
+        )
+    );
+}
+
+#[test]
+fn convert_to_source() {
+    let source = from_html::convert_to_source(
+        r#"<div class="card" style="margin: 0"><p>1 &lt; 2 <b>yes</b></p></div>"#,
+    );
+    assert_eq!(
+        source,
+        concat!(
+            "<div\n",
+            "  className=\"card\"\n",
+            "  style={{\n",
+            "    \"margin\": 0\n",
+            "  }}\n",
+            ">\n",
+            "  <p>{\"1 < 2\"}{\" \"}<b>yes</b></p>\n",
+            "</div>\n",
+        )
+    );
+    assert_eq!(
+        jsxn::parse(&source).unwrap(),
+        JsxnValue::JsxValue(from_html::convert(
+            r#"<div class="card" style="margin: 0"><p>1 &lt; 2 <b>yes</b></p></div>"#
+        ))
+    );
+}
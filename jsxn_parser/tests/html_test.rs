@@ -0,0 +1,180 @@
+use ::jsxn::{html, json::JsonValue, jsx, jsxn};
+use pretty_assertions::assert_eq;
+use std::collections::BTreeMap;
+
+fn render(input: &str) -> String {
+    html::render_jsxn(&jsxn::parse(input).unwrap())
+}
+
+#[test]
+fn render_elements() {
+    assert_eq!(
+        render(
+            r#"
+            <div className="card" id={"main"} tabIndex={0}>
+                <label htmlFor="name">Name</label>
+                <input id="name" disabled value="a &quot;b&quot;" />
+                <br></br>
+                <Hello signOff />
+            </div>
+            "#
+        ),
+        concat!(
+            r#"<div class="card" id="main" tabIndex="0">"#,
+            r#"<label for="name">Name</label>"#,
+            r#"<input disabled id="name" value="a &amp;quot;b&amp;quot;"/>"#,
+            "<br/>",
+            "<Hello signOff></Hello>",
+            "</div>",
+        )
+    );
+}
+
+#[test]
+fn render_children() {
+    assert_eq!(
+        render(
+            r#"
+            <ul>
+                <>
+                    <li>{"one"}</li>
+                    <li>{2}</li>
+                </>
+                {[<li>three</li>, "four & \"five\""]}
+                {null}{true}{false}
+                a < b
+            </ul>
+            "#
+        ),
+        concat!(
+            "<ul>",
+            "<li>one</li>",
+            "<li>2</li>",
+            "<li>three</li>",
+            "four &amp; &quot;five&quot;",
+            "a &lt; b",
+            "</ul>",
+        )
+    );
+    assert_eq!(render(r#"["a", 1.5, null]"#), "a1.5");
+}
+
+#[test]
+fn render_numbers() {
+    for (input, html) in &[
+        ("1e21", "1e+21"),
+        ("1e20", "100000000000000000000"),
+        ("-12.5", "-12.5"),
+        ("-0", "0"),
+        ("0.000001", "0.000001"),
+        ("1.25e-7", "1.25e-7"),
+        ("0.1", "0.1"),
+        ("123456789012", "123456789012"),
+    ] {
+        assert_eq!(render(&format!("[{}]", input)), *html, "{}", input);
+    }
+}
+
+#[test]
+fn render_invalid_names() {
+    let element = |r#type: &str, prop: &str| {
+        let mut props = BTreeMap::new();
+        props.insert(
+            String::from(prop),
+            jsx::JsxValue::JsonValue(JsonValue::Str(String::from("x"))),
+        );
+        jsx::JsxValue::JsxElement(jsx::JsxElement::new(String::from(r#type), props, vec![]))
+    };
+    assert_eq!(
+        html::render(&element("div", "x onload=alert(1)")),
+        "<div></div>"
+    );
+    assert_eq!(
+        html::render(&element("my-element", "data-id")),
+        r#"<my-element data-id="x"></my-element>"#
+    );
+    assert_eq!(html::render(&element("script><script", "id")), "");
+}
+
+#[test]
+fn render_style_objects() {
+    assert_eq!(
+        render(
+            r#"<p style={{
+                "backgroundColor": "red",
+                "fontSize": 12,
+                "lineHeight": 1.5,
+                "margin": 0,
+                "WebkitTransition": "none",
+                "msFilter": "none",
+                "--accent": "blue",
+                "color": null
+            }} />"#
+        ),
+        concat!(
+            r#"<p style="--accent:blue;-webkit-transition:none;background-color:red;"#,
+            r#"font-size:12px;line-height:1.5;margin:0;-ms-filter:none"></p>"#,
+        )
+    );
+}
+
+#[test]
+fn render_pretty() {
+    let jsxn = jsxn::parse(
+        r#"<html><body><h1>Title</h1><ul><li>one</li><li>{"two"}</li></ul><br /></body></html>"#,
+    )
+    .unwrap();
+    assert_eq!(
+        html::render_jsxn_with_options(&jsxn, &html::RenderOptions::PRETTY),
+        r#"<html>
+  <body>
+    <h1>Title</h1>
+    <ul>
+      <li>one</li>
+      <li>two</li>
+    </ul>
+    <br/>
+  </body>
+</html>"#
+    );
+}
+
+/// A writer that accepts a fixed number of bytes
+struct Limited(usize);
+
+impl std::io::Write for Limited {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.len() > self.0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "full"));
+        }
+        self.0 -= buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn render_to_writers() {
+    let jsx = match jsxn::parse(r#"<p className="note">a &amp; b</p>"#).unwrap() {
+        jsxn::JsxnValue::JsxValue(jsx) => jsx,
+        jsxn => panic!("unexpected value {:?}", jsxn),
+    };
+    let mut bytes = vec![];
+    html::render_to_writer(&jsx, &mut bytes).unwrap();
+    assert_eq!(
+        String::from_utf8(bytes).unwrap(),
+        r#"<p class="note">a &amp;amp; b</p>"#
+    );
+
+    let mut string = String::new();
+    html::render_to_fmt_writer(&jsx, &mut string).unwrap();
+    assert_eq!(string, html::render(&jsx));
+
+    assert_eq!(
+        html::render_to_writer(&jsx, Limited(8)).unwrap_err().kind(),
+        std::io::ErrorKind::WriteZero
+    );
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::vec_init_then_push)]
+
 use jsxn::{json, jsx};
 use nom::{
     error::{convert_error, ErrorKind, VerboseError},
@@ -97,6 +99,33 @@ fn parse_invalid_json() {
     )
 }
 
+#[test]
+fn parse_numbers() {
+    let num = |input| match jsxn::jsxn::parse(input) {
+        Ok(jsxn::jsxn::JsxnValue::JsonValue(json::JsonValue::Array(array))) => match array[..] {
+            [json::JsonValue::Num(num)] => Some(num),
+            _ => panic!("expected a single number"),
+        },
+        _ => None,
+    };
+    assert_eq!(num("[0]"), Some(0.0));
+    assert_eq!(num("[-12.5e-1]"), Some(-1.25));
+    assert_eq!(num("[1E+2]"), Some(100.0));
+    for input in &[
+        "[NaN]",
+        "[inf]",
+        "[Infinity]",
+        "[+1]",
+        "[.5]",
+        "[1.]",
+        "[01]",
+        "[-]",
+        "[1e]",
+    ] {
+        assert_eq!(num(input), None, "{}", input);
+    }
+}
+
 #[test]
 fn parse_invalid_json_verbose_trace() {
     if let Err(Err::Error(e)) | Err(Err::Failure(e)) =
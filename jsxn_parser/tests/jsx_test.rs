@@ -1,3 +1,5 @@
+#![allow(clippy::vec_init_then_push)]
+
 use jsxn::{json, jsx};
 use nom::error::ErrorKind;
 use pretty_assertions::assert_eq;
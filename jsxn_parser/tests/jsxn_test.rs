@@ -1,4 +1,7 @@
-use ::jsxn::{json, jsx, jsxn};
+use ::jsxn::{
+    json, jsx, jsxn,
+    options::{ParserOptions, RootKinds},
+};
 use nom::error::ErrorKind;
 use pretty_assertions::assert_eq;
 use std::collections::BTreeMap;
@@ -47,6 +50,51 @@ fn parse_valid_jsxn() {
     );
 }
 
+#[test]
+fn parse_scalar_jsxn() {
+    assert!(jsxn::root::<(&str, ErrorKind)>("42").is_err());
+
+    let options = ParserOptions {
+        roots: RootKinds::ALL,
+        ..ParserOptions::STRICT
+    };
+    let root = jsxn::root_with_options::<(&str, ErrorKind)>(&options);
+    assert_eq!(
+        root(" \"hello\" "),
+        Ok((
+            "",
+            jsxn::JsxnValue::JsonValue(json::JsonValue::Str(String::from("hello")))
+        ))
+    );
+    assert_eq!(
+        root("42"),
+        Ok(("", jsxn::JsxnValue::JsonValue(json::JsonValue::Num(42.0))))
+    );
+    assert_eq!(
+        root("null"),
+        Ok(("", jsxn::JsxnValue::JsonValue(json::JsonValue::Null)))
+    );
+    assert_eq!(
+        root("true <Element />"),
+        Ok((
+            "<Element />",
+            jsxn::JsxnValue::JsonValue(json::JsonValue::Boolean(true))
+        ))
+    );
+    assert_eq!(
+        root("<true />"),
+        Ok((
+            "",
+            jsxn::JsxnValue::JsxValue(jsx::JsxValue::JsxElement(jsx::JsxElement::new(
+                String::from("true"),
+                BTreeMap::new(),
+                vec![],
+            )))
+        ))
+    );
+    assert!(jsxn::parse("42 <Element />").is_err());
+}
+
 #[test]
 fn serialize_jsxn() {
     assert_eq!(
@@ -72,3 +120,38 @@ fn serialize_jsxn() {
         )
     )
 }
+
+#[test]
+fn parse_multiple_documents() {
+    let input = "{\"a\": 1}\n[2, }\n<Element />\n\"three\" 4";
+    let mut documents = jsxn::parse_documents_with_options(input, &ParserOptions::RELAXED);
+    assert_eq!(
+        documents.next(),
+        Some(Ok(jsxn::JsxnValue::JsonValue(json::JsonValue::Object({
+            let mut object = BTreeMap::new();
+            object.insert(String::from("a"), json::JsonValue::Num(1.0));
+            object
+        }))))
+    );
+    assert_eq!(documents.byte_offset(), 9);
+    let error = documents.next().unwrap().unwrap_err();
+    assert_eq!(error.location().line, 2);
+    assert_eq!(
+        documents.next(),
+        Some(Ok(jsxn::JsxnValue::JsxValue(jsx::JsxValue::JsxElement(
+            jsx::JsxElement::new(String::from("Element"), BTreeMap::new(), vec![])
+        ))))
+    );
+    assert_eq!(
+        documents.next(),
+        Some(Ok(jsxn::JsxnValue::JsonValue(json::JsonValue::Str(
+            String::from("three")
+        ))))
+    );
+    assert_eq!(
+        documents.next(),
+        Some(Ok(jsxn::JsxnValue::JsonValue(json::JsonValue::Num(4.0))))
+    );
+    assert_eq!(documents.next(), None);
+    assert_eq!(jsxn::parse_documents(" \n ").count(), 0);
+}
@@ -0,0 +1,197 @@
+use ::jsxn::{
+    error::{Error, Location},
+    jsxn,
+    limits::ParseLimits,
+};
+use pretty_assertions::assert_eq;
+
+const VALID_JSXN: &str = r#"
+    <List title="Groceries">
+        <Item checked />
+        {["eggs", "milk"]}
+        Remember the bags.
+    </List>
+"#;
+
+#[test]
+fn parse_within_limits() {
+    assert_eq!(
+        jsxn::parse_with_limits(
+            VALID_JSXN,
+            &ParseLimits {
+                max_input_bytes: Some(VALID_JSXN.len()),
+                max_nodes: Some(9),
+                max_string_length: Some(18),
+                max_props: Some(1),
+                max_children: Some(3),
+                max_depth: Some(3),
+            }
+        ),
+        jsxn::parse(VALID_JSXN)
+    );
+}
+
+#[test]
+fn limit_input_bytes() {
+    assert_eq!(
+        jsxn::parse_with_limits(
+            VALID_JSXN,
+            &ParseLimits {
+                max_input_bytes: Some(10),
+                ..ParseLimits::default()
+            }
+        ),
+        Err(Error::InputTooLarge {
+            location: Location {
+                offset: 10,
+                line: 2,
+                column: 10,
+            },
+            limit: 10,
+        })
+    );
+}
+
+#[test]
+fn limit_nodes() {
+    assert_eq!(
+        jsxn::parse_with_limits(
+            VALID_JSXN,
+            &ParseLimits {
+                max_nodes: Some(8),
+                ..ParseLimits::default()
+            }
+        ),
+        Err(Error::TooManyNodes {
+            location: Location {
+                offset: 90,
+                line: 5,
+                column: 9,
+            },
+            limit: 8,
+        })
+    );
+}
+
+#[test]
+fn limit_string_length() {
+    assert_eq!(
+        jsxn::parse_with_limits(
+            VALID_JSXN,
+            &ParseLimits {
+                max_string_length: Some(8),
+                ..ParseLimits::default()
+            }
+        ),
+        Err(Error::StringTooLong {
+            location: Location {
+                offset: 17,
+                line: 2,
+                column: 17,
+            },
+            limit: 8,
+        })
+    );
+}
+
+#[test]
+fn limit_props_and_children() {
+    assert_eq!(
+        jsxn::parse_with_limits(
+            r#"<Item a b c />"#,
+            &ParseLimits {
+                max_props: Some(2),
+                ..ParseLimits::default()
+            }
+        ),
+        Err(Error::TooManyProps {
+            location: Location {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            limit: 2,
+        })
+    );
+    assert_eq!(
+        jsxn::parse_with_limits(
+            VALID_JSXN,
+            &ParseLimits {
+                max_children: Some(2),
+                ..ParseLimits::default()
+            }
+        ),
+        Err(Error::TooManyChildren {
+            location: Location {
+                offset: 5,
+                line: 2,
+                column: 5,
+            },
+            limit: 2,
+        })
+    );
+}
+
+#[test]
+fn limit_depth() {
+    let limits = ParseLimits {
+        max_depth: Some(64),
+        ..ParseLimits::default()
+    };
+    let deep = "[".repeat(10000) + &"]".repeat(10000);
+    assert_eq!(
+        jsxn::parse_with_limits(&deep, &limits),
+        Err(Error::TooDeep {
+            location: Location {
+                offset: 64,
+                line: 1,
+                column: 65,
+            },
+            limit: 64,
+        })
+    );
+
+    let nested = "<a>{[<>{{\"b\": <c d={<e />} />}}</>]}</a>";
+    assert!(jsxn::parse_with_limits(
+        nested,
+        &ParseLimits {
+            max_depth: Some(9),
+            ..ParseLimits::default()
+        }
+    )
+    .is_ok());
+    assert_eq!(
+        jsxn::parse_with_limits(
+            nested,
+            &ParseLimits {
+                max_depth: Some(8),
+                ..ParseLimits::default()
+            }
+        ),
+        Err(Error::TooDeep {
+            location: Location {
+                offset: 20,
+                line: 1,
+                column: 21,
+            },
+            limit: 8,
+        })
+    );
+}
+
+#[test]
+fn report_syntax_error_location() {
+    let error = jsxn::parse("{ \"a\": 1,, }").unwrap_err();
+    assert_eq!(
+        error.location(),
+        Location {
+            offset: 8,
+            line: 1,
+            column: 9,
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "expected '}' in json object at line 1, column 9"
+    );
+}
@@ -0,0 +1,200 @@
+use ::jsxn::{
+    json, jsx, jsxn,
+    options::{ParserOptions, RootKinds},
+};
+use nom::error::ErrorKind;
+use pretty_assertions::assert_eq;
+use std::collections::BTreeMap;
+
+const RELAXED_JSON: &str = r#"
+    // Line comments
+    {
+        /* Block comments */
+        "a": [1, 2,],
+        "b": { "c": null, },
+    }
+"#;
+
+#[test]
+fn parse_relaxed_json() {
+    assert_eq!(
+        json::root_with_options::<(&str, ErrorKind)>(&ParserOptions::RELAXED)(RELAXED_JSON),
+        Ok((
+            "",
+            json::JsonValue::Object({
+                let mut object = BTreeMap::new();
+                object.insert(
+                    String::from("a"),
+                    json::JsonValue::Array(vec![
+                        json::JsonValue::Num(1.0),
+                        json::JsonValue::Num(2.0),
+                    ]),
+                );
+                object.insert(
+                    String::from("b"),
+                    json::JsonValue::Object({
+                        let mut object = BTreeMap::new();
+                        object.insert(String::from("c"), json::JsonValue::Null);
+                        object
+                    }),
+                );
+                object
+            })
+        ))
+    );
+    assert!(jsxn::parse_with_options(RELAXED_JSON, &ParserOptions::STRICT).is_err());
+    assert!(jsxn::parse_with_options("[,]", &ParserOptions::RELAXED).is_err());
+    assert!(jsxn::parse_with_options("[1,,]", &ParserOptions::RELAXED).is_err());
+}
+
+#[test]
+fn comments_are_text_in_jsx_children() {
+    assert_eq!(
+        jsx::root_with_options::<(&str, ErrorKind)>(&ParserOptions::RELAXED)(
+            "<a /* b */ c>// d</a>"
+        ),
+        Ok((
+            "",
+            jsx::JsxValue::JsxElement(jsx::JsxElement::new(
+                String::from("a"),
+                {
+                    let mut props = BTreeMap::new();
+                    props.insert(
+                        String::from("c"),
+                        jsx::JsxValue::JsonValue(json::JsonValue::Boolean(true)),
+                    );
+                    props
+                },
+                vec![jsx::JsxValue::JsonValue(json::JsonValue::Str(
+                    String::from("// d")
+                ))],
+            ))
+        ))
+    );
+}
+
+#[test]
+fn disable_mixing_json_and_jsx() {
+    let options = ParserOptions {
+        jsx_in_json: false,
+        ..ParserOptions::STRICT
+    };
+    assert!(jsxn::parse_with_options(r#"{ "a": <b /> }"#, &options).is_err());
+    assert!(jsxn::parse_with_options("<a>{<b />}</a>", &options).is_ok());
+
+    let options = ParserOptions {
+        json_in_jsx: false,
+        ..ParserOptions::STRICT
+    };
+    assert!(jsxn::parse_with_options("<a>{1}</a>", &options).is_err());
+    assert!(jsxn::parse_with_options("<a>{ <b /> }</a>", &options).is_ok());
+    assert!(jsxn::parse_with_options(r#"{ "a": <b /> }"#, &options).is_ok());
+}
+
+#[test]
+fn restrict_root_kinds() {
+    let options = ParserOptions {
+        roots: RootKinds {
+            object: true,
+            array: false,
+            scalar: false,
+            element: true,
+            fragment: false,
+        },
+        ..ParserOptions::STRICT
+    };
+    assert!(jsxn::parse_with_options("{}", &options).is_ok());
+    assert!(jsxn::parse_with_options("[]", &options).is_err());
+    assert!(jsxn::parse_with_options("<a />", &options).is_ok());
+    assert!(jsxn::parse_with_options("<></>", &options).is_err());
+    assert!(jsxn::parse_with_options("true", &options).is_err());
+
+    let options = ParserOptions {
+        roots: RootKinds::COMPOUND,
+        ..ParserOptions::STRICT
+    };
+    assert!(jsxn::parse_with_options("[]", &options).is_ok());
+    assert!(jsxn::parse_with_options("\"hello\"", &options).is_err());
+}
+
+const JSON5: &str = r#"
+    {
+        unquoted: 'single \'quoted\' "string"',
+        $multi_line: "line \
+continuation",
+        hex: 0xFF,
+        positive: +1.5,
+        infinities: [Infinity, -Infinity],
+        element: <Element prop='value' />,
+    }
+"#;
+
+#[test]
+fn parse_json5() {
+    let options = ParserOptions::RELAXED;
+    let (remainder, json) = json::root_with_options::<(&str, ErrorKind)>(&options)(JSON5).unwrap();
+    assert_eq!(remainder, "");
+    assert_eq!(
+        json,
+        json::JsonValue::Object({
+            let mut object = BTreeMap::new();
+            object.insert(
+                String::from("unquoted"),
+                json::JsonValue::Str(String::from(r#"single 'quoted' \"string\""#)),
+            );
+            object.insert(
+                String::from("$multi_line"),
+                json::JsonValue::Str(String::from("line continuation")),
+            );
+            object.insert(String::from("hex"), json::JsonValue::Num(255.0));
+            object.insert(String::from("positive"), json::JsonValue::Num(1.5));
+            object.insert(
+                String::from("infinities"),
+                json::JsonValue::Array(vec![
+                    json::JsonValue::Num(f64::INFINITY),
+                    json::JsonValue::Num(f64::NEG_INFINITY),
+                ]),
+            );
+            object.insert(
+                String::from("element"),
+                json::JsonValue::JsxValue(Box::new(jsx::JsxValue::JsxElement(
+                    jsx::JsxElement::new(
+                        String::from("Element"),
+                        {
+                            let mut props = BTreeMap::new();
+                            props.insert(
+                                String::from("prop"),
+                                jsx::JsxValue::JsonValue(json::JsonValue::Str(String::from(
+                                    "value",
+                                ))),
+                            );
+                            props
+                        },
+                        vec![],
+                    ),
+                ))),
+            );
+            object
+        })
+    );
+
+    match jsxn::parse_with_options("[NaN]", &options) {
+        Ok(jsxn::JsxnValue::JsonValue(json::JsonValue::Array(array))) => match array[..] {
+            [json::JsonValue::Num(num)] => assert!(num.is_nan()),
+            _ => panic!("expected a single number"),
+        },
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert_eq!(
+        jsxn::parse_with_options("[.5, 1., -.5e1, +0x10]", &options),
+        Ok(jsxn::JsxnValue::JsonValue(json::JsonValue::Array(vec![
+            json::JsonValue::Num(0.5),
+            json::JsonValue::Num(1.0),
+            json::JsonValue::Num(-5.0),
+            json::JsonValue::Num(16.0),
+        ])))
+    );
+    assert!(jsxn::parse_with_options("[01]", &options).is_err());
+    assert!(jsxn::parse_with_options(JSON5, &ParserOptions::STRICT).is_err());
+    assert!(jsxn::parse_with_options("['unterminated]", &options).is_err());
+}
@@ -0,0 +1,87 @@
+use ::jsxn::{
+    jsxn,
+    profile::{self, Profile},
+};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+const VALID_JSXN: &str = r#"
+    <ul key="list" ref={"items"} className="items">
+        <li key={1}>eggs</li>
+        <>{"milk"}</>
+    </ul>
+"#;
+
+#[test]
+fn serialize_react() {
+    let value = jsxn::parse(VALID_JSXN).unwrap();
+    assert_eq!(
+        serde_json::to_value(profile::profiled(&value, Profile::React)).unwrap(),
+        json!({
+            "$$typeof": "react.element",
+            "type": "ul",
+            "key": "list",
+            "ref": "items",
+            "props": {
+                "className": "items",
+                "children": [
+                    {
+                        "$$typeof": "react.element",
+                        "type": "li",
+                        "key": "1",
+                        "ref": null,
+                        "props": { "children": "eggs" },
+                    },
+                    {
+                        "$$typeof": "react.element",
+                        "type": "react.fragment",
+                        "key": null,
+                        "ref": null,
+                        "props": { "children": "milk" },
+                    },
+                ],
+            },
+        })
+    );
+}
+
+#[test]
+fn serialize_jsxn() {
+    let value = jsxn::parse(VALID_JSXN).unwrap();
+    assert_eq!(
+        serde_json::to_value(profile::profiled(&value, Profile::Jsxn)).unwrap(),
+        json!({
+            "type": "ul",
+            "props": {
+                "key": "list",
+                "ref": "items",
+                "className": "items",
+                "children": [
+                    { "type": "li", "props": { "key": 1.0, "children": "eggs" } },
+                    { "type": "Fragment", "props": { "children": "milk" } },
+                ],
+            },
+        })
+    );
+}
+
+#[test]
+fn serialize_current() {
+    let value = jsxn::parse(r#"{ "list": [<br />] }"#).unwrap();
+    assert_eq!(
+        serde_json::to_value(profile::profiled(&value, Profile::Current)).unwrap(),
+        serde_json::to_value(&value).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(profile::profiled(&value, Profile::React)).unwrap(),
+        json!({
+            "list": [{
+                "$$typeof": "react.element",
+                "type": "br",
+                "key": null,
+                "ref": null,
+                "props": {},
+            }],
+        })
+    );
+}
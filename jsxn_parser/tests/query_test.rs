@@ -0,0 +1,180 @@
+use ::jsxn::{
+    json::JsonValue,
+    jsxn::{self, JsxnValue},
+    query::{self, Node, Selector, SelectorError, Step},
+};
+use pretty_assertions::assert_eq;
+
+fn parse(input: &str) -> JsxnValue {
+    jsxn::parse(input).unwrap()
+}
+
+/// The element types, texts and JSON values of the matching nodes
+fn describe(jsxn: &JsxnValue, selector: &str) -> Vec<String> {
+    query::select(jsxn, selector)
+        .unwrap()
+        .into_iter()
+        .map(|node| match node {
+            Node::Element(element) => match element.props().get("id") {
+                Some(id) => format!("<{} {:?}>", element.r#type(), id),
+                None => format!("<{}>", element.r#type()),
+            },
+            Node::Fragment(_) => String::from("<>"),
+            Node::Text(text) => text.to_string(),
+            Node::Expression(_) => String::from("{}"),
+            Node::Variable(path) => format!("${}", path.join(".")),
+            Node::Error(_) => String::from("error"),
+            Node::Json(json) => format!("{:?}", json),
+        })
+        .collect()
+}
+
+const PAGE: &str = r#"<Page meta={{"title": "Home", "og": {"title": "Welcome"}}}>
+  <Nav>
+    <Link id="a" href="/" />
+    <Link id="b" />
+    {[<Link id="c" href="/about" />, {"title": "Docs"}]}
+    <Menu><Link id="d" href="/more" /></Menu>
+  </Nav>
+  <Link id="e" href="/out" />
+</Page>"#;
+
+#[test]
+fn select_descendants() {
+    let page = parse(PAGE);
+    assert_eq!(
+        describe(&page, "Nav Link[href]"),
+        [
+            r#"<Link JsonValue(Str("a"))>"#,
+            r#"<Link JsonValue(Str("c"))>"#,
+            r#"<Link JsonValue(Str("d"))>"#,
+        ]
+    );
+    assert_eq!(
+        describe(&page, "Page > Link, Menu > *"),
+        [
+            r#"<Link JsonValue(Str("d"))>"#,
+            r#"<Link JsonValue(Str("e"))>"#
+        ]
+    );
+    assert_eq!(describe(&page, "Nav > Link[href=\"/\"]").len(), 1);
+    assert!(describe(&page, "Nav > Menu > Page").is_empty());
+}
+
+#[test]
+fn select_json_through_expressions() {
+    let page = parse(PAGE);
+    assert_eq!(
+        describe(&page, ":expression :key(title)"),
+        [r#"Str("Welcome")"#, r#"Str("Home")"#, r#"Str("Docs")"#]
+    );
+    assert_eq!(
+        describe(&page, "Page > :expression > :object > :key(title)"),
+        [r#"Str("Home")"#]
+    );
+    assert_eq!(
+        describe(&page, "Nav :array > :nth-child(2)[title=Docs]").len(),
+        1
+    );
+    assert_eq!(describe(&page, ":object [href]").len(), 0);
+}
+
+#[test]
+fn select_nth_child() {
+    let list = parse("<ul><li>1</li><li>2</li><li>3</li><li>4</li><li>5</li></ul>");
+    assert_eq!(describe(&list, "li:nth-child(2) :text"), ["2"]);
+    assert_eq!(describe(&list, "li:nth-child(odd) :text"), ["1", "3", "5"]);
+    assert_eq!(describe(&list, "li:nth-child(even) :text"), ["2", "4"]);
+    assert_eq!(describe(&list, "li:nth-child(3n+2) :text"), ["2", "5"]);
+    assert_eq!(describe(&list, "li:nth-child(-n + 2) :text"), ["1", "2"]);
+    assert_eq!(describe(&list, "ul:nth-child(1)").len(), 0);
+    assert_eq!(
+        describe(&list, "li:nth-child(n-9223372036854775808)").len(),
+        0
+    );
+    assert_eq!(
+        describe(&list, "li:nth-child(-9223372036854775808n+1) :text"),
+        ["1"]
+    );
+}
+
+#[test]
+fn select_in_deep_trees() {
+    let depth = 150;
+    let chain = parse(&format!(
+        "{}<b />{}",
+        "<a>".repeat(depth),
+        "</a>".repeat(depth)
+    ));
+    assert!(describe(&chain, "b a a a a").is_empty());
+    assert_eq!(describe(&chain, "a a a a a").len(), depth - 4);
+    assert_eq!(describe(&chain, "a > a a > a b"), ["<b>"]);
+    assert_eq!(describe(&chain, "a > b:nth-child(1)"), ["<b>"]);
+}
+
+#[test]
+fn select_prop_values() {
+    let form = parse(r#"<form><input disabled size={3} name={"q"} /><input size="3" /></form>"#);
+    assert_eq!(describe(&form, "[disabled=true]").len(), 1);
+    assert_eq!(describe(&form, "[size=3]").len(), 2);
+    assert_eq!(describe(&form, "input[name='q'][size=\"3.0\"]").len(), 1);
+    assert_eq!(
+        describe(&form, "[name] > :expression > *"),
+        [r#"Str("q")"#, r#"Num(3.0)"#]
+    );
+}
+
+#[test]
+fn select_paths_and_get() {
+    let page = parse(PAGE);
+    let selector: Selector = "Nav :key(title)".parse().unwrap();
+    let paths = selector.select_paths(&page);
+    assert_eq!(
+        paths,
+        [vec![
+            Step::Child(0),
+            Step::Child(2),
+            Step::Expression,
+            Step::Item(1),
+            Step::Key(String::from("title")),
+        ]]
+    );
+    assert_eq!(
+        query::get(&page, &paths[0]),
+        Some(Node::Json(&JsonValue::Str(String::from("Docs"))))
+    );
+    assert!(selector.matches(&page, &paths[0]));
+    assert!(!selector.matches(&page, &paths[0][..4]));
+    assert_eq!(query::get(&page, &[Step::Child(7)]), None);
+    assert_eq!(
+        query::get(
+            &page,
+            &[
+                Step::Prop(String::from("meta")),
+                Step::Key(String::from("x"))
+            ]
+        ),
+        None
+    );
+}
+
+#[test]
+fn selector_errors() {
+    let error = |selector: &str| Selector::parse(selector).unwrap_err();
+    assert_eq!(
+        error(""),
+        SelectorError {
+            offset: 0,
+            message: String::from("expected a selector"),
+        }
+    );
+    assert_eq!(error("a >").offset, 3);
+    assert_eq!(error("a[href").message, "expected `]`");
+    assert_eq!(error("a:hover").message, "unknown pseudo-class `:hover`");
+    assert_eq!(
+        error("a:nth-child(2x)").message,
+        "invalid `:nth-child` argument"
+    );
+    assert_eq!(error("[title='x]").message, "unterminated string");
+    assert_eq!(error("a, ").to_string(), "expected a selector at offset 3");
+}
@@ -0,0 +1,199 @@
+use ::jsxn::{
+    error::{Error, Location, Span},
+    json, jsx, jsxn,
+    limits::ParseLimits,
+    options::ParserOptions,
+};
+use pretty_assertions::assert_eq;
+use std::collections::BTreeMap;
+
+fn span(start: usize, end: usize) -> Span {
+    Span {
+        start: Location {
+            offset: start,
+            line: 1,
+            column: start + 1,
+        },
+        end: Location {
+            offset: end,
+            line: 1,
+            column: end + 1,
+        },
+    }
+}
+
+fn element(r#type: &str, children: Vec<jsx::JsxValue>) -> jsx::JsxValue {
+    jsx::JsxValue::JsxElement(jsx::JsxElement::new(
+        String::from(r#type),
+        BTreeMap::new(),
+        children,
+    ))
+}
+
+fn text(text: &str) -> jsx::JsxValue {
+    jsx::JsxValue::JsonValue(json::JsonValue::Str(String::from(text)))
+}
+
+#[test]
+fn recover_valid_input() {
+    let input = r#"<a b="c">{[1, { "d": null }]} text <></></a>"#;
+    assert_eq!(
+        jsxn::parse_recovering(input),
+        (jsxn::parse(input).unwrap(), vec![])
+    );
+}
+
+#[test]
+fn recover_unclosed_tags() {
+    // A child that is never closed is read as text, like the tree parser
+    // does, leaving the element around it unclosed
+    let (value, diagnostics) = jsxn::parse_recovering("<List><Item>eggs</Item><Item>milk");
+    assert_eq!(
+        value,
+        jsxn::JsxnValue::JsxValue(element(
+            "List",
+            vec![element("Item", vec![text("eggs")]), text("<Item>milk")]
+        ))
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].location().offset, 33);
+
+    let (value, diagnostics) = jsxn::parse_recovering("<a>text");
+    assert_eq!(
+        value,
+        jsxn::JsxnValue::JsxValue(element("a", vec![text("text")]))
+    );
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn recover_bad_props_and_expressions() {
+    let (value, diagnostics) = jsxn::parse_recovering("<a b=1 @ c>{ @ }</x>{2</a>");
+    assert_eq!(
+        value,
+        jsxn::JsxnValue::JsxValue(jsx::JsxValue::JsxElement(jsx::JsxElement::new(
+            String::from("a"),
+            {
+                let mut props = BTreeMap::new();
+                props.insert(String::from("b"), jsx::JsxValue::Error(span(5, 6)));
+                props.insert(
+                    String::from("c"),
+                    jsx::JsxValue::JsonValue(json::JsonValue::Boolean(true)),
+                );
+                props
+            },
+            vec![
+                jsx::JsxValue::JsxExpression(Box::new(jsx::JsxValue::Error(span(13, 15)))),
+                jsx::JsxValue::Error(span(16, 20)),
+                jsx::JsxValue::JsxExpression(Box::new(jsx::JsxValue::JsonValue(
+                    json::JsonValue::Num(2.0)
+                ))),
+            ],
+        )))
+    );
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|error| error.location().offset)
+            .collect::<Vec<_>>(),
+        vec![5, 7, 13, 16, 22]
+    );
+}
+
+#[test]
+fn recover_children() {
+    let (value, diagnostics) = jsxn::parse_recovering("<div><!-- c --><p>ok</p></div>");
+    assert_eq!(
+        value,
+        jsxn::JsxnValue::JsxValue(element(
+            "div",
+            vec![text("<!-- c -->"), element("p", vec![text("ok")])]
+        ))
+    );
+    assert_eq!(diagnostics, vec![]);
+
+    let (value, diagnostics) = jsxn::parse_recovering("<a>{1 2}<b></b></a>");
+    assert_eq!(
+        value,
+        jsxn::JsxnValue::JsxValue(element(
+            "a",
+            vec![
+                jsx::JsxValue::JsxExpression(Box::new(jsx::JsxValue::JsonValue(
+                    json::JsonValue::Num(1.0)
+                ))),
+                jsx::JsxValue::Error(span(6, 7)),
+                element("b", vec![]),
+            ]
+        ))
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].location().offset, 6);
+
+    let (value, diagnostics) = jsxn::parse_recovering("<a b={1 2} c />");
+    assert_eq!(value, jsxn::parse("<a b={1} c />").unwrap());
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn recover_json() {
+    let (value, diagnostics) = jsxn::parse_recovering(r#"{"a": , "b" 2, "c": [1, 2 3, 4}"#);
+    assert_eq!(
+        value,
+        jsxn::JsxnValue::JsonValue(json::JsonValue::Object({
+            let mut object = BTreeMap::new();
+            object.insert(
+                String::from("a"),
+                json::JsonValue::JsxValue(Box::new(jsx::JsxValue::Error(span(6, 6)))),
+            );
+            object.insert(
+                String::from("c"),
+                json::JsonValue::Array(vec![
+                    json::JsonValue::Num(1.0),
+                    json::JsonValue::Num(2.0),
+                    json::JsonValue::JsxValue(Box::new(jsx::JsxValue::Error(span(26, 27)))),
+                    json::JsonValue::Num(4.0),
+                ]),
+            );
+            object
+        }))
+    );
+    assert_eq!(diagnostics.len(), 4);
+
+    let (value, diagnostics) = jsxn::parse_recovering("@");
+    assert_eq!(
+        value,
+        jsxn::JsxnValue::JsxValue(jsx::JsxValue::Error(span(0, 1)))
+    );
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn recovery_stops_at_limits() {
+    let (value, diagnostics) = jsxn::parse_recovering_with_options(
+        "[[1, 2], [3, 4]]",
+        &ParserOptions {
+            limits: ParseLimits {
+                max_nodes: Some(3),
+                ..ParseLimits::default()
+            },
+            ..ParserOptions::STRICT
+        },
+    );
+    assert_eq!(
+        value,
+        jsxn::JsxnValue::JsonValue(json::JsonValue::Array(vec![json::JsonValue::Array(vec![
+            json::JsonValue::Num(1.0)
+        ])]))
+    );
+    assert_eq!(
+        diagnostics,
+        vec![Error::TooManyNodes {
+            location: Location {
+                offset: 5,
+                line: 1,
+                column: 6,
+            },
+            limit: 3,
+        }]
+    );
+}
@@ -0,0 +1,191 @@
+use ::jsxn::{
+    format,
+    json::JsonValue,
+    jsxn::{self, JsxnValue},
+    ser::{self, Element, Fragment},
+};
+use pretty_assertions::assert_eq;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+fn parse(input: &str) -> JsxnValue {
+    jsxn::parse(input).unwrap()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Status {
+    Active,
+    Banned { reason: String },
+}
+
+#[derive(Serialize)]
+struct User {
+    name: String,
+    age: u32,
+    tags: Vec<&'static str>,
+    nickname: Option<String>,
+    status: Status,
+    scores: BTreeMap<u8, f64>,
+}
+
+#[test]
+fn serialize_json() {
+    let mut scores = BTreeMap::new();
+    scores.insert(1, 0.5);
+    let users = vec![
+        User {
+            name: String::from("Ada \"the\" first\n"),
+            age: 36,
+            tags: vec!["math"],
+            nickname: None,
+            status: Status::Active,
+            scores,
+        },
+        User {
+            name: String::from("Bob"),
+            age: 7,
+            tags: vec![],
+            nickname: Some(String::from("B")),
+            status: Status::Banned {
+                reason: String::from("spam"),
+            },
+            scores: BTreeMap::new(),
+        },
+    ];
+    assert_eq!(
+        JsxnValue::JsonValue(ser::to_value(&users).unwrap()),
+        parse(
+            r#"[
+                {
+                    "name": "Ada \"the\" first\n",
+                    "age": 36,
+                    "tags": ["math"],
+                    "nickname": null,
+                    "status": "active",
+                    "scores": { "1": 0.5 }
+                },
+                {
+                    "name": "Bob",
+                    "age": 7,
+                    "tags": [],
+                    "nickname": "B",
+                    "status": { "banned": { "reason": "spam" } },
+                    "scores": {}
+                }
+            ]"#
+        )
+    );
+}
+
+#[test]
+fn serialize_elements() {
+    #[derive(Serialize)]
+    struct LinkProps<'a> {
+        href: &'a str,
+        title: Option<&'a str>,
+        external: bool,
+        rank: u8,
+    }
+
+    let link = |href, text| {
+        Element::new(
+            "a",
+            LinkProps {
+                href,
+                title: None,
+                external: true,
+                rank: 1,
+            },
+            text,
+        )
+    };
+    let nav = Element::new(
+        "nav",
+        (),
+        vec![link("/", "Home"), link("/about", "About <us>")],
+    );
+    assert_eq!(
+        ser::to_jsxn_value(&nav).unwrap(),
+        parse(
+            r#"
+            <nav>
+                <a href="/" external rank={1}>Home</a>
+                <a href="/about" external rank={1}>{"About <us>"}</a>
+            </nav>
+            "#
+        )
+    );
+}
+
+#[test]
+fn serialize_elements_in_json() {
+    type Heading = Element<(), &'static str>;
+
+    #[derive(Serialize)]
+    struct Page {
+        title: &'static str,
+        body: Element<(), (Heading, u32, Option<u32>)>,
+    }
+
+    let page = Page {
+        title: "Home",
+        body: Element::new("main", (), (Element::new("h1", (), "Welcome"), 42, None)),
+    };
+    let value = ser::to_jsxn_value(&page).unwrap();
+    assert_eq!(
+        value,
+        parse(r#"{"title": "Home", "body": <main><h1>Welcome</h1>{42}</main>}"#)
+    );
+    assert_eq!(
+        format::format(&value),
+        "{\n  \"body\": <main><h1>Welcome</h1>{42}</main>,\n  \"title\": \"Home\"\n}\n"
+    );
+}
+
+#[test]
+fn serialize_fragments() {
+    assert_eq!(
+        ser::to_jsxn_value(&Fragment(("one", Element::new("br", (), ()), "  two"))).unwrap(),
+        parse(r#"<>one<br />{"  two"}</>"#)
+    );
+}
+
+#[test]
+fn serialize_errors() {
+    assert_eq!(
+        ser::to_value(&Element::new("my-element", (), ()))
+            .unwrap_err()
+            .to_string(),
+        "element types must be alphanumeric strings"
+    );
+    let mut props = BTreeMap::new();
+    props.insert("-label", "x");
+    assert_eq!(
+        ser::to_value(&Element::new("div", props, ()))
+            .unwrap_err()
+            .to_string(),
+        "prop name `-label` is not alphanumeric with hyphens"
+    );
+    assert_eq!(
+        ser::to_value(&Element::new("div", 1, ()))
+            .unwrap_err()
+            .to_string(),
+        "element props must be an object"
+    );
+    let mut keys = BTreeMap::new();
+    keys.insert(vec![1], 1);
+    assert_eq!(
+        ser::to_value(&keys).unwrap_err().to_string(),
+        "object keys must be strings"
+    );
+}
+
+#[test]
+fn other_serializers_see_tuples() {
+    assert_eq!(
+        serde_json::to_string(&Element::new("p", (), vec!["a", "b"])).unwrap(),
+        r#"["p",null,["a","b"]]"#
+    );
+    assert_eq!(ser::to_value(&()).unwrap(), JsonValue::Null);
+}
@@ -0,0 +1,204 @@
+use ::jsxn::{
+    error::{Error, Location},
+    json, jsx, jsxn,
+    limits::ParseLimits,
+    options::ParserOptions,
+    stream::{Stream, StreamRoot},
+};
+use pretty_assertions::assert_eq;
+use std::{
+    collections::BTreeMap,
+    io::{self, Read},
+};
+
+/// A reader that returns one byte at a time
+struct Trickle<'a>(&'a [u8]);
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.0.split_first() {
+            Some((byte, rest)) if !buf.is_empty() => {
+                buf[0] = *byte;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+const VALID_ARRAY: &str = r#"
+    [
+        { "name": "Ünïcödé 👍" },
+        12.5,
+        <Element prop="value" />
+    ]
+"#;
+
+const VALID_JSX_ELEMENT: &str = r#"
+    <List title="Groceries">
+        <Item checked />
+        {["eggs", "milk"]}
+        Remember <b>the</b> bags < 2.
+    </List>
+"#;
+
+#[test]
+fn stream_array_items() {
+    let mut stream = Stream::new(Trickle(VALID_ARRAY.as_bytes()));
+    assert_eq!(
+        stream.next(),
+        Some(Ok(jsxn::JsxnValue::JsonValue(json::JsonValue::Object({
+            let mut object = BTreeMap::new();
+            object.insert(
+                String::from("name"),
+                json::JsonValue::Str(String::from("Ünïcödé 👍")),
+            );
+            object
+        }))))
+    );
+    assert_eq!(stream.root(), Some(&StreamRoot::Array));
+    assert_eq!(
+        stream.next(),
+        Some(Ok(jsxn::JsxnValue::JsonValue(json::JsonValue::Num(12.5))))
+    );
+    assert_eq!(
+        stream.next(),
+        Some(Ok(jsxn::JsxnValue::JsonValue(json::JsonValue::JsxValue(
+            Box::new(jsx::JsxValue::JsxElement(jsx::JsxElement::new(
+                String::from("Element"),
+                {
+                    let mut props = BTreeMap::new();
+                    props.insert(
+                        String::from("prop"),
+                        jsx::JsxValue::JsonValue(json::JsonValue::Str(String::from("value"))),
+                    );
+                    props
+                },
+                vec![],
+            )))
+        ))))
+    );
+    assert_eq!(stream.next(), None);
+}
+
+#[test]
+fn stream_element_children() {
+    let children = Stream::new(Trickle(VALID_JSX_ELEMENT.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    match jsxn::parse(VALID_JSX_ELEMENT) {
+        Ok(jsxn::JsxnValue::JsxValue(jsx::JsxValue::JsxElement(element))) => assert_eq!(
+            element,
+            jsx::JsxElement::new(
+                String::from("List"),
+                {
+                    let mut props = BTreeMap::new();
+                    props.insert(
+                        String::from("title"),
+                        jsx::JsxValue::JsonValue(json::JsonValue::Str(String::from("Groceries"))),
+                    );
+                    props
+                },
+                children
+                    .into_iter()
+                    .map(|child| match child {
+                        jsxn::JsxnValue::JsxValue(jsx) => jsx,
+                        jsxn::JsxnValue::JsonValue(json) => jsx::JsxValue::JsonValue(json),
+                    })
+                    .collect(),
+            )
+        ),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn stream_other_roots_as_one_item() {
+    let mut stream = Stream::new(Trickle(br#" { "key": "value" } "#));
+    assert!(stream.next().unwrap().is_ok());
+    assert_eq!(stream.root(), Some(&StreamRoot::Document));
+    assert_eq!(stream.next(), None);
+
+    let mut stream = Stream::new(Trickle(b"<Element />"));
+    assert_eq!(stream.next(), None);
+    assert_eq!(
+        stream.root(),
+        Some(&StreamRoot::Element {
+            r#type: String::from("Element"),
+            props: BTreeMap::new(),
+        })
+    );
+}
+
+#[test]
+fn stream_errors_without_reading_further() {
+    let mut input = b"[1, x, 2".chain(io::repeat(b' ').take(16 << 20));
+    let mut stream = Stream::new(&mut input);
+    assert!(stream.next().unwrap().is_ok());
+    assert_eq!(
+        stream.next().unwrap().unwrap_err().to_string(),
+        "expected ']' in json array at line 1, column 5"
+    );
+    assert!(input.into_inner().1.limit() > 15 << 20);
+}
+
+#[test]
+fn stream_errors() {
+    let mut stream = Stream::new(Trickle(b"[1, 2,]"));
+    assert!(stream.next().unwrap().is_ok());
+    assert!(stream.next().unwrap().is_ok());
+    assert_eq!(
+        stream.next(),
+        Some(Err(Error::Syntax {
+            location: Location {
+                offset: 5,
+                line: 1,
+                column: 6,
+            },
+            message: String::from("expected ']' in json array"),
+        }))
+    );
+    assert_eq!(stream.next(), None);
+
+    let items = Stream::with_options(Trickle(b"[1, 2,]"), ParserOptions::RELAXED)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(items.len(), 2);
+
+    let mut stream = Stream::new(Trickle(b"[1]\n [2]"));
+    assert!(stream.next().unwrap().is_ok());
+    assert_eq!(
+        stream.next().unwrap().unwrap_err().location(),
+        Location {
+            offset: 5,
+            line: 2,
+            column: 2,
+        }
+    );
+
+    let mut stream = Stream::new(Trickle(b"[\"\xff\"]"));
+    assert!(stream.next().unwrap().is_err());
+
+    let mut stream = Stream::with_options(
+        Trickle(VALID_ARRAY.as_bytes()),
+        ParserOptions {
+            limits: ParseLimits {
+                max_input_bytes: Some(20),
+                ..ParseLimits::default()
+            },
+            ..ParserOptions::STRICT
+        },
+    );
+    assert_eq!(
+        stream.next(),
+        Some(Err(Error::InputTooLarge {
+            location: Location {
+                offset: 20,
+                line: 3,
+                column: 14,
+            },
+            limit: 20,
+        }))
+    );
+}
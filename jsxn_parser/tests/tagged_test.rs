@@ -0,0 +1,104 @@
+use ::jsxn::{
+    json::JsonValue,
+    jsx::JsxValue,
+    jsxn::{self, JsxnValue},
+    options::ParserOptions,
+    tagged,
+};
+use pretty_assertions::assert_eq;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const VALID_JSXN: &str = r#"
+    <List title="Groceries\n" count={1} items={[<Item />, "eggs"]}>
+        <Item key="eggs" checked>eggs &amp; ham</Item>
+        <>{["milk", null, { "type": "div", "props": {}, "children": [] }]}</>
+        {$user.name}
+    </List>
+"#;
+
+fn round_trip(value: &JsxnValue) -> JsxnValue {
+    let json = serde_json::to_string(&tagged::tagged(value)).unwrap();
+    tagged::deserialize(&mut serde_json::Deserializer::from_str(&json)).unwrap()
+}
+
+#[test]
+fn serialize_tagged() {
+    let value = jsxn::parse(r#"<p class="a">{[1, <br />]}</p>"#).unwrap();
+    assert_eq!(
+        serde_json::to_value(tagged::tagged(&value)).unwrap(),
+        json!({
+            "kind": "element",
+            "type": "p",
+            "props": { "class": { "kind": "string", "value": "a" } },
+            "children": [{
+                "kind": "expression",
+                "value": {
+                    "kind": "array",
+                    "items": [
+                        { "kind": "number", "value": 1.0 },
+                        { "kind": "element", "type": "br", "props": {}, "children": [] },
+                    ],
+                },
+            }],
+        })
+    );
+}
+
+#[test]
+fn round_trip_tagged() {
+    let value = jsxn::parse_with_options(VALID_JSXN, &ParserOptions::RELAXED).unwrap();
+    assert_eq!(round_trip(&value), value);
+
+    let value = jsxn::parse_with_options(
+        r#"{ "a": [NaN, -Infinity, "A"], "b": <br /> }"#,
+        &ParserOptions::RELAXED,
+    )
+    .unwrap();
+    assert_eq!(
+        serde_json::to_string(&tagged::tagged(&round_trip(&value))).unwrap(),
+        serde_json::to_string(&tagged::tagged(&value)).unwrap()
+    );
+
+    let (value, errors) = jsxn::parse_recovering("<div><p>text");
+    assert!(!errors.is_empty());
+    assert_eq!(round_trip(&value), value);
+}
+
+#[test]
+fn round_trip_nested_values() {
+    let values = vec![
+        JsxnValue::JsxValue(JsxValue::JsonValue(JsonValue::Null)),
+        JsxnValue::JsonValue(JsonValue::JsxValue(Box::new(JsxValue::JsonValue(
+            JsonValue::Str(String::from("text")),
+        )))),
+        JsxnValue::JsonValue(JsonValue::Array(vec![JsonValue::JsxValue(Box::new(
+            JsxValue::JsonValue(JsonValue::Boolean(true)),
+        ))])),
+        JsxnValue::JsxValue(JsxValue::JsxExpression(Box::new(JsxValue::JsonValue(
+            JsonValue::JsxValue(Box::new(JsxValue::Variable(vec![String::from("x")]))),
+        )))),
+    ];
+    for value in values {
+        assert_eq!(round_trip(&value), value);
+    }
+}
+
+#[test]
+fn serde_with_tagged() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Document {
+        #[serde(with = "tagged")]
+        body: JsxnValue,
+    }
+
+    let document = Document {
+        body: jsxn::parse("<><b>bold</b></>").unwrap(),
+    };
+    let json = serde_json::to_string(&document).unwrap();
+    assert_eq!(serde_json::from_str::<Document>(&json).unwrap(), document);
+    assert!(
+        serde_json::from_str::<Document>(r#"{ "body": { "kind": "number", "value": "1" } }"#)
+            .is_err()
+    );
+}
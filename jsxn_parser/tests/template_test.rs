@@ -0,0 +1,72 @@
+use ::jsxn::{
+    events::Parser,
+    html, json, jsx, jsxn,
+    options::ParserOptions,
+    template::{self, EvaluateError},
+};
+use pretty_assertions::assert_eq;
+
+const TEMPLATE: &str = r#"
+    <p title={$user.name}>
+        Hello, {$user.name}! You have {$messages.length} messages.
+        {$messages.items.0}{ $badge }
+    </p>
+"#;
+
+const OPTIONS: ParserOptions = ParserOptions {
+    variables: true,
+    ..ParserOptions::STRICT
+};
+
+fn context() -> json::JsonValue {
+    match jsxn::parse(
+        r#"{
+            "user": { "name": "Ada" },
+            "messages": { "length": 2, "items": [<b>New</b>, "Old"] },
+            "badge": null
+        }"#,
+    ) {
+        Ok(jsxn::JsxnValue::JsonValue(json)) => json,
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn parse_variables() {
+    let template = jsxn::parse_with_options("<a>{$user.name}</a>", &OPTIONS).unwrap();
+    assert_eq!(
+        template,
+        jsxn::JsxnValue::JsxValue(jsx::JsxValue::JsxElement(jsx::JsxElement::new(
+            String::from("a"),
+            Default::default(),
+            vec![jsx::JsxValue::JsxExpression(Box::new(
+                jsx::JsxValue::Variable(vec![String::from("user"), String::from("name")])
+            ))],
+        )))
+    );
+    assert_eq!(
+        Parser::with_options(TEMPLATE, OPTIONS).build(),
+        jsxn::parse_with_options(TEMPLATE, &OPTIONS)
+    );
+    assert!(jsxn::parse(TEMPLATE).is_err());
+    assert!(jsxn::parse_with_options("<a>{$}</a>", &OPTIONS).is_err());
+    assert!(jsxn::parse_with_options("<a>{$a.}</a>", &OPTIONS).is_err());
+}
+
+#[test]
+fn evaluate_variables() {
+    let template = jsxn::parse_with_options(TEMPLATE, &OPTIONS).unwrap();
+    let evaluated = template::evaluate_jsxn(&template, &context()).unwrap();
+    assert_eq!(
+        html::render_jsxn(&evaluated),
+        r#"<p title="Ada">Hello,Ada! You have2messages.<b>New</b></p>"#
+    );
+
+    let template = jsxn::parse_with_options("<a>{$user.email}</a>", &OPTIONS).unwrap();
+    assert_eq!(
+        template::evaluate_jsxn(&template, &context()),
+        Err(EvaluateError::UndefinedVariable {
+            path: vec![String::from("user"), String::from("email")],
+        })
+    );
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "serde_json")]
+
+use ::jsxn::{
+    json::JsonValue,
+    jsxn::{self, JsxnValue},
+    options::ParserOptions,
+    profile::Profile,
+    value::{self, JsxShape, ValueError},
+};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use std::convert::TryFrom;
+
+fn parse(input: &str) -> JsxnValue {
+    jsxn::parse(input).unwrap()
+}
+
+#[test]
+fn json_round_trip() {
+    let value = json!({
+        "text": "line\n\"quoted\" \u{e9}",
+        "numbers": [0, -1, 1.5, 9007199254740992u64],
+        "flags": [true, false, null],
+        "nested": { "empty": {}, "list": [] },
+        "key \"with\"\tescapes": 1
+    });
+    let json = JsonValue::try_from(value.clone()).unwrap();
+    assert_eq!(
+        json,
+        match parse(&value.to_string()) {
+            JsxnValue::JsonValue(json) => json,
+            jsx => panic!("not JSON: {:?}", jsx),
+        }
+    );
+    assert_eq!(Value::try_from(&json).unwrap(), value);
+}
+
+#[test]
+fn inexact_numbers() {
+    assert_eq!(
+        JsonValue::try_from(json!([9007199254740993u64])),
+        Err(ValueError::InexactNumber(9007199254740993u64.into()))
+    );
+    assert_eq!(
+        JsonValue::try_from(json!(u64::MAX)),
+        Err(ValueError::InexactNumber(u64::MAX.into()))
+    );
+    assert_eq!(
+        JsonValue::try_from(json!(i64::MIN)),
+        Ok(JsonValue::Num(i64::MIN as f64))
+    );
+    let infinite = jsxn::parse_with_options("[Infinity]", &ParserOptions::RELAXED).unwrap();
+    assert_eq!(
+        value::to_serde_json(&infinite, JsxShape::Error),
+        Err(ValueError::NonFiniteNumber(f64::INFINITY))
+    );
+}
+
+#[test]
+fn jsx_shapes() {
+    let jsxn = parse(r#"{"page": <a href="/" key={1}>Home</a>}"#);
+    assert_eq!(
+        value::to_serde_json(&jsxn, JsxShape::Error),
+        Err(ValueError::Jsx)
+    );
+    assert_eq!(
+        value::to_serde_json(&jsxn, JsxShape::Profile(Profile::React)).unwrap(),
+        json!({
+            "page": {
+                "$$typeof": "react.element",
+                "type": "a",
+                "key": "1",
+                "ref": null,
+                "props": { "href": "/", "children": "Home" }
+            }
+        })
+    );
+    assert_eq!(
+        value::to_serde_json(&jsxn, JsxShape::Profile(Profile::Jsxn)).unwrap(),
+        json!({
+            "page": {
+                "type": "a",
+                "props": { "href": "/", "key": 1.0, "children": "Home" }
+            }
+        })
+    );
+}
+
+#[test]
+fn tagged_round_trip() {
+    let jsxn = parse(r#"<p title="a \"b\"">{[1, "two", <br />]}</p>"#);
+    let value = value::to_serde_json(&jsxn, JsxShape::Tagged).unwrap();
+    assert_eq!(value["kind"], "element");
+    assert_eq!(value::from_tagged(value).unwrap(), jsxn);
+}
+
+#[test]
+fn escapes_in_every_shape() {
+    let json = parse(r#"{"k\"ey": ["a\"b"]}"#);
+    assert_eq!(
+        value::to_serde_json(&json, JsxShape::Error).unwrap(),
+        json!({ "k\"ey": ["a\"b"] })
+    );
+    let jsxn = parse(r#"{"k\"ey": <p title="a\"b" data={{"k\"ey": "a\"b"}}>a\"b</p>}"#);
+    let shape = |shape| value::to_serde_json(&jsxn, shape).unwrap();
+    assert_eq!(
+        shape(JsxShape::Profile(Profile::Current)),
+        json!({
+            "k\"ey": {
+                "type": "p",
+                "props": { "title": "a\"b", "data": { "k\"ey": "a\"b" } },
+                "children": ["a\\\"b"]
+            }
+        })
+    );
+    assert_eq!(
+        shape(JsxShape::Profile(Profile::React)),
+        json!({
+            "k\"ey": {
+                "$$typeof": "react.element",
+                "type": "p",
+                "key": null,
+                "ref": null,
+                "props": {
+                    "title": "a\"b",
+                    "data": { "k\"ey": "a\"b" },
+                    "children": "a\\\"b"
+                }
+            }
+        })
+    );
+    assert_eq!(
+        shape(JsxShape::Profile(Profile::Jsxn)),
+        json!({
+            "k\"ey": {
+                "type": "p",
+                "props": {
+                    "title": "a\"b",
+                    "data": { "k\"ey": "a\"b" },
+                    "children": "a\\\"b"
+                }
+            }
+        })
+    );
+    let tagged = shape(JsxShape::Tagged);
+    let element = &tagged["entries"]["k\"ey"];
+    assert_eq!(element["props"]["title"]["value"], "a\"b");
+    assert_eq!(
+        element["props"]["data"]["value"]["entries"]["k\"ey"]["value"],
+        "a\"b"
+    );
+    assert_eq!(element["children"][0]["value"], "a\\\"b");
+    assert_eq!(value::from_tagged(tagged).unwrap(), jsxn);
+}
@@ -0,0 +1,182 @@
+use ::jsxn::{
+    format,
+    json::JsonValue,
+    jsx::{JsxElement, JsxValue},
+    jsxn::{self, JsxnValue},
+    visit::{self, Fold, Visit, VisitMut},
+};
+use pretty_assertions::assert_eq;
+
+fn parse(input: &str) -> JsxnValue {
+    jsxn::parse(input).unwrap()
+}
+
+#[derive(Default)]
+struct Collect<'a> {
+    nodes: Vec<String>,
+    texts: Vec<&'a str>,
+}
+
+impl<'a> Visit<'a> for Collect<'a> {
+    fn visit_element(&mut self, element: &'a JsxElement) {
+        self.nodes.push(format!("<{}>", element.r#type()));
+        visit::visit_element(self, element);
+    }
+
+    fn visit_prop(&mut self, name: &'a str, value: &'a JsxValue) {
+        self.nodes.push(format!("{}=", name));
+        visit::visit_prop(self, name, value);
+    }
+
+    fn visit_text(&mut self, text: &'a str) {
+        self.texts.push(text);
+    }
+
+    fn visit_expression(&mut self, jsx: &'a JsxValue) {
+        self.nodes.push(String::from("{}"));
+        visit::visit_expression(self, jsx);
+    }
+
+    fn visit_object_entry(&mut self, key: &'a str, value: &'a JsonValue) {
+        self.nodes.push(format!("{}:", key));
+        visit::visit_object_entry(self, key, value);
+    }
+
+    fn visit_array_item(&mut self, index: usize, item: &'a JsonValue) {
+        self.nodes.push(format!("[{}]", index));
+        visit::visit_array_item(self, index, item);
+    }
+
+    fn visit_string(&mut self, string: &'a str) {
+        self.nodes.push(format!("{:?}", string));
+    }
+}
+
+#[test]
+fn visit_every_node_kind() {
+    let tree = parse(r#"<Nav id="top">Home<Link to={{"items": [1, <b>x</b>]}} />{"y"}</Nav>"#);
+    let mut collect = Collect::default();
+    collect.visit_jsxn(&tree);
+
+    assert_eq!(
+        collect.nodes,
+        [
+            "<Nav>", "id=", "\"top\"", "<Link>", "to=", "{}", "items:", "[0]", "[1]", "<b>", "{}",
+            "\"y\""
+        ]
+    );
+    assert_eq!(collect.texts, ["Home", "x"]);
+}
+
+#[test]
+fn visit_lints_without_walking_below() {
+    struct Images(usize);
+
+    impl<'a> Visit<'a> for Images {
+        fn visit_element(&mut self, element: &'a JsxElement) {
+            if element.r#type() == "img" && !element.props().contains_key("alt") {
+                self.0 += 1;
+            }
+            if element.r#type() != "svg" {
+                visit::visit_element(self, element);
+            }
+        }
+    }
+
+    let tree = parse(r#"<div><img /><img alt="" /><svg><img /></svg>{[<img />]}</div>"#);
+    let mut images = Images(0);
+    images.visit_jsxn(&tree);
+    assert_eq!(images.0, 2);
+}
+
+#[test]
+fn visit_mut_in_place() {
+    struct Shout;
+
+    impl VisitMut for Shout {
+        fn visit_prop_mut(&mut self, name: &str, value: &mut JsxValue) {
+            if name != "id" {
+                visit::visit_prop_mut(self, name, value);
+            }
+        }
+
+        fn visit_text_mut(&mut self, text: &mut String) {
+            *text = text.to_uppercase();
+        }
+
+        fn visit_string_mut(&mut self, string: &mut String) {
+            string.push('!');
+        }
+
+        fn visit_number_mut(&mut self, number: &mut f64) {
+            *number *= 2.0;
+        }
+    }
+
+    let mut tree = parse(r#"<p id="a" title="b">hi <i>there</i>{[1, "c"]}</p>"#);
+    Shout.visit_jsxn_mut(&mut tree);
+    assert_eq!(
+        tree,
+        parse(r#"<p id="a" title="b!">HI <i>THERE</i>{[2, "c!"]}</p>"#)
+    );
+}
+
+#[test]
+fn fold_rewrites_the_tree() {
+    struct Rewrite;
+
+    impl Fold for Rewrite {
+        fn fold_element(&mut self, element: JsxElement) -> JsxValue {
+            if element.r#type() == "Br" {
+                return JsxValue::JsonValue(JsonValue::Str(String::from("-")));
+            }
+            visit::fold_element(self, element)
+        }
+
+        fn fold_prop(&mut self, name: String, value: JsxValue) -> Option<(String, JsxValue)> {
+            if name == "key" {
+                return None;
+            }
+            visit::fold_prop(self, name, value)
+        }
+
+        fn fold_object_entry(
+            &mut self,
+            key: String,
+            value: JsonValue,
+        ) -> Option<(String, JsonValue)> {
+            if key == "secret" {
+                return None;
+            }
+            visit::fold_object_entry(self, key, value)
+        }
+
+        fn fold_expression(&mut self, jsx: JsxValue) -> JsxValue {
+            // Unwrap expressions holding elements
+            match self.fold_jsx(jsx) {
+                jsx @ JsxValue::JsxElement(_) => jsx,
+                jsx => JsxValue::JsxExpression(Box::new(jsx)),
+            }
+        }
+    }
+
+    let tree = parse(
+        r#"<ul key="1"><li key="2">a<Br />b</li>{<li />}<li data={{"secret": 1, "shown": 2}} /></ul>"#,
+    );
+    assert_eq!(
+        format::format(&Rewrite.fold_jsxn(tree)),
+        format::format(&parse(
+            r#"<ul><li>a-b</li><li /><li data={{"shown": 2}} /></ul>"#
+        ))
+    );
+}
+
+#[test]
+fn fold_is_identity_by_default() {
+    struct Identity;
+
+    impl Fold for Identity {}
+
+    let source = r#"{"a": [<p x={1} y>text{"s"}<></></p>, null, true, 1.5]}"#;
+    assert_eq!(Identity.fold_jsxn(parse(source)), parse(source));
+}